@@ -50,6 +50,7 @@ impl CanRun<conf::Bmc> for Bmc {
       None => (),
       Some(ref cmd) => solver_conf = solver_conf.cmd(cmd.clone()),
     } ;
+    let solver_conf = event.time_limit(solver_conf) ;
 
     mk_solver_run!(
       solver_conf, conf.smt_log(), "bmc", event.factory(),
@@ -72,6 +73,7 @@ fn bmc<
     event, Unroller::mk(& sys, & props, solver)
     => "while creating unroller"
   ) ;
+  unroller.assume( event.assumptions() ) ;
 
   // event.log("creating manager, declaring actlits") ;
   let mut props = log_try!(
@@ -156,7 +156,8 @@ impl<T: Parse> Parse for Option<T> {
 
 
 
-trait HasSet {
+/// Configuration structures whose items can be set from their key.
+pub trait HasSet {
   /// Sets the value of a configuration item.
   fn set(& mut self, & str, & str) -> Result<(), String> ;
 }
@@ -302,6 +303,13 @@ conf!{
       None,
       val => Option::<usize>::of(val)
     ),
+    timeout (
+      Option<usize>,
+      "timeout", "<int>".to_string(),
+      "Timeout in seconds.".to_string(),
+      None,
+      val => Option::<usize>::of(val)
+    ),
     smt (
       SolverStyle,
       "smt", solver_keys(),
@@ -343,6 +351,21 @@ conf!{
       None,
       val => Option::<usize>::of(val)
     ),
+    simple_path (
+      bool,
+      "simple_path", "[on/off]".to_string(),
+      "Lazily forces the states of the step check to be pairwise distinct. \
+      Complete on finite-state systems.".to_string(),
+      false,
+      val => bool::of(val)
+    ),
+    timeout (
+      Option<usize>,
+      "timeout", "<int>".to_string(),
+      "Timeout in seconds.".to_string(),
+      None,
+      val => Option::<usize>::of(val)
+    ),
     smt (
       SolverStyle,
       "smt", solver_keys(),
@@ -377,6 +400,13 @@ conf!{
       true,
       val => bool::of(val)
     ),
+    timeout (
+      Option<usize>,
+      "timeout", "<int>".to_string(),
+      "Timeout in seconds.".to_string(),
+      None,
+      val => Option::<usize>::of(val)
+    ),
     smt (
       SolverStyle,
       "smt", solver_keys(),
@@ -418,6 +448,20 @@ conf!{
       false,
       val => bool::of(val)
     ),
+    two_state (
+      bool,
+      "two_state", "[on/off]".to_string(),
+      "Generates two-state candidate terms.".to_string(),
+      true,
+      val => bool::of(val)
+    ),
+    arith (
+      bool,
+      "arith", "[on/off]".to_string(),
+      "Generates int and rat candidate terms.".to_string(),
+      true,
+      val => bool::of(val)
+    ),
     max (
       Option<usize>,
       "max", "<int>".to_string(),
@@ -425,6 +469,13 @@ conf!{
       None,
       val => Option::<usize>::of(val)
     ),
+    timeout (
+      Option<usize>,
+      "timeout", "<int>".to_string(),
+      "Timeout in seconds.".to_string(),
+      None,
+      val => Option::<usize>::of(val)
+    ),
     smt (
       SolverStyle,
       "smt", solver_keys(),
@@ -486,6 +537,54 @@ conf!{
 }
 
 
+conf!{
+  Pdr("Property Directed Reachability (PDR) options".to_string()) {
+    is_on (
+      bool,
+      "turn", "[on/off]".to_string(),
+      "(De)activates PDR.".to_string(),
+      false,
+      val => bool::of(val)
+    ),
+    max (
+      Option<usize>,
+      "max", "<int>".to_string(),
+      "Maximum number of frames.".to_string(),
+      None,
+      val => Option::<usize>::of(val)
+    ),
+    timeout (
+      Option<usize>,
+      "timeout", "<int>".to_string(),
+      "Timeout in seconds.".to_string(),
+      None,
+      val => Option::<usize>::of(val)
+    ),
+    smt (
+      SolverStyle,
+      "smt", solver_keys(),
+      "Kind of solver to use.".to_string(),
+      SolverStyle::Z3,
+      val => SolverStyle::of(val)
+    ),
+    smt_cmd (
+      Option<String>,
+      "smt_cmd", "<cmd>".to_string(),
+      "Command to run the solver with.".to_string(),
+      None,
+      val => Option::<String>::of(val)
+    ),
+    smt_log (
+      Option<String>,
+      "smt_log", "<file>".to_string(),
+      "File to log the smt trace to.".to_string(),
+      None,
+      val => Option::<String>::of(val)
+    ),
+  }
+}
+
+
 conf!{
   Pruner("Options of the pruner for discovered invariants".to_string()) {
     is_on (
@@ -495,6 +594,20 @@ conf!{
       true,
       val => bool::of(val)
     ),
+    minimize (
+      bool,
+      "minimize", "[on/off]".to_string(),
+      "(De)activates lemma minimization after the analysis.".to_string(),
+      false,
+      val => bool::of(val)
+    ),
+    timeout (
+      Option<usize>,
+      "timeout", "<int>".to_string(),
+      "Timeout in seconds.".to_string(),
+      None,
+      val => Option::<usize>::of(val)
+    ),
     smt (
       SolverStyle,
       "smt", solver_keys(),
@@ -584,7 +697,7 @@ named! {
         ) ~
         opts: options ~
         delimited!(
-          opt!(multispace), char!(')'), opt!(multispace)
+          opt!(multispace), char!(')'), opt!( complete!(multispace) )
         ),
         || ( Some(scope), opts )
       )
@@ -593,10 +706,56 @@ named! {
   )
 }
 
+/// Output format of the analysis results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutFormat {
+  /// Human readable log.
+  Human,
+  /// One JSON record per property.
+  Json,
+}
+impl Parse for OutFormat {
+  fn of(val: & str) -> Result<OutFormat, String> {
+    match val {
+      "human" => Ok(OutFormat::Human),
+      "json" => Ok(OutFormat::Json),
+      _ => Err(
+        format!("expected format [human/json], got {}", val)
+      ),
+    }
+  }
+}
+
 /// Top level configuration.
 pub struct Master {
   /// All the technique scopes.
   scopes: Vec<& 'static str>,
+  /// Global timeout in seconds.
+  pub timeout: Option<usize>,
+  /// Output format.
+  pub format: OutFormat,
+  /// Directory to dump counterexamples in as VCD files, if any.
+  pub vcd: Option<String>,
+  /// Directory to write the certificates of the proved properties in, if
+  /// any.
+  pub certif: Option<String>,
+  /// File to write the system and properties in as constrained Horn
+  /// clauses, instead of analyzing them, if any.
+  pub chc: Option<String>,
+  /// Slice the system with respect to the cone of influence of the
+  /// properties before analyzing it.
+  pub coi: bool,
+  /// Dispatch the groups of properties with disjoint cones of influence to
+  /// their own technique instances.
+  pub schedule: bool,
+  /// Maximum number of groups of properties analyzed concurrently when
+  /// scheduling, unbounded if none. The techniques of the groups finished
+  /// are only asked to stop, and may keep running until their current query
+  /// returns.
+  pub jobs: Option<usize>,
+  /// Verify the properties declared over the sub-systems first, and use the
+  /// ones proved as invariants of their callers.
+  pub compositional: bool,
   /// Optional BMC configuration.
   pub bmc: Option<Bmc>,
   /// Optional Kind configuration.
@@ -607,6 +766,8 @@ pub struct Master {
   pub tig: Option<Tig>,
   /// Optional Pruner configuration.
   pub pruner: Option<Pruner>,
+  /// Optional PDR configuration.
+  pub pdr: Option<Pdr>,
 }
 impl Master {
   /// The scope to technique mapping.
@@ -684,12 +845,34 @@ impl Master {
         self.pruner = Some(pruner) ;
         Ok(self)
       },
+      "pdr" => {
+        let mut pdr = self.pdr.unwrap_or_else(|| Pdr::default()) ;
+        for & (ref key, ref val) in opts.iter() {
+          match pdr.set(key, val) {
+            Ok(()) => (),
+            Err(e) => {
+              self.pdr = Some(pdr) ;
+              return Err( (e, self) )
+            },
+          }
+        } ;
+        self.pdr = Some(pdr) ;
+        Ok(self)
+      },
       "all" => {
         // println!("all") ;
         let scopes = self.scopes.clone() ;
         let mut res = self ;
         for opt in opts.iter() {
           // println!(" opt: {:?}", opt) ;
+          // Timeout at top level is global, not per technique.
+          if opt.0 == "timeout" {
+            match Option::<usize>::of(& opt.1) {
+              Ok(timeout) => res.timeout = timeout,
+              Err(e) => return Err( (e, res) ),
+            }
+            continue
+          }
           let mut one_ok = false ;
           for scope in scopes.iter() {
             // println!("  scope: {} ({})", scope, one_ok) ;
@@ -719,12 +902,22 @@ impl Master {
   /// Default top level configuration.
   pub fn default() -> Self {
     Master {
-      scopes: vec![ "bmc", "kind", "twind", "tig", "pruner" ],
+      scopes: vec![ "bmc", "kind", "twind", "tig", "pruner", "pdr" ],
+      timeout: None,
+      format: OutFormat::Human,
+      vcd: None,
+      certif: None,
+      chc: None,
+      coi: false,
+      schedule: false,
+      jobs: None,
+      compositional: false,
       bmc: Some( Bmc::default() ),
       kind: Some( Kind::default() ),
       twind: Some( Twind::default() ),
       tig: Some( Tig::default() ),
       pruner: Some( Pruner::default() ),
+      pdr: Some( Pdr::default() ),
     }
   }
 
@@ -773,6 +966,81 @@ impl Master {
               "expected options after \"-o\", found nothing".to_string()
             ),
           }
+        } else if "--format" == nxt {
+          match args.next() {
+            Some(format) => match OutFormat::of(& format) {
+              Ok(format) => conf.format = format,
+              Err(e) => return Err(e),
+            },
+            None => return Err(
+              "expected format after \"--format\", found nothing".to_string()
+            ),
+          }
+        } else if "--vcd" == nxt {
+          match args.next() {
+            Some(dir) => conf.vcd = Some(dir),
+            None => return Err(
+              "expected directory after \"--vcd\", found nothing".to_string()
+            ),
+          }
+        } else if "--certif" == nxt {
+          match args.next() {
+            Some(dir) => conf.certif = Some(dir),
+            None => return Err(
+              "expected directory after \"--certif\", found nothing".to_string()
+            ),
+          }
+        } else if "--chc" == nxt {
+          match args.next() {
+            Some(file) => conf.chc = Some(file),
+            None => return Err(
+              "expected file after \"--chc\", found nothing".to_string()
+            ),
+          }
+        } else if "--coi" == nxt {
+          match args.next() {
+            Some(coi) => match bool::of(& coi) {
+              Ok(coi) => conf.coi = coi,
+              Err(e) => return Err(e),
+            },
+            None => return Err(
+              "expected bool after \"--coi\", found nothing".to_string()
+            ),
+          }
+        } else if "--schedule" == nxt {
+          match args.next() {
+            Some(schedule) => match bool::of(& schedule) {
+              Ok(schedule) => conf.schedule = schedule,
+              Err(e) => return Err(e),
+            },
+            None => return Err(
+              "expected bool after \"--schedule\", found nothing".to_string()
+            ),
+          }
+        } else if "--jobs" == nxt {
+          match args.next() {
+            Some(jobs) => match usize::of(& jobs) {
+              Ok(0) => return Err(
+                "expected positive int after \"--jobs\", got 0".to_string()
+              ),
+              Ok(jobs) => conf.jobs = Some(jobs),
+              Err(e) => return Err(e),
+            },
+            None => return Err(
+              "expected int after \"--jobs\", found nothing".to_string()
+            ),
+          }
+        } else if "--compositional" == nxt {
+          match args.next() {
+            Some(comp) => match bool::of(& comp) {
+              Ok(comp) => conf.compositional = comp,
+              Err(e) => return Err(e),
+            },
+            None => return Err(
+              "expected bool after \"--compositional\", found nothing"
+                .to_string()
+            ),
+          }
         } else {
           if "-h" == nxt || "--help" == nxt {
             let scope = if let Some(next) = args.next() {
@@ -827,6 +1095,9 @@ impl Master {
       "pruner" => for line in Pruner::lines(log.fmt(), log.stl()) {
         println!("{}", line)
       },
+      "pdr" => for line in Pdr::lines(log.fmt(), log.stl()) {
+        println!("{}", line)
+      },
       "all" => {
         let mut fst = true ;
         for scope in Master::default().scopes {
@@ -862,15 +1133,64 @@ where [option] can be
   {} \"[ <opt> <val> | <mdl>([<opt> <val>],+) ],+\"
       Sets some options globally (first version) or for a specific module
       (second version). Check the options of each module for more details.
+      Option `timeout <int>` is a global timeout in seconds when set globally,
+      and a timeout for the module when set for a specific module. At the
+      timeout the techniques are asked to stop, but solver processes are not
+      killed: they exit at their own time limit, set to the deadline. A
+      technique busy outside of a solver query, or a solver ignoring its time
+      limit, keeps running after the analysis ends.
       {}:
       > kino -o \"smt_log: path/to/log, bmc(max: 7, solver: cvc4)\"
       Activates log of the solver's trace for all modules, and option `max`
-      (`solver`) in the `bmc` module to `7` (`cvc4`).\
+      (`solver`) in the `bmc` module to `7` (`cvc4`).
+  {} [human|json]
+      Output format of the results, defaults to human. In json mode, prints
+      one JSON record per property on its own line and nothing else.
+  {} [dir]
+      Writes the counterexample of each falsified property to
+      `[dir]/[property].vcd`, in the VCD waveform format.
+  {} [dir]
+      Writes a certificate for each proved property to
+      `[dir]/[property].smt2`. A certificate is an SMT-LIB 2 script checking
+      that the properties and invariants discovered are k-inductive: all its
+      queries are unsat. Uses the solver of module `kind`.
+  {} [file]
+      Writes the system and the properties to check to `[file]` as an SMT-LIB
+      2 `HORN` problem instead of analyzing them. The problem is sat iff all
+      the properties hold. Assumptions of `verify-assuming` are conjoined to
+      the init clause, over the next state in the step clause, and to the
+      queries.
+  {} [on|off]
+      Slices the system with respect to the cone of influence of the
+      properties before analyzing it, defaults to off. Counterexamples are
+      lifted to the original system and replayed on it before being
+      reported.
+  {} [on|off]
+      Groups the properties by cone of influence, properties whose cones
+      share state variables being in the same group, and dispatches each
+      group to its own instances of the techniques. Defaults to off.
+  {} [int]
+      Maximum number of groups analyzed concurrently when scheduling,
+      unbounded by default.
+  {} [on|off]
+      Verifies the properties declared over the sub-systems of the system
+      first, defaults to off. The ones proved are lifted as invariants of the
+      callers of the sub-system.
+The exit code is 0 if the system is safe, 10 if it is unsafe, 20 if the
+analysis is inconclusive, and 2 on errors.\
             ",
             log.mk_emph("-h / --help"),
             scopes,
             log.mk_emph("-o"),
-            log.mk_emph("Example")
+            log.mk_emph("Example"),
+            log.mk_emph("--format"),
+            log.mk_emph("--vcd"),
+            log.mk_emph("--certif"),
+            log.mk_emph("--chc"),
+            log.mk_emph("--coi"),
+            log.mk_emph("--schedule"),
+            log.mk_emph("--jobs"),
+            log.mk_emph("--compositional")
           )
         ) ;
         log.nl() ;
@@ -366,6 +366,8 @@ pub enum Tek {
   Tig,
   /// Invariant pruner.
   Pruner,
+  /// Property directed reachability.
+  Pdr,
   /// Custom technique.
   /// First string is a short description that should be a legal filename.
   /// Second is an arbitrarily long description.
@@ -388,6 +390,7 @@ impl Tek {
       Twind => "2-ind",
       Tig => "tig",
       Pruner => "pruner",
+      Pdr => "pdr",
       Tec(ref s, _) => & s,
     }
   }
@@ -402,6 +405,7 @@ impl Tek {
       Twind => "2-induction",
       Tig => "invariant generation",
       Pruner => "invariant pruner",
+      Pdr => "property directed reachability",
       Tec(_, ref desc) => & desc,
     }
   }
@@ -416,6 +420,7 @@ impl Tek {
       Twind => "kino_2-induction".to_string(),
       Tig => "kino_invgen".to_string(),
       Pruner => "kino_pruner".to_string(),
+      Pdr => "kino_pdr".to_string(),
       Tec(ref s, _) => format!("kino_{}", s),
     }
   }
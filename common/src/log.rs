@@ -162,6 +162,8 @@ pub struct MasterLog<F, S> {
   fmt: F,
   /// Styling.
   stl: S,
+  /// Nothing is printed if true.
+  quiet: bool,
 }
 
 impl<F, S: Clone> MasterLog<F, S> {
@@ -173,19 +175,28 @@ impl<F, S: Clone> MasterLog<F, S> {
   /// The styler.
   #[inline(always)]
   pub fn stl(& self) -> & S { & self.stl }
+  /// Silences a log, nothing will be printed. Used when the output is not
+  /// meant for humans, *e.g.* JSON output.
+  pub fn silence(mut self) -> Self {
+    self.quiet = true ;
+    self
+  }
+  /// True iff the log is silenced.
+  #[inline(always)]
+  pub fn is_quiet(& self) -> bool { self.quiet }
 }
 
 impl MasterLog<Format, Style> {
   /// Creates a default log.
   pub fn default() -> Self {
-    MasterLog { fmt: Format::default(), stl: Style::default() }
+    MasterLog { fmt: Format::default(), stl: Style::default(), quiet: false }
   }
 }
 
 impl MasterLog<NoFormat, NoStyle> {
   /// Creates a no formatting, no styling log.
   pub fn empty() -> Self {
-    MasterLog { fmt: NoFormat, stl: NoStyle }
+    MasterLog { fmt: NoFormat, stl: NoStyle, quiet: false }
   }
 }
 
@@ -211,22 +222,26 @@ impl<
 
   /// Prints a separation between log sections.
   pub fn sep(& self) {
+    if self.quiet { return () }
     println!("")
   }
 
   /// Prints a newline in a log section.
   pub fn nl(& self) {
+    if self.quiet { return () }
     println!("{} {}", self.fmt.ppre(), self.fmt.pref())
   }
 
   /// Prints a trailer line.
   pub fn trail(& self) {
+    if self.quiet { return () }
     println!("{} {}{}", self.fmt.ppre(), self.fmt.pref(), self.fmt.trail()) ;
     self.sep()
   }
 
   /// Prints a title line.
   pub fn title(& self, e: & str) {
+    if self.quiet { return () }
     println!(
       "{} {}{} {}",
       self.fmt.ppre(), self.fmt.pref(), self.fmt.head(), self.mk_emph(e)
@@ -235,6 +250,7 @@ impl<
 
   /// Prints some log lines.
   pub fn print(& self, e: & str) {
+    if self.quiet { return () }
     for line in e.lines() {
       println!("{} {} {}", self.fmt.ppre(), self.fmt.pref(), line)
     }
@@ -244,6 +260,7 @@ impl<
   pub fn pref_log(
     & self, pref: & str, title: & super::Tek, bla: & str
   ) {
+    if self.quiet { return () }
     println!(
       "{} {} {}", self.fmt.ppre(), pref, self.emph(title.to_str())
     ) ;
@@ -287,6 +304,7 @@ impl<
 
   /// Logs a `safe` end of analysis.
   pub fn log_safe(& self, time: Duration) {
+    if self.quiet { return () }
     let pref = format!(
       "{} {}",
       self.fmt.ppre(),
@@ -305,6 +323,7 @@ impl<
 
   /// Logs an `unsafe` end of analysis.
   pub fn log_unsafe(& self, time: Duration) {
+    if self.quiet { return () }
     let pref = format!(
       "{} {}",
       self.fmt.ppre(),
@@ -326,6 +345,7 @@ impl<
   pub fn log_unknown<
     'a, Props: Iterator<Item = & 'a Sym>
   >(& self, props: Props, time: Duration) {
+    if self.quiet { return () }
     let pref = format!(
       "{} {}",
       self.fmt.ppre(),
@@ -358,6 +378,7 @@ impl<
 
   /// Logs a `unknown` end of analysis without any unknown properties.
   pub fn just_log_unknown(& self) {
+    if self.quiet { return () }
     let pref = format!(
       "{} {}",
       self.fmt.ppre(),
@@ -377,6 +398,7 @@ impl<
   pub fn log_proved(
     & self, t: & super::Tek, props: & [Sym], info: & Offset
   ) {
+    if self.quiet { return () }
     let pref = format!(
       "{} {}", self.fmt.ppre(), self.mk_happy(self.fmt.pref())
     ) ;
@@ -398,6 +420,7 @@ impl<
   pub fn log_error(
     & self, t: & super::Tek, error: & ::errors::ErrorKind
   ) {
+    if self.quiet { return () }
     use errors::ErrorKind::* ;
     let pref = format!(
       "{} {}", self.fmt.ppre(), self.mk_bad(self.fmt.pref())
@@ -459,6 +482,7 @@ impl<
   pub fn log_cex(
     & self, t: & super::Tek, cex: & Cex, props: & [Sym]
   ) {
+    if self.quiet { return () }
     let pref = format!(
       "{} {}", self.fmt.ppre(), self.mk_bad(self.fmt.pref())
     ) ;
@@ -12,14 +12,18 @@
 use std::fmt ;
 use std::thread ;
 use std::sync::mpsc ;
-use std::sync::mpsc::{ Sender, Receiver, TryRecvError } ;
-use std::collections::HashMap ;
+use std::sync::mpsc::{
+  Sender, Receiver, TryRecvError, RecvTimeoutError
+} ;
+use std::time::{ Duration, Instant } ;
+use std::collections::{ HashMap, BTreeMap } ;
 
-use std::sync::Arc ;
+use std::sync::{ Arc, Mutex } ;
 
 use term::{
-  Offset, Sym, Factory, Model, STermSet
+  Offset, Sym, Term, Factory, Model, STermSet
 } ;
+use term::smt::{ SolverConf, SolverStyle } ;
 
 use sys::{ Prop, Sys } ;
 
@@ -28,13 +32,17 @@ use ::{ Tek, CanRun } ;
 use errors::* ;
 
 /// Wrapper around master and kids receive and send channels.
+///
+/// Kids are organized in groups, each group analyzing its own properties. A
+/// group runs at most one instance of each technique, and messages from the
+/// kids come with the group of their sender.
 pub struct KidManager {
   /// Receives messages from kids.
-  r: Receiver<MsgUp>,
+  r: Receiver<(usize, MsgUp)>,
   /// Sends messages to master.
-  s: Sender<MsgUp>,
-  /// Senders to running techniques.
-  senders: HashMap<Tek, mpsc::Sender<MsgDown>>,
+  s: Sender<(usize, MsgUp)>,
+  /// Senders to running techniques, by group.
+  senders: HashMap<(usize, Tek), mpsc::Sender<MsgDown>>,
 }
 impl KidManager {
   /// Constructs a kid manager.
@@ -42,18 +50,25 @@ impl KidManager {
     let (sender, receiver) = mpsc::channel() ;
     KidManager { r: receiver, s: sender, senders: HashMap::new() }
   }
-  /// Launches a technique.
+  /// Launches a technique in a group. The technique verifies `props` under
+  /// `assumptions`.
+  ///
+  /// The solvers of the technique cannot run past `deadline`, see
+  /// [`Event::time_limit`](struct.Event.html#method.time_limit).
   pub fn launch<
     Conf: 'static + Sync + Send, T: CanRun<Conf> + Send + 'static
   >(
-    & mut self, t: T, sys: Sys, props: Vec<Prop>, f: & Factory, conf: Arc<Conf>
+    & mut self, group: usize, t: T, sys: Sys, props: Vec<Prop>,
+    assumptions: & [Term], f: & Factory, conf: Arc<Conf>,
+    deadline: Option<Instant>
   ) -> Res<()> {
     let (s,r) = mpsc::channel() ;
     let id = t.id() ;
     let event = Event::mk(
-      self.s.clone(), r, t.id().clone(), f.clone(), & props
+      self.s.clone(), r, t.id().clone(), group, f.clone(), & props,
+      assumptions.to_vec(), deadline
     ) ;
-    match self.senders.get( & id ) {
+    match self.senders.get( & (group, id) ) {
       None => (),
       Some(_) => bail!(
         ErrorKind::TekDuplicateError(id)
@@ -67,7 +82,7 @@ impl KidManager {
         ErrorKind::TekSpawnError(e, id)
       ),
     } ;
-    match self.senders.insert(id, s) {
+    match self.senders.insert( (group, id), s ) {
       None => Ok(()),
       Some(_) => unreachable!(),
     }
@@ -85,18 +100,80 @@ impl KidManager {
       }
     }
   }
+  /// Broadcasts a message to the kids of a group.
+  #[inline(always)]
+  pub fn broadcast_to(& self, group: usize, msg: MsgDown) {
+    for (& (kid_group, _), sender) in self.senders.iter() {
+      if kid_group == group {
+        // Techniques might already have exited, nothing to do then.
+        match sender.send(msg.clone()) { _ => () }
+      }
+    }
+  }
 
-  /// Receive a message from the kids.
+  /// Receive a message from the kids, and the group of its sender.
   #[inline(always)]
-  pub fn recv(& self) -> Res<MsgUp> {
+  pub fn recv(& self) -> Res<(usize, MsgUp)> {
     self.r.recv().chain_err(
       || ErrorKind::MsgRcvError(Tek::Kino)
     )
   }
-  /// Forget a kid.
+  /// Receive a message from the kids, waiting at most `timeout`. Returns
+  /// `None` if no message was received in time.
   #[inline(always)]
-  pub fn forget(& mut self, t: & Tek) -> Res<()> {
-    match self.senders.remove(t) {
+  pub fn recv_timeout(
+    & self, timeout: Duration
+  ) -> Res< Option<(usize, MsgUp)> > {
+    match self.r.recv_timeout(timeout) {
+      Ok(msg) => Ok( Some(msg) ),
+      Err( RecvTimeoutError::Timeout ) => Ok(None),
+      Err( RecvTimeoutError::Disconnected ) => bail!(
+        ErrorKind::MsgRcvError(Tek::Kino)
+      ),
+    }
+  }
+  /// Tells a kid of a group to stop and forgets it.
+  ///
+  /// The kid exits the next time it checks for messages, which kills its
+  /// solver(s). A kid blocked on a solver query only checks for messages
+  /// once the query returns: its solvers were launched with a hard time
+  /// limit matching its deadline, so a kid stopped because of a timeout
+  /// exits at the latest when its solvers do.
+  pub fn stop(& mut self, group: usize, t: & Tek) -> Res<()> {
+    match self.senders.remove( & (group, * t) ) {
+      Some(sender) => {
+        // Kid might already be dead, nothing to do in this case.
+        match sender.send(MsgDown::Stop) { _ => Ok(()) }
+      },
+      None => bail!( ErrorKind::TekUnknownError(* t) ),
+    }
+  }
+  /// Tells all the kids of a group to stop and forgets them.
+  ///
+  /// Cooperative, see [`stop`](#method.stop): the kids keep running until
+  /// they check for messages.
+  pub fn stop_group(& mut self, group: usize) {
+    self.broadcast_to(group, MsgDown::Stop) ;
+    let kids: Vec<(usize, Tek)> = self.senders.keys().filter(
+      |& & (kid_group, _)| kid_group == group
+    ).cloned().collect() ;
+    for kid in kids.iter() {
+      self.senders.remove(kid) ; ()
+    }
+  }
+  /// Tells all the kids to stop and forgets them.
+  ///
+  /// Cooperative, see [`stop`](#method.stop). On a global timeout the
+  /// solvers exit by themselves since their time limit is the global
+  /// deadline.
+  pub fn stop_all(& mut self) {
+    self.broadcast(MsgDown::Stop) ;
+    self.senders.clear()
+  }
+  /// Forget a kid of a group.
+  #[inline(always)]
+  pub fn forget(& mut self, group: usize, t: & Tek) -> Res<()> {
+    match self.senders.remove( & (group, * t) ) {
       Some(_) => Ok(()),
       None => bail!( ErrorKind::TekUnknownError(* t) ),
     }
@@ -104,19 +181,26 @@ impl KidManager {
   /// True iff there's no more kids known by the manager.
   #[inline(always)]
   pub fn kids_done(& self) -> bool { self.senders.is_empty() }
+  /// True iff there's no more kids of a group known by the manager.
+  #[inline(always)]
+  pub fn group_done(& self, group: usize) -> bool {
+    ! self.senders.keys().any( |& (kid_group, _)| kid_group == group )
+  }
 
-  /// Sends a pruning message if a pruner is registered. Returns `true` iff the
-  /// pruning message was successfully sent.
+  /// Sends a pruning message if a pruner is registered in a group. Returns
+  /// `true` iff the pruning message was successfully sent.
   ///
   /// If the pruner is registered but the message can't be sent, writes a `bad`
   /// message explaining what happened, forgets the pruner, and returns
   /// `false`.
   #[inline(always)]
   pub fn prune_if_possible<BadLog: Fn(& str)>(
-    & mut self, tek: Tek, sys: Sym, invs: STermSet, info: Option<usize>,
-    bad_log: BadLog
+    & mut self, group: usize, tek: Tek, sys: Sym, invs: STermSet,
+    info: Option<usize>, bad_log: BadLog
   ) -> bool {
-    let send_res = if let Some(pruner) = self.senders.get(& Tek::Pruner) {
+    let send_res = if let Some(pruner) = self.senders.get(
+      & (group, Tek::Pruner)
+    ) {
       pruner.send(
         MsgDown::InvariantPruning( tek, sys.clone(), invs.clone(), info )
       )
@@ -134,7 +218,7 @@ impl KidManager {
             pruner seems to be dead, forgetting it", e
           )
         ) ;
-        match self.senders.remove(& Tek::Pruner) {
+        match self.senders.remove( & (group, Tek::Pruner) ) {
           _ => false,
         }
       },
@@ -181,6 +265,8 @@ pub enum MsgDown {
   Forget(Vec<Sym>, Status),
   /// Some properties were found k-true.
   KTrue(Vec<Sym>, Offset),
+  /// The technique should stop, typically because of a timeout.
+  Stop,
 }
 
 /// Message from the techniques to kino.
@@ -280,24 +366,37 @@ fn exit<T>(_: T) {
   exit(0)
 }
 
+/// Solver time limit options, by option. `rsmt2` options are static strings,
+/// each time limit is only leaked once.
+static TIME_LIMITS: Mutex< BTreeMap<String, & 'static str> > = Mutex::new(
+  BTreeMap::new()
+) ;
+
 /// Used by the techniques to communicate with kino.
 pub struct Event {
   /// Sender to kino.
-  s: Sender<MsgUp>,
+  s: Sender<(usize, MsgUp)>,
   /// Receiver from kino.
   r: Receiver<MsgDown>,
   /// Identifier of the technique.
   t: Tek,
+  /// Group of the technique.
+  group: usize,
   /// Term factory.
   f: Factory,
   /// K-true properties.
   k_true: HashMap<Sym, Option<Offset>>,
+  /// Assumptions the properties are verified under.
+  assumptions: Vec<Term>,
+  /// Time at which the technique is stopped, if any.
+  deadline: Option<Instant>,
 }
 impl Event {
   /// Creates a new `Event`.
   pub fn mk(
-    s: Sender<MsgUp>, r: Receiver<MsgDown>,
-    t: Tek, f: Factory, props: & [Prop]
+    s: Sender<(usize, MsgUp)>, r: Receiver<MsgDown>,
+    t: Tek, group: usize, f: Factory, props: & [Prop], assumptions: Vec<Term>,
+    deadline: Option<Instant>
   ) -> Self {
     let mut k_true = HashMap::with_capacity(props.len()) ;
     for prop in props {
@@ -306,7 +405,15 @@ impl Event {
         Some(_) => unreachable!(),
       }
     } ;
-    Event { s: s, r: r, t: t, f: f, k_true: k_true }
+    Event {
+      s: s, r: r, t: t, group: group, f: f, k_true: k_true,
+      assumptions: assumptions, deadline: deadline
+    }
+  }
+
+  /// Sends a message upwards, exits if kino is gone.
+  fn send(& self, msg: MsgUp) {
+    self.s.send( (self.group, msg) ).unwrap_or_else( exit )
   }
 
   /// The technique this event manager belongs to.
@@ -315,34 +422,80 @@ impl Event {
     self.t
   }
 
+  /// The assumptions the properties are verified under.
+  #[inline]
+  pub fn assumptions(& self) -> & [Term] {
+    & self.assumptions
+  }
+
+  /// Adds a hard time limit to a solver configuration so that the solver
+  /// process exits by itself at the deadline of the technique, if any.
+  ///
+  /// `rsmt2` only kills a solver process from the thread owning it, which
+  /// does not check for messages while it waits for an answer. The time
+  /// limit is what actually terminates the solvers of a technique stopped
+  /// because of a timeout.
+  pub fn time_limit(& self, conf: SolverConf) -> SolverConf {
+    let deadline = match self.deadline {
+      Some(deadline) => deadline,
+      None => return conf,
+    } ;
+    let now = Instant::now() ;
+    let left = if deadline > now {
+      deadline - now
+    } else { Duration::from_secs(0) } ;
+    // One second of slack so that the master notices the timeout first.
+    let secs = left.as_secs() + if left.subsec_nanos() > 0 { 2 } else { 1 } ;
+    let option = match * conf.style() {
+      SolverStyle::Z3 => format!("-T:{}", secs),
+      SolverStyle::CVC4 => format!("--tlimit={}", secs * 1000),
+    } ;
+    // Options are static strings, each one is leaked once and reused.
+    let option = match TIME_LIMITS.lock() {
+      Ok(mut limits) => {
+        if let Some(option) = limits.get(& option) {
+          return conf.option(option)
+        }
+        let leaked: & 'static str = Box::leak(
+          option.clone().into_boxed_str()
+        ) ;
+        limits.insert(option, leaked) ;
+        leaked
+      },
+      // Poisoned, some technique panicked. No time limit then.
+      Err(_) => return conf,
+    } ;
+    conf.option(option)
+  }
+
   /// Sends a pruned invariant message upwards.
   pub fn pruned_invariants(
     & self, tek: Tek, sys: & Sym, invs: STermSet, old_card: usize,
     info: Option<usize>
   ) {
-    self.s.send(
+    self.send(
       MsgUp::PrunedInvariants(self.t, tek, sys.clone(), invs, old_card, info)
-    ).unwrap_or_else( exit )
+    )
   }
 
   /// Sends an invariant message upwards.
   pub fn invariants(& self, sys: & Sym, invs: STermSet) {
-    self.s.send(
+    self.send(
       MsgUp::Invariants(self.t, sys.clone(), invs, None)
-    ).unwrap_or_else( exit )
+    )
   }
   /// Sends an invariant message upwards, with a notion of offset.
   pub fn invariants_at(& self, sys: & Sym, invs: STermSet, at: usize) {
-    self.s.send(
+    self.send(
       MsgUp::Invariants(self.t, sys.clone(), invs, Some(at))
-    ).unwrap_or_else( exit )
+    )
   }
 
   /// Sends a done message upwards.
   pub fn done(& self, info: Info) {
-    self.s.send(
+    self.send(
       MsgUp::Done(self.t, info)
-    ).unwrap_or_else( exit )
+    )
   }
   /// Sends a done message upwards.
   pub fn done_at(& self, o: & Offset) {
@@ -350,9 +503,9 @@ impl Event {
   }
   /// Sends a proved message upwards.
   pub fn proved(& self, props: Vec<Sym>, info: Offset) {
-    self.s.send(
+    self.send(
       MsgUp::Proved(props, self.t, info)
-    ).unwrap_or_else( exit )
+    )
   }
   /// Sends a proved message upwards.
   pub fn proved_at(& self, props: Vec<Sym>, o: & Offset) {
@@ -360,9 +513,9 @@ impl Event {
   }
   /// Sends a falsification message upwards.
   pub fn disproved(& self, model: Model, props: Vec<Sym>, info: Info) {
-    self.s.send(
+    self.send(
       MsgUp::Disproved(model, props, self.t, info)
-    ).unwrap_or_else( exit )
+    )
   }
   /// Sends a falsification message upwards.
   pub fn disproved_at(& self, model: Model, props: Vec<Sym>, o: & Offset) {
@@ -370,27 +523,27 @@ impl Event {
   }
   /// Sends some k-true properties.
   pub fn k_true(& self, props: Vec<Sym>, o: & Offset) {
-    self.s.send(
+    self.send(
       MsgUp::KTrue(self.t, props, self.t, o.clone())
-    ).unwrap_or_else( exit )
+    )
   }
   /// Sends a log message upwards.
   pub fn log(& self, s: & str) {
-    self.s.send(
+    self.send(
       MsgUp::Bla(self.t, s.to_string())
-    ).unwrap_or_else( exit )
+    )
   }
   /// Sends an error upwards.
   pub fn error(& self, e: ::errors::Error) {
-    self.s.send(
+    self.send(
       MsgUp::Error(self.t, e)
-    ).unwrap_or_else( exit )
+    )
   }
   /// Sends a warning upwards.
   pub fn warning(& self, s: & str) {
-    self.s.send(
+    self.send(
       MsgUp::Warning(self.t, s.to_string())
-    ).unwrap_or_else( exit )
+    )
   }
   /// The factory in an `Event`.
   pub fn factory(& self) -> & Factory {
@@ -405,6 +558,9 @@ impl Event {
     }
   }
   /// Receive messages from the master.
+  ///
+  /// Returns `None` if the technique should stop, either because the master
+  /// is gone or because it sent a `Stop` message.
   pub fn recv(& mut self) -> Option<Vec<MsgDown>> {
     let mut vec = vec![] ;
     loop {
@@ -414,6 +570,7 @@ impl Event {
             self.k_true.insert(prop, Some(o)) ; ()
           }
         },
+        Ok( MsgDown::Stop ) => return None,
         Ok( msg ) => vec.push(msg),
         Err( TryRecvError::Empty ) => break,
         Err( TryRecvError::Disconnected ) => return None,
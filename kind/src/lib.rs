@@ -11,6 +11,11 @@
 //! K-induction.
 //!
 //! Unrolls backwards.
+//!
+//! In simple path mode, when the step check fails with a trace visiting the
+//! same state twice, a constraint stating that these two states are distinct
+//! is asserted and the check is performed again. Such constraints are sound
+//! for k-induction, and make it complete on finite-state systems.
 
 extern crate term ;
 extern crate system ;
@@ -22,7 +27,9 @@ use std::sync::Arc ;
 use std::time::Duration ;
 use std::thread::sleep ;
 
-use term::Offset2 ;
+use term::{
+  Term, Offset, Offset2, STermSet, State, VarMaker
+} ;
 
 use common::conf ;
 use common::SolverTrait ;
@@ -52,6 +59,7 @@ impl common::CanRun<conf::Kind> for KInd {
       None => (),
       Some(ref cmd) => solver_conf = solver_conf.cmd(cmd.clone()),
     } ;
+    let solver_conf = event.time_limit(solver_conf) ;
 
     mk_solver_run!(
       solver_conf, conf.smt_log(), "kind", event.factory(),
@@ -61,6 +69,31 @@ impl common::CanRun<conf::Kind> for KInd {
   }
 }
 
+/// Checks that some invariants are `k`-inductive for a system, using the
+/// solver of a kind configuration.
+///
+/// The queries are logged to `<dir>/<name>.smt2`, which is then a certificate
+/// checkable by any solver. See
+/// [`unroll::certif`](../unroll/certif/index.html).
+pub fn certify(
+  conf: & conf::Kind, factory: & term::Factory,
+  sys: & Sys, props: & [Prop], assumptions: & [Term],
+  invs: & STermSet, k: usize, dir: & str, name: & str
+) -> common::errors::Res<bool> {
+  let mut solver_conf = conf.smt().clone().default().print_success() ;
+  match * conf.smt_cmd() {
+    None => (),
+    Some(ref cmd) => solver_conf = solver_conf.cmd(cmd.clone()),
+  } ;
+  let smt_log = Some( dir.to_string() ) ;
+
+  mk_solver_run!(
+    solver_conf, & smt_log, name, factory,
+    solver => certif::certify(solver, sys, props, assumptions, invs, k),
+    err => Err(err)
+  )
+}
+
 fn kind<
   'a,
   S: SolverTrait<'a>
@@ -77,6 +110,20 @@ fn kind<
     event, Unroller::mk(& sys, & props, solver)
     => "while creating unroller"
   ) ;
+  unroller.assume( event.assumptions() ) ;
+
+  // States at the current and next offset are distinct, used in simple path
+  // mode.
+  let distinct = {
+    let factory = event.factory() ;
+    let mut eqs = Vec::with_capacity( sys.state().len() ) ;
+    for & (ref sym, _) in sys.state().args().iter() {
+      let curr: Term = factory.svar( sym.get().clone(), State::Curr ) ;
+      let next: Term = factory.svar( sym.get().clone(), State::Next ) ;
+      eqs.push( factory.eq( vec![ curr, next ] ) )
+    }
+    factory.not( factory.and(eqs) )
+  } ;
 
   // event.log("creating manager, declaring actlits") ;
   let mut props = log_try!(
@@ -188,6 +235,38 @@ fn kind<
         => "during a `check_sat_assuming` query at {}", k
       ) ;
 
+      if is_sat && * conf.simple_path() {
+        // Looking for a state visited twice.
+        let mut states = Vec::with_capacity( k.curr().to_usize() + 1 ) ;
+        let mut off = Offset::zero() ;
+        let mut twice = None ;
+        while off <= * k.curr() {
+          let state = log_try!(
+            event, unroller.get_state(& off)
+            => "while retrieving state at {}", off
+          ) ;
+          if let Some(first) = states.iter().position(
+            |other| other == & state
+          ) {
+            twice = Some( Offset2::mk(Offset::of_int(first), off) ) ;
+            break
+          }
+          states.push(state) ;
+          off = off.nxt()
+        }
+        if let Some(twice) = twice {
+          log_try!(
+            event, unroller.deactivate(actlit)
+            => "while deactivating negative actlit"
+          ) ;
+          log_try!(
+            event, unroller.assert(& distinct, & twice)
+            => "while asserting distinct states at {}", twice
+          ) ;
+          continue 'split
+        }
+      }
+
       if is_sat {
         // event.log("sat, getting falsified props") ;
         let falsified = log_try!(
@@ -0,0 +1,614 @@
+// Copyright 2015 Adrien Champion. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+#![deny(missing_docs)]
+
+//! Property directed reachability (IC3).
+//!
+//! Only unrolls the system once, at `(0, 1)`. Frame `0` is the initial
+//! predicate. Frame `i > 0` over-approximates the states reachable in `i`
+//! steps or less, and is a set of clauses activated by an actlit. Clauses are
+//! stored in the last frame they are known to hold in, so frame `i` is
+//! activated by the actlits of all frames `j >= i`.
+//!
+//! Clauses are learnt by blocking the cubes that can reach a bad state, and
+//! are generalized by dropping literals. When a frame is equal to its
+//! successor, it is an inductive strengthening of the properties: they are
+//! proved and the clauses of the frame are broadcast as invariants.
+
+extern crate term ;
+extern crate system ;
+#[macro_use]
+extern crate common ;
+extern crate unroll ;
+
+use std::sync::Arc ;
+
+use term::{
+  Offset, Offset2, Sym, Term, Model, STerm, STermSet, Factory, UnTermOps
+} ;
+use term::tmp::{ TmpTerm, TmpTermMker } ;
+
+use common::conf ;
+use common::SolverTrait ;
+use common::errors::* ;
+use common::msg::{ Event, MsgDown, Status } ;
+
+use system::{ Sys, Prop } ;
+
+use unroll::* ;
+
+/// Property directed reachability.
+pub struct Pdr ;
+unsafe impl Send for Pdr {}
+impl common::CanRun<conf::Pdr> for Pdr {
+  fn id(& self) -> common::Tek { common::Tek::Pdr }
+
+  fn run(
+    & self, conf: Arc<conf::Pdr>, sys: Sys, props: Vec<Prop>, mut event: Event
+  ) {
+    let mut solver_conf = conf.smt().clone().default().print_success() ;
+    match * conf.smt_cmd() {
+      None => (),
+      Some(ref cmd) => solver_conf = solver_conf.cmd(cmd.clone()),
+    } ;
+    let solver_conf = event.time_limit(solver_conf) ;
+
+    mk_solver_run!(
+      solver_conf, conf.smt_log(), "pdr", event.factory(),
+      solver => pdr(solver, conf.clone(), sys, props, & mut event),
+      err => event.error(err)
+    )
+  }
+}
+
+/// A proof obligation: a cube that can reach a bad state.
+struct Obligation {
+  /// Literals of the cube, over the current state.
+  cube: Vec<Term>,
+  /// Frame the cube should be blocked in.
+  lvl: usize,
+  /// Values the cube was extracted from, at `0`.
+  model: Model,
+  /// Index of the obligation this one is a predecessor of.
+  parent: Option<usize>,
+}
+
+/// Result of the blocking phase.
+enum Blocked {
+  /// All the obligations were blocked.
+  Yes,
+  /// Reached the initial states. Stores the counterexample, the properties
+  /// it falsifies and its length.
+  No(Model, Vec<Sym>, Offset),
+}
+
+/// Builds a counterexample from the chain of obligations starting at `index`
+/// and the model of the transition to the bad state.
+///
+/// Also returns the offset of the last state.
+fn cex_of(
+  obligations: & [Obligation], mut index: usize, bad: & Model
+) -> (Model, Offset) {
+  let mut cex = Vec::with_capacity( bad.len() * obligations.len() ) ;
+  let mut k = Offset::zero() ;
+  loop {
+    let obligation = & obligations[index] ;
+    for & ( (ref var, ref off), ref cst ) in obligation.model.iter() {
+      if off.is_some() {
+        cex.push( ( (var.clone(), Some(k)), cst.clone() ) )
+      } else if k == Offset::zero() {
+        cex.push( ( (var.clone(), None), cst.clone() ) )
+      }
+    }
+    match obligation.parent {
+      Some(parent) => {
+        index = parent ;
+        k = k.nxt()
+      },
+      None => break,
+    }
+  }
+  // Last state is the second state of the bad transition.
+  let one = Offset::zero().nxt() ;
+  let last = k.nxt() ;
+  for & ( (ref var, ref off), ref cst ) in bad.iter() {
+    if * off == Some(one) {
+      cex.push( ( (var.clone(), Some(last)), cst.clone() ) )
+    }
+  }
+  (cex, last)
+}
+
+/// The frames and everything needed to query them.
+struct Frames<S> {
+  /// Unroller, unrolled once at `(0, 1)`.
+  unroller: Unroller<S>,
+  /// Properties, one-state ones are activated at `0`.
+  props: PropManager,
+  /// Activates the initial predicate at `0`.
+  init: Actlit,
+  /// Actlits of the frames. Frame `i > 0` is activated by `acts[i - 1]`.
+  acts: Vec<Actlit>,
+  /// Cubes blocked in each frame. Those of frame `i > 0` are `cubes[i - 1]`.
+  cubes: Vec< Vec< Vec<Term> > >,
+  /// Term factory.
+  factory: Factory,
+}
+
+impl<
+  'a, S: SolverTrait<'a>
+> Frames<S> {
+  /// Creates the frames.
+  ///
+  /// Asserts the transition relation and the assumptions at `(0, 1)`, the
+  /// initial predicate at `0` under an actlit, and activates one-state
+  /// properties at `0`.
+  fn mk(
+    sys: & Sys, props: Vec<Prop>, assumptions: & [Term],
+    solver: S, factory: Factory
+  ) -> Res<Self> {
+    let o = Offset2::init() ;
+    let mut unroller = try_chain!(
+      Unroller::mk(sys, & props, solver) => "while creating unroller"
+    ) ;
+    unroller.assume(assumptions) ;
+    let props = try_chain!(
+      PropManager::mk(props, unroller.solver())
+      => "while creating property manager"
+    ) ;
+    try_chain!(
+      unroller.declare_svars( o.curr() ) => "while declaring state variables"
+    ) ;
+    try_chain!(
+      unroller.unroll(& o) => "while unrolling system"
+    ) ;
+    let init = try!( unroller.fresh_actlit() ) ;
+    let implication = init.activate_term(
+      TmpTerm::Trm( sys.init_term().clone() )
+    ) ;
+    try_chain!(
+      unroller.assert(& implication, & o)
+      => "while asserting initial predicate"
+    ) ;
+    try_chain!(
+      props.activate_state(unroller.solver(), & o)
+      => "while activating one-state properties"
+    ) ;
+    Ok(
+      Frames {
+        unroller: unroller, props: props, init: init,
+        acts: vec![], cubes: vec![], factory: factory,
+      }
+    )
+  }
+
+  /// Index of the last frame.
+  #[inline]
+  fn top(& self) -> usize { self.acts.len() }
+
+  /// Adds a new, empty frame.
+  fn push_frame(& mut self) -> Res<()> {
+    let actlit = try!( self.unroller.fresh_actlit() ) ;
+    self.acts.push(actlit) ;
+    self.cubes.push( vec![] ) ;
+    Ok(())
+  }
+
+  /// Forgets all the frames but the initial one.
+  fn reset(& mut self) -> Res<()> {
+    while let Some(actlit) = self.acts.pop() {
+      try!( self.unroller.deactivate(actlit) )
+    }
+    self.cubes.clear() ;
+    Ok(())
+  }
+
+  /// Actlits activating frame `lvl`.
+  fn actlits(& self, lvl: usize) -> Vec<String> {
+    if lvl == 0 {
+      vec![ self.init.name() ]
+    } else {
+      let mut actlits = self.props.actlits() ;
+      for actlit in self.acts[ (lvl - 1) .. ].iter() {
+        actlits.push( actlit.name() )
+      }
+      actlits
+    }
+  }
+
+  /// Checks frame `lvl` conjoined with `fst` asserted at `(0, 1)` and `snd`
+  /// asserted at `(1, 2)`.
+  ///
+  /// Returns the actlit guarding `fst` and `snd`. It should be deactivated
+  /// once the client is done with the model.
+  fn check(
+    & mut self, lvl: usize, fst: Option<TmpTerm>, snd: Option<TmpTerm>
+  ) -> Res<(bool, Actlit)> {
+    let o = Offset2::init() ;
+    let actlit = try!( self.unroller.fresh_actlit() ) ;
+    if let Some(fst) = fst {
+      try!( self.unroller.assert(& actlit.activate_term(fst), & o) )
+    }
+    if let Some(snd) = snd {
+      try!( self.unroller.assert(& actlit.activate_term(snd), & o.nxt()) )
+    }
+    let mut actlits = self.actlits(lvl) ;
+    actlits.push( actlit.name() ) ;
+    let is_sat = try_chain!(
+      self.unroller.check_sat_assuming(& actlits)
+      => "while checking frame {}", lvl
+    ) ;
+    Ok( (is_sat, actlit) )
+  }
+
+  /// Same as `check`, but deactivates the actlit right away.
+  fn just_check(
+    & mut self, lvl: usize, fst: Option<TmpTerm>, snd: Option<TmpTerm>
+  ) -> Res<bool> {
+    let (is_sat, actlit) = try!( self.check(lvl, fst, snd) ) ;
+    try!( self.unroller.deactivate(actlit) ) ;
+    Ok(is_sat)
+  }
+
+  /// The cube corresponding to the values of a model at `0`, and these
+  /// values.
+  ///
+  /// Non-stateful variables are part of the cube so that all the states of a
+  /// counterexample agree on them.
+  fn cube_of(& self, model: & Model) -> (Vec<Term>, Model) {
+    let zero = Offset::zero() ;
+    let mut cube = Vec::with_capacity( model.len() ) ;
+    let mut values = Vec::with_capacity( model.len() ) ;
+    for & ( (ref var, ref off), ref cst ) in model.iter() {
+      let keep = match * off {
+        Some(ref off) => * off == zero,
+        None => true,
+      } ;
+      if keep {
+        cube.push(
+          self.factory.eq(
+            vec![
+              self.factory.mk_var( var.clone() ),
+              self.factory.mk_cst( cst.clone() )
+            ]
+          )
+        ) ;
+        values.push( ( (var.clone(), * off), cst.clone() ) )
+      }
+    }
+    (cube, values)
+  }
+
+  /// Looks for an initial state falsifying some one-state properties.
+  fn bad_init(& mut self) -> Res< Option<(Model, Vec<Sym>)> > {
+    let o = Offset2::init() ;
+    let one_false = match self.props.one_false_state() {
+      Some(one_false) => one_false,
+      None => return Ok(None),
+    } ;
+    let (is_sat, actlit) = try!( self.check(0, Some(one_false), None) ) ;
+    let res = if is_sat {
+      let falsified = try_chain!(
+        self.props.get_false_state(self.unroller.solver(), & o)
+        => "while retrieving falsified properties"
+      ) ;
+      let model = try!( self.unroller.get_model(& o) ) ;
+      Some( (self.cube_of(& model).1, falsified) )
+    } else { None } ;
+    try!( self.unroller.deactivate(actlit) ) ;
+    Ok(res)
+  }
+
+  /// Looks for a state of frame `lvl` with a successor falsifying some
+  /// properties.
+  ///
+  /// Returns the model of the transition and the properties falsified.
+  fn bad(& mut self, lvl: usize) -> Res< Option<(Model, Vec<Sym>)> > {
+    let o = Offset2::init() ;
+    let one_false = match self.props.one_false_next() {
+      Some(one_false) => one_false,
+      None => return Ok(None),
+    } ;
+    let (is_sat, actlit) = try!( self.check(lvl, Some(one_false), None) ) ;
+    let res = if is_sat {
+      let falsified = try_chain!(
+        self.props.get_false_next(self.unroller.solver(), & o)
+        => "while retrieving falsified properties"
+      ) ;
+      let model = try!( self.unroller.get_model(& o) ) ;
+      Some( (model, falsified) )
+    } else { None } ;
+    try!( self.unroller.deactivate(actlit) ) ;
+    Ok(res)
+  }
+
+  /// Drops literals from a cube as long as it does not intersect the initial
+  /// states and its negation is inductive relative to frame `lvl - 1`.
+  fn generalize(
+    & mut self, mut cube: Vec<Term>, lvl: usize
+  ) -> Res< Vec<Term> > {
+    let mut cnt = 0 ;
+    while cnt < cube.len() && cube.len() > 1 {
+      let lit = cube.remove(cnt) ;
+      let conj = TmpTerm::mk_term_conj(& cube) ;
+      let keep = try!(
+        self.just_check( 0, Some( conj.clone() ), None )
+      ) || try!(
+        self.just_check( lvl - 1, Some( conj.clone().tmp_neg() ), Some(conj) )
+      ) ;
+      if keep {
+        cube.insert(cnt, lit) ;
+        cnt += 1
+      }
+    }
+    Ok(cube)
+  }
+
+  /// Adds the negation of a cube to frame `lvl`.
+  fn block_cube(& mut self, cube: Vec<Term>, lvl: usize) -> Res<()> {
+    let implication = self.acts[lvl - 1].activate_term(
+      TmpTerm::mk_term_conj(& cube).tmp_neg()
+    ) ;
+    try_chain!(
+      self.unroller.assert(& implication, & Offset2::init())
+      => "while adding a clause to frame {}", lvl
+    ) ;
+    self.cubes[lvl - 1].push(cube) ;
+    Ok(())
+  }
+
+  /// Blocks the cube of a bad transition, and its predecessors, in the last
+  /// frame.
+  fn block(
+    & mut self, bad: Model, falsified: Vec<Sym>
+  ) -> Res<Blocked> {
+    let (cube, model) = self.cube_of(& bad) ;
+    let mut obligations = vec![
+      Obligation { cube: cube, lvl: self.top(), model: model, parent: None }
+    ] ;
+    // Indices of the obligations not blocked yet.
+    let mut stack = vec![ 0 ] ;
+
+    loop {
+      let index = match stack.last() {
+        Some(index) => * index,
+        None => return Ok(Blocked::Yes),
+      } ;
+      let (cube, lvl) = (
+        obligations[index].cube.clone(), obligations[index].lvl
+      ) ;
+      let conj = TmpTerm::mk_term_conj(& cube) ;
+
+      // Initial state, counterexample.
+      if lvl == 0 || try!( self.just_check(0, Some( conj.clone() ), None) ) {
+        let (cex, len) = cex_of(& obligations, index, & bad) ;
+        return Ok( Blocked::No(cex, falsified, len) )
+      }
+
+      let (is_sat, actlit) = try!(
+        self.check( lvl - 1, Some( conj.clone().tmp_neg() ), Some(conj) )
+      ) ;
+
+      if is_sat {
+        let model = try!( self.unroller.get_model(& Offset2::init()) ) ;
+        try!( self.unroller.deactivate(actlit) ) ;
+        let (cube, model) = self.cube_of(& model) ;
+        stack.push( obligations.len() ) ;
+        obligations.push(
+          Obligation {
+            cube: cube, lvl: lvl - 1, model: model, parent: Some(index)
+          }
+        )
+      } else {
+        try!( self.unroller.deactivate(actlit) ) ;
+        let cube = try!( self.generalize(cube, lvl) ) ;
+        try!( self.block_cube(cube, lvl) ) ;
+        stack.pop() ; ()
+      }
+    }
+  }
+
+  /// Pushes the cubes of each frame to the next one when possible.
+  ///
+  /// Returns the first frame equal to its successor, if any.
+  fn propagate(& mut self) -> Res< Option<usize> > {
+    use std::mem::replace ;
+    let top = self.top() ;
+    for lvl in 1..top {
+      let cubes = replace(& mut self.cubes[lvl - 1], vec![]) ;
+      for cube in cubes {
+        let is_sat = try!(
+          self.just_check(
+            lvl, None, Some( TmpTerm::mk_term_conj(& cube) )
+          )
+        ) ;
+        if is_sat {
+          self.cubes[lvl - 1].push(cube)
+        } else {
+          try!( self.block_cube(cube, lvl + 1) )
+        }
+      }
+      if self.cubes[lvl - 1].is_empty() {
+        return Ok( Some(lvl) )
+      }
+    }
+    Ok(None)
+  }
+
+  /// The clauses of frame `lvl`, as invariants.
+  fn invariants(& self, lvl: usize) -> Res<STermSet> {
+    let mut invs = STermSet::new() ;
+    for cubes in self.cubes[ (lvl - 1) .. ].iter() {
+      for cube in cubes.iter() {
+        let clause = self.factory.not( self.factory.and( cube.clone() ) ) ;
+        let next = try_chain!(
+          self.factory.bump(& clause) => "while bumping clause {}", clause
+        ) ;
+        invs.insert( STerm::One(clause, next) ) ; ()
+      }
+    }
+    Ok(invs)
+  }
+
+  /// Handles the messages from the master.
+  ///
+  /// Returns `None` if the master is gone, `Some(true)` if the frames were
+  /// reset because some properties were disproved.
+  fn recv(& mut self, event: & mut Event) -> Res< Option<bool> > {
+    let mut reset = false ;
+    match event.recv() {
+      None => return Ok(None),
+      Some(msgs) => for msg in msgs {
+        match msg {
+          MsgDown::Forget(ps, status) => {
+            let before = self.props.len() ;
+            try_chain!(
+              self.props.forget(self.unroller.solver(), ps.iter())
+              => "while forgetting some properties \
+                because of a `Forget` message"
+            ) ;
+            // Frames were built assuming disproved properties hold.
+            if let Status::Disproved = status {
+              reset = reset || before != self.props.len()
+            }
+          },
+          MsgDown::Invariants(sym, invs) => {
+            let is_ours = self.unroller.sys().sym().get() == & sym ;
+            if is_ours {
+              let o = Offset2::init() ;
+              try_chain!(
+                self.unroller.add_invs(invs, & o, & o)
+                => "while adding invariants from supervisor"
+              )
+            }
+          },
+          msg => event.error(
+            format!("unexpected message `{:?}`", msg).into()
+          ),
+        }
+      },
+    }
+    if reset {
+      try!( self.reset() )
+    }
+    Ok( Some(reset) )
+  }
+}
+
+fn pdr<
+  'a,
+  S: SolverTrait<'a>
+>(
+  solver: S, conf: Arc<conf::Pdr>,
+  sys: Sys, props: Vec<Prop>, event: & mut Event
+) {
+
+  let factory = event.factory().clone() ;
+  let mut frames = log_try!(
+    event, Frames::mk(& sys, props, event.assumptions(), solver, factory)
+    => "while creating frames"
+  ) ;
+
+  if frames.props.none_left() {
+    event.log("no properties to run on, stopping") ;
+    event.done_at( & Offset::zero() ) ;
+    return ()
+  }
+
+  // Initial states falsifying some properties.
+  if let Some( (model, falsified) ) = log_try!(
+    event, frames.bad_init() => "while checking initial states"
+  ) {
+    log_try!(
+      event, frames.props.forget(frames.unroller.solver(), falsified.iter())
+      => "while forgetting falsified properties"
+    ) ;
+    event.disproved_at(model, falsified, & Offset::zero())
+  }
+
+  'out: loop {
+
+    if frames.top() == 0 {
+      log_try!(
+        event, frames.push_frame() => "while creating the first frame"
+      )
+    }
+    let k = frames.top() ;
+
+    if let Some(ref max) = * conf.max() {
+      if max < & k {
+        event.done_at( & Offset::of_int(k - 1) ) ;
+        break 'out
+      }
+    }
+
+    match log_try!(
+      event, frames.recv(event) => "while receiving messages"
+    ) {
+      None => return (),
+      // Frames were reset.
+      Some(true) => continue 'out,
+      Some(false) => (),
+    }
+
+    if frames.props.none_left() {
+      event.done_at( & Offset::of_int(k) ) ;
+      break 'out
+    }
+
+    // Blocking bad states.
+    while let Some( (bad, falsified) ) = log_try!(
+      event, frames.bad(k) => "while looking for bad states in frame {}", k
+    ) {
+      match log_try!(
+        event, frames.block(bad, falsified)
+        => "while blocking bad cube in frame {}", k
+      ) {
+        Blocked::Yes => (),
+        Blocked::No(cex, falsified, len) => {
+          log_try!(
+            event, frames.props.forget(
+              frames.unroller.solver(), falsified.iter()
+            ) => "while forgetting falsified properties"
+          ) ;
+          log_try!(
+            event, frames.reset() => "while resetting frames"
+          ) ;
+          event.disproved_at(cex, falsified, & len) ;
+          continue 'out
+        },
+      }
+    }
+
+    log_try!(
+      event, frames.push_frame() => "while creating frame {}", k + 1
+    ) ;
+
+    if let Some(lvl) = log_try!(
+      event, frames.propagate() => "while propagating clauses at {}", k
+    ) {
+      let invs = log_try!(
+        event, frames.invariants(lvl)
+        => "while retrieving the clauses of frame {}", lvl
+      ) ;
+      if ! invs.is_empty() {
+        event.invariants_at( sys.sym().get(), invs, lvl )
+      }
+      let proved = frames.props.not_inhibited() ;
+      log_try!(
+        event, frames.props.forget(frames.unroller.solver(), proved.iter())
+        => "while forgetting proved properties"
+      ) ;
+      event.proved_at( proved, & Offset::of_int(lvl) ) ;
+      event.done_at( & Offset::of_int(k) ) ;
+      break 'out
+    }
+
+  }
+}
@@ -22,7 +22,7 @@ use std::sync::Arc ;
 use std::time::Duration ;
 use std::thread::sleep ;
 
-use term::{ Offset2, STermSet } ;
+use term::{ Term, Offset2, STermSet } ;
 
 use common::conf ;
 use common::SolverTrait ;
@@ -32,6 +32,7 @@ use common::errors::* ;
 use system::{ Sys, Prop } ;
 
 use unroll::* ;
+use unroll::certif::check_k_inductive ;
 
 /// Pruner.
 pub struct Pruner ;
@@ -54,6 +55,7 @@ impl common::CanRun<conf::Pruner> for Pruner {
       None => (),
       Some(ref cmd) => solver_conf = solver_conf.cmd(cmd.clone()),
     } ;
+    let solver_conf = event.time_limit(solver_conf) ;
 
     mk_solver_run!(
       solver_conf, conf.smt_log(), "pruner", event.factory(),
@@ -63,6 +65,83 @@ impl common::CanRun<conf::Pruner> for Pruner {
   }
 }
 
+/// Minimizes the lemmas a proved property is `k`-inductive relative to,
+/// using the solver of a pruner configuration.
+///
+/// First looks for the smallest `k' <= k` such that `prop` is
+/// `k'`-inductive relative to all the lemmas, then drops the lemmas one by
+/// one as long as the property stays `k'`-inductive. The lemmas must be
+/// invariants of the system. Returns `k'` and the lemmas kept.
+pub fn minimize(
+  conf: & conf::Pruner, factory: & term::Factory,
+  sys: & Sys, props: & [Prop], assumptions: & [Term],
+  prop: & Prop, lemmas: & STermSet, k: usize
+) -> Res<(usize, STermSet)> {
+  let mut solver_conf = conf.smt().clone().default().print_success() ;
+  match * conf.smt_cmd() {
+    None => (),
+    Some(ref cmd) => solver_conf = solver_conf.cmd(cmd.clone()),
+  } ;
+
+  let log_file = format!(
+    "pruner_minimize_{}", system::file_name( & format!("{}", prop.sym()) )
+  ) ;
+
+  mk_solver_run!(
+    solver_conf, conf.smt_log(), log_file, factory,
+    solver => minimize_lemmas(
+      solver, sys, props, assumptions, prop, lemmas, k
+    ),
+    err => Err(err)
+  )
+}
+
+fn minimize_lemmas< 'a, S: SolverTrait<'a> >(
+  solver: S, sys: & Sys, props: & [Prop], assumptions: & [Term],
+  prop: & Prop, lemmas: & STermSet, k: usize
+) -> Res<(usize, STermSet)> {
+  let mut unroller = try_chain!(
+    Unroller::mk(sys, props, solver) => "while creating unroller"
+  ) ;
+  unroller.assume(assumptions) ;
+
+  let mut target = STermSet::with_capacity(1) ;
+  target.insert( prop.body().clone() ) ;
+  let mut lemmas = lemmas.clone() ;
+  lemmas.remove( prop.body() ) ;
+
+  // Smallest `k` working with all the lemmas.
+  let mut min_k = None ;
+  for k in 1..(k + 1) {
+    if try!(
+      check_k_inductive(& mut unroller, props, & lemmas, & target, k)
+    ) {
+      min_k = Some(k) ;
+      break
+    }
+  }
+  let k = match min_k {
+    Some(k) => k,
+    None => bail!(
+      "property {} is not {}-inductive relative to its lemmas",
+      prop.sym(), k
+    ),
+  } ;
+
+  // Dropping the lemmas that are not needed.
+  let candidates: Vec<_> = lemmas.iter().cloned().collect() ;
+  for lemma in candidates.into_iter() {
+    lemmas.remove(& lemma) ;
+    if ! try!(
+      check_k_inductive(& mut unroller, props, & lemmas, & target, k)
+    ) {
+      lemmas.insert(lemma) ; ()
+    }
+  }
+
+  Ok( (k, lemmas) )
+}
+
 fn pruner< 'a, S: SolverTrait<'a> >(
   solver: S, sys: Sys, _: Vec<Prop>, event: & mut Event
 ) {
@@ -75,6 +154,7 @@ fn pruner< 'a, S: SolverTrait<'a> >(
     event, Unroller::mk(& sys, & [], solver)
     => "while creating unroller"
   ) ;
+  unroller.assume( event.assumptions() ) ;
 
   log_try!(
     event, unroller.declare_svars(
@@ -0,0 +1,105 @@
+// Copyright 2015 Adrien Champion. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! JSON output of the results of an analysis.
+//!
+//! One record per property, one record per line. All the fields are always
+//! present, `null` if irrelevant:
+//!
+//! ```json
+//! {
+//!   "property": "out_le_10", "status": "falsified", "k": null,
+//!   "technique": "bmc", "time": 0.012003000, "lemmas": null,
+//!   "cex": { "length": 10, "functions": {}, "trace": [ ... ] }
+//! }
+//! ```
+//!
+//! The status is one of `unknown`, `k-true`, `falsified` and `invariant`.
+//! The lemmas are the ones an invariant is `k`-inductive relative to, when
+//! they have been minimized. Counterexamples of liveness properties are
+//! lassos, their `cex` object also has a `loop` field giving the index of the
+//! first state of the loop.
+
+use std::io ;
+use std::time::Duration ;
+
+use system::{ PropStatus, json_str } ;
+
+use report::AnalysisReport ;
+
+/// Formats a duration as a number of seconds.
+fn secs(d: & Duration) -> String {
+  format!("{}.{:09}", d.as_secs(), d.subsec_nanos())
+}
+
+/// Writes a JSON error record.
+pub fn write_error<W: io::Write>(w: & mut W, blah: & str) -> io::Result<()> {
+  write!(w, "{{\"error\": {}}}\n", json_str(blah))
+}
+
+/// Writes one JSON record per property of a report.
+pub fn write_report<W: io::Write>(
+  w: & mut W, report: & AnalysisReport
+) -> io::Result<()> {
+  for prop in report.props.iter() {
+    try!(
+      write!(
+        w, "{{\"property\": {}, ", json_str( & format!("{}", prop.sym()) )
+      )
+    ) ;
+    match prop.status {
+      PropStatus::Unknown => try!(
+        write!(w, "\"status\": \"unknown\", \"k\": null, ")
+      ),
+      PropStatus::KTrue(k) => try!(
+        write!(w, "\"status\": \"k-true\", \"k\": {}, ", k)
+      ),
+      PropStatus::Falsified(_) => try!(
+        write!(w, "\"status\": \"falsified\", \"k\": null, ")
+      ),
+      PropStatus::Invariant(k) |
+      PropStatus::MinInvariant(k, _) => try!(
+        write!(w, "\"status\": \"invariant\", \"k\": {}, ", k)
+      ),
+    }
+    match (prop.tek, prop.time) {
+      (Some(tek), Some(time)) => try!(
+        write!(
+          w, "\"technique\": {}, \"time\": {}, ",
+          json_str( tek.to_str() ), secs(& time)
+        )
+      ),
+      _ => try!(
+        write!(w, "\"technique\": null, \"time\": null, ")
+      ),
+    }
+    try!( write!(w, "\"lemmas\": ") ) ;
+    match prop.status {
+      PropStatus::MinInvariant(_, ref lemmas) => {
+        try!( write!(w, "[") ) ;
+        let mut pref = "" ;
+        for lemma in lemmas.iter() {
+          try!(
+            write!(w, "{}{}", pref, json_str( & format!("{}", lemma) ))
+          ) ;
+          pref = ", "
+        }
+        try!( write!(w, "], ") )
+      },
+      _ => try!( write!(w, "null, ") ),
+    }
+    try!( write!(w, "\"cex\": ") ) ;
+    match prop.cex() {
+      Some(cex) => try!( cex.write_json(w) ),
+      None => try!( write!(w, "null") ),
+    }
+    try!( write!(w, "}}\n") )
+  }
+  Ok(())
+}
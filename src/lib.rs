@@ -24,12 +24,18 @@ extern crate kind ;
 extern crate twind ;
 extern crate tig ;
 extern crate pruner ;
+extern crate pdr ;
 
 mod master ;
+mod report ;
 
-// use term::{ Sym, SymMaker } ;
+use std::io::Read ;
+
+pub use term::{ Sym, SymMaker } ;
 use system::Prop ;
+pub use system::{ PropStatus, Cex } ;
 pub use system::Error as SysError ;
+pub use common::{ conf, Tek } ;
 // pub use unroll::* ;
 // pub use common::* ;
 
@@ -60,14 +66,52 @@ use errors::* ;
 pub mod teks {
   pub use bmc::Bmc ;
   pub use kind::KInd ;
+  pub use pdr::Pdr ;
   pub use tig::* ;
 }
 
 pub use master::Master ;
+pub use report::{ AnalysisReport, PropReport } ;
 pub use system::ctxt::Context ;
 use system::ctxt::Res as CtxtRes ;
 
-/// Loads a file, creates a context.
+/// A reader method of a context, see
+/// [`reader_of`](fn.reader_of.html).
+pub type Reader = fn(& mut Context, & mut Read) -> Result<CtxtRes, SysError> ;
+
+/// The reader method of a context for a file. Files with extension `.lus` are
+/// read as Lustre programs, files with extension `.btor2` or `.btor` as BTOR2
+/// models, files with extension `.aag` or `.aig` as AIGER graphs. Other files
+/// are read with [`read_vmt`](fn.read_vmt.html).
+pub fn reader_of(path: & str) -> Reader {
+  if path.ends_with(".lus") {
+    Context::read_lustre
+  } else if is_btor(path) {
+    Context::read_btor
+  } else if is_aiger(path) {
+    Context::read_aiger
+  } else {
+    read_vmt
+  }
+}
+
+/// Reads an annotation-based VMT-LIB script if the input uses VMT-LIB
+/// annotations, and kinō's VMT-LIB otherwise. The input is read entirely
+/// before being parsed.
+pub fn read_vmt(
+  context: & mut Context, reader: & mut Read
+) -> Result<CtxtRes, SysError> {
+  let mut src = String::new() ;
+  try!( reader.read_to_string(& mut src).map_err(SysError::Io) ) ;
+  if system::ctxt::is_vmtlib(& src) {
+    context.read_vmtlib( & mut src.as_bytes() )
+  } else {
+    context.read( & mut src.as_bytes() )
+  }
+}
+
+/// Loads a file, creates a context. The file is read with the reader method
+/// given by [`reader_of`](fn.reader_of.html).
 pub fn load(path: & str) -> Res< (Context, CtxtRes) > {
   use std::fs::File ;
   use term::Factory ;
@@ -75,7 +119,7 @@ pub fn load(path: & str) -> Res< (Context, CtxtRes) > {
     Ok(mut file) => {
       let factory = Factory::mk() ;
       let mut context = Context::mk(factory, 1000) ;
-      match context.read(& mut file) {
+      match reader_of(path)(& mut context, & mut file) {
         Ok(res) => Ok( (context, res) ),
         Err(e) => bail!( ErrorKind::SysError(e) ),
       }
@@ -98,14 +142,145 @@ pub fn analyze(path: & str) -> Res<(Context, Vec<Prop>)> {
       match Master::launch(
         & log, & mut context, sys, props.clone(), None, conf
       ) {
-        Ok(()) => Ok( (context, props) ),
+        Ok(_) => Ok( (context, props) ),
+        Err(()) => Err(
+          "master did not return successfully".into()
+        ),
+      }
+    },
+    CtxtRes::CheckAss(sys, props, assumptions) => {
+      let log = ::common::log::MasterLog::default() ;
+      let conf = ::common::conf::Master::default() ;
+      match Master::launch(
+        & log, & mut context, sys, props.clone(), Some(assumptions), conf
+      ) {
+        Ok(_) => Ok( (context, props) ),
         Err(()) => Err(
           "master did not return successfully".into()
         ),
       }
     },
-    CtxtRes::CheckAss(_, _, _) => Err(
-      "verify assuming is not supported".into()
+  }
+}
+
+/// Reads some input with a reader method of a fresh context, runs the master
+/// with some configuration. Does not print anything.
+fn read_launch<R: Read>(
+  reader: & mut R, conf: conf::Master, read: Reader
+) -> Res<(Context, AnalysisReport)> {
+  use term::Factory ;
+  let factory = Factory::mk() ;
+  let mut context = Context::mk(factory, 1000) ;
+  let res = match read(& mut context, reader) {
+    Ok(res) => res,
+    Err(e) => bail!( ErrorKind::SysError(e) ),
+  } ;
+  launch(context, res, conf)
+}
+
+/// Reads a system and a query from a reader, kinō's VMT-LIB or
+/// annotation-based VMT-LIB, see [`read_vmt`](fn.read_vmt.html). Runs the
+/// master with some configuration. Does not print anything.
+pub fn analyze_read<R: Read>(
+  reader: & mut R, conf: conf::Master
+) -> Res<(Context, AnalysisReport)> {
+  read_launch(reader, conf, read_vmt)
+}
+
+/// Reads a Lustre program from a reader, runs the master with some
+/// configuration on the properties of its main node. Does not print
+/// anything.
+pub fn analyze_lustre_read<R: Read>(
+  reader: & mut R, conf: conf::Master
+) -> Res<(Context, AnalysisReport)> {
+  read_launch(reader, conf, Context::read_lustre)
+}
+
+/// Reads a BTOR2 model from a reader, runs the master with some
+/// configuration on its `bad` properties. Does not print anything.
+pub fn analyze_btor_read<R: Read>(
+  reader: & mut R, conf: conf::Master
+) -> Res<(Context, AnalysisReport)> {
+  read_launch(reader, conf, Context::read_btor)
+}
+
+/// Reads an AIGER graph, ASCII or binary, from a reader, runs the master
+/// with some configuration on its bad states. Does not print anything.
+pub fn analyze_aiger_read<R: Read>(
+  reader: & mut R, conf: conf::Master
+) -> Res<(Context, AnalysisReport)> {
+  read_launch(reader, conf, Context::read_aiger)
+}
+
+/// True if a path has a BTOR2 extension.
+fn is_btor(path: & str) -> bool {
+  path.ends_with(".btor2") || path.ends_with(".btor")
+}
+
+/// True if a path has an AIGER extension.
+fn is_aiger(path: & str) -> bool {
+  path.ends_with(".aag") || path.ends_with(".aig")
+}
+
+/// Loads a file and writes the system and properties of its check as
+/// constrained Horn clauses, see [`system::chc`](../system/chc/index.html).
+/// The assumptions of `verify-assuming` checks hold in all the states of the
+/// system.
+pub fn write_chc<W: ::std::io::Write>(path: & str, w: & mut W) -> Res<()> {
+  let (_, res) = try!( load(path) ) ;
+  let (sys, props, assumptions) = match res {
+    CtxtRes::Check(sys, props) => (sys, props, vec![]),
+    CtxtRes::CheckAss(sys, props, assumptions) => (sys, props, assumptions),
+    _ => bail!( format!("no check to export in \"{}\"", path) ),
+  } ;
+  match system::chc::write(w, & sys, & props, & assumptions) {
+    Ok(()) => Ok(()),
+    Err(e) => bail!( ErrorKind::SysError( SysError::Io(e) ) ),
+  }
+}
+
+/// Runs the master on the result of reading some input.
+fn launch(
+  mut context: Context, res: CtxtRes, conf: conf::Master
+) -> Res<(Context, AnalysisReport)> {
+  let log = ::common::log::MasterLog::default().silence() ;
+  let result = match res {
+    CtxtRes::Success => bail!("got success"),
+    CtxtRes::Exit => return Ok( (context, AnalysisReport::empty()) ),
+    CtxtRes::Check(sys, props) => Master::launch(
+      & log, & mut context, sys, props, None, conf
+    ),
+    CtxtRes::CheckAss(sys, props, assumptions) => Master::launch(
+      & log, & mut context, sys, props, Some(assumptions), conf
+    ),
+  } ;
+  match result {
+    Ok(report) => Ok( (context, report) ),
+    Err(()) => bail!(
+      ErrorKind::AnalysisError(
+        "master did not return successfully".to_string()
+      )
+    ),
+  }
+}
+
+/// Same as [`analyze_read`](fn.analyze_read.html) on a string.
+pub fn analyze_str(
+  s: & str, conf: conf::Master
+) -> Res<(Context, AnalysisReport)> {
+  analyze_read(& mut s.as_bytes(), conf)
+}
+
+/// Same as [`analyze_read`](fn.analyze_read.html) on a file, read with the
+/// reader method given by [`reader_of`](fn.reader_of.html).
+pub fn analyze_file(
+  path: & str, conf: conf::Master
+) -> Res<(Context, AnalysisReport)> {
+  use std::fs::File ;
+  match File::open(path) {
+    Ok(mut file) => read_launch(& mut file, conf, reader_of(path)),
+    Err(e) => bail!(
+      ErrorKind::SysError( SysError::Io(e) )
     ),
   }
-}
\ No newline at end of file
+}
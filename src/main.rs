@@ -26,47 +26,170 @@ extern crate kind ;
 extern crate twind ;
 extern crate tig ;
 extern crate pruner ;
+extern crate pdr ;
+extern crate kino_api ;
 
 use std::process::exit ;
+use std::io::stdout ;
 
+use system::{ Sys, Prop } ;
 use system::ctxt::* ;
 
 use common::Tek::Kino ;
-use common::log::MasterLog ;
+use common::conf::OutFormat ;
+use common::log::{ MasterLog, Formatter, Styler } ;
 
 pub mod master ;
+pub mod report ;
+mod json ;
 
 use master::Master ;
+use report::AnalysisReport ;
+
+/// Exit code when the system is safe.
+static exit_safe: i32 = 0 ;
+/// Exit code when the system is unsafe.
+static exit_unsafe: i32 = 10 ;
+/// Exit code when the analysis is inconclusive.
+static exit_unknown: i32 = 20 ;
+/// Exit code on errors.
+static exit_error: i32 = 2 ;
+
+/// Writes the counterexample of each falsified property of a report to
+/// `<dir>/<property>.vcd`. Properties whose names give the same file name are
+/// disambiguated by an index suffix, see `system::file_names`.
+fn dump_vcds(dir: & str, report: & AnalysisReport) -> Result<(), String> {
+  use std::fs::{ File, DirBuilder } ;
+  use std::path::Path ;
+  try!(
+    DirBuilder::new().recursive(true).create(dir).map_err(
+      |e| format!("could not create directory \"{}\":\n> {}", dir, e)
+    )
+  ) ;
+  let falsified: Vec<_> = report.props.iter().filter_map(
+    |prop| prop.cex().map( |cex| (format!("{}", prop.sym()), cex) )
+  ).collect() ;
+  let names = system::file_names(
+    falsified.iter().map( |& (ref sym, _)| sym as & str )
+  ) ;
+  for (& (_, cex), name) in falsified.iter().zip( names.iter() ) {
+    let path = Path::new(dir).join( format!("{}.vcd", name) ) ;
+    let mut file = try!(
+      File::create(& path).map_err(
+        |e| format!(
+          "could not create file \"{}\":\n> {}", path.display(), e
+        )
+      )
+    ) ;
+    try!(
+      cex.write_vcd(& mut file).map_err(
+        |e| format!(
+          "could not write to file \"{}\":\n> {}", path.display(), e
+        )
+      )
+    )
+  }
+  Ok(())
+}
+
+/// Writes a system and some properties to a file as constrained Horn
+/// clauses, under some assumptions.
+fn dump_chc(
+  path: & str, sys: & Sys, props: & [Prop], assumptions: & [term::Term]
+) -> Result<(), String> {
+  use std::fs::File ;
+  let mut file = try!(
+    File::create(path).map_err(
+      |e| format!("could not create file \"{}\":\n> {}", path, e)
+    )
+  ) ;
+  system::chc::write(& mut file, sys, props, assumptions).map_err(
+    |e| format!("could not write to file \"{}\":\n> {}", path, e)
+  )
+}
+
+/// Reports the result of an analysis and returns the corresponding exit code.
+fn report<F: Formatter, S: Styler>(
+  log: & MasterLog<F, S>, format: OutFormat, vcd: Option<String>,
+  result: Result<AnalysisReport, ()>
+) -> i32 {
+  let report = match result {
+    Ok(report) => report,
+    Err(()) => {
+      report_error(log, format, "master did not return successfully") ;
+      return exit_error
+    },
+  } ;
+  if format == OutFormat::Json {
+    json::write_report(& mut stdout(), & report).expect(
+      "could not write to stdout"
+    )
+  }
+  if let Some(dir) = vcd {
+    if let Err(e) = dump_vcds(& dir, & report) {
+      report_error(log, format, & e) ;
+      return exit_error
+    }
+  }
+  if report.is_unsafe() {
+    exit_unsafe
+  } else if report.is_safe() {
+    exit_safe
+  } else {
+    exit_unknown
+  }
+}
+
+/// Reports an error in the output format specified.
+fn report_error<F: Formatter, S: Styler>(
+  log: & MasterLog<F, S>, format: OutFormat, blah: & str
+) {
+  if format == OutFormat::Json {
+    json::write_error(& mut stdout(), blah).expect(
+      "could not write to stdout"
+    )
+  } else {
+    log.bad(& Kino, blah)
+  }
+}
 
 fn main() {
   use std::fs::File ;
 
   let log = MasterLog::default() ;
 
-  log.sep() ;
-  log.sep() ;
-
   let (conf, file) = match common::conf::Master::mk(& log) {
     Ok(conf) => conf,
     Err(e) => {
+      log.sep() ;
+      log.sep() ;
       log.title("CLA parsing") ;
       log.nl() ;
       log.bad(& Kino, & e) ;
       log.trail() ;
       log.sep() ;
       log.sep() ;
-      exit(2)
+      exit(exit_error)
     },
   } ;
 
+  let format = conf.format ;
+  let vcd = conf.vcd.clone() ;
+  let chc = conf.chc.clone() ;
+  let log = if format == OutFormat::Json { log.silence() } else { log } ;
+
+  log.sep() ;
+  log.sep() ;
+
   let factory = term::Factory::mk() ;
   let mut context = Context::mk(factory, 10000) ;
   log.title( & format!("opening \"{}\"", file) ) ;
-  match File::open(& file) {
+  let code = match File::open(& file) {
     Ok(mut f) => {
       log.print( & log.mk_happy("success") ) ;
       log.title("parsing") ;
-      match context.read(& mut f) {
+      let res = kino_api::reader_of(& file)(& mut context, & mut f) ;
+      match res {
         Ok(res) => {
           log.print( & log.mk_happy("success") ) ;
 
@@ -82,41 +205,73 @@ fn main() {
 
           match res {
             Res::Success => panic!("update master to work with new parsers"),
-            Res::Exit => log.trail(),
+            Res::Exit => {
+              log.trail() ;
+              exit_safe
+            },
+            Res::Check(ref sys, ref props) |
+            Res::CheckAss(ref sys, ref props, _) if chc.is_some() => {
+              let path = chc.unwrap() ;
+              let assumptions = match res {
+                Res::CheckAss(_, _, ref assumptions) => assumptions.clone(),
+                _ => vec![],
+              } ;
+              log.title("CHC export") ;
+              match dump_chc(& path, sys, props, & assumptions) {
+                Ok(()) => {
+                  log.print(
+                    & log.mk_happy( & format!("written to \"{}\"", path) )
+                  ) ;
+                  log.trail() ;
+                  exit_safe
+                },
+                Err(e) => {
+                  report_error(& log, format, & e) ;
+                  log.trail() ;
+                  exit_error
+                },
+              }
+            },
             Res::Check(sys, props) => {
               log.trail() ;
-              match Master::launch(
+              let result = Master::launch(
                 & log, & mut context, sys, props, None, conf
-              ) {
-                Ok(()) => exit(0),
-                Err(()) => exit(2),
-              }
+              ) ;
+              report(& log, format, vcd, result)
             },
-            Res::CheckAss(_, _, _) => {
-              log.bad(
-                & Kino, "verify with assumption is not supported"
+            Res::CheckAss(sys, props, assumptions) => {
+              log.trail() ;
+              let result = Master::launch(
+                & log, & mut context, sys, props, Some(assumptions), conf
               ) ;
-              log.trail()
+              report(& log, format, vcd, result)
             },
           }
         },
         Err(e) => {
-          log.log_error(& Kino, & e.into()) ;
-          log.trail()
+          if format == OutFormat::Json {
+            report_error(& log, format, & format!("{}", e))
+          } else {
+            log.log_error(& Kino, & e.into())
+          }
+          log.trail() ;
+          exit_error
         },
       }
     },
     Err(e) => {
       log.nl() ;
-      log.bad(
-        & Kino,
+      report_error(
+        & log, format,
         & format!(
           "could not open file \"{}\":\n> {}", file, e
         )
       ) ;
-      log.trail()
+      log.trail() ;
+      exit_error
     },
   } ;
 
-  log.sep()
+  log.sep() ;
+  exit(code)
 }
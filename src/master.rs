@@ -12,13 +12,16 @@
 //! It runs on a system and tries to prove some properties.
 
 use std::sync::Arc ;
-use std::collections::HashMap ;
+use std::time::{ Instant, Duration } ;
+use std::collections::{ HashMap, HashSet } ;
 
-use term::{ Term, STermSet } ;
+use term::{ Sym, Term, STermSet } ;
 
-use system::{ Prop, Sys } ;
+use system::{ Prop, Sys, Inconsistency, file_names } ;
+use system::coi ;
 use system::ctxt::Context ;
 
+use common::Tek ;
 use common::Tek::Kino ;
 use common::conf ;
 use common::msg::MsgUp::* ;
@@ -30,6 +33,9 @@ use kind ;
 use twind ;
 use tig ;
 use pruner ;
+use pdr ;
+
+use report::AnalysisReport ;
 
 /// If the result is an error, prints it using `bad`.
 macro_rules! try_log {
@@ -61,18 +67,114 @@ macro_rules! try_log_run {
   )
 }
 
+/// Configurations of the techniques, shared by all the groups of
+/// properties.
+struct Teks {
+  /// Optional BMC configuration.
+  bmc: Option< Arc<conf::Bmc> >,
+  /// Optional Kind configuration.
+  kind: Option< Arc<conf::Kind> >,
+  /// Optional Twind configuration.
+  twind: Option< Arc<conf::Twind> >,
+  /// Optional TIG configuration.
+  tig: Option< Arc<conf::Tig> >,
+  /// Optional Pruner configuration.
+  pruner: Option< Arc<conf::Pruner> >,
+  /// Optional PDR configuration.
+  pdr: Option< Arc<conf::Pdr> >,
+}
+
+/// A group of properties analyzed by its own technique instances.
+struct Group {
+  /// System the techniques of the group run on.
+  sys: Sys,
+  /// Properties of the group.
+  props: Vec<Prop>,
+}
+
+/// Outcome of the analysis of some properties of a system.
+struct Run {
+  /// Technique that decided each property, and when.
+  decisions: HashMap<Sym, (Tek, Duration)>,
+  /// Duration of the analysis.
+  time: Duration,
+  /// Error if the message loop was exited because of an error.
+  result: Result<(), ()>,
+}
+impl Run {
+  /// Empty run.
+  fn empty() -> Self {
+    Run {
+      decisions: HashMap::new(), time: Duration::from_secs(0), result: Ok(()),
+    }
+  }
+  /// Merges the decisions, duration and result of a later run.
+  fn merge(& mut self, run: Run) {
+    self.decisions.extend( run.decisions ) ;
+    self.time = self.time + run.time ;
+    if run.result.is_err() { self.result = Err(()) }
+  }
+}
+
+/// Beginning of an analysis and its deadline, if any.
+struct Clock {
+  /// Beginning of the analysis, decision times are relative to it.
+  start: Instant,
+  /// Deadline of the whole analysis.
+  deadline: Option<Instant>,
+}
+
 /// Master, handles all the underlying techniques running in parallel.
 pub struct Master ;
 impl Master {
   /// Launches the master and all the techniques specified to try to prove that
   /// `props` are invariants for `sys`.
+  ///
+  /// If some `assumptions` are given, the techniques assert them at every step
+  /// of their unrollings.
+  ///
+  /// If `conf.coi` is set, the techniques run on the system sliced with
+  /// respect to the cone of influence of the properties and assumptions, see
+  /// [`coi`](../../system/coi/index.html). Counterexamples are lifted back to
+  /// `sys` and replayed on it, the ones that do not replay are ignored.
+  ///
+  /// If `conf.schedule` is set, the properties are grouped so that groups
+  /// have disjoint cones of influence. Each group is analyzed by its own
+  /// instances of the techniques, on its own slice of the system if
+  /// `conf.coi` is set. At most `conf.jobs` groups run at the same time, the
+  /// others are launched as groups finish. Results are aggregated in the
+  /// context. The techniques of a finished group are only asked to stop, see
+  /// `KidManager::stop_group`, so `conf.jobs` does not bound the number of
+  /// techniques actually running.
+  ///
+  /// If `conf.compositional` is set, the properties declared over the
+  /// sub-systems of `sys` are analyzed first, sub-systems first. The ones
+  /// proved are lifted as invariants of the callers by replacing the state
+  /// variables of the sub-system by the actual parameters of the call.
+  ///
+  /// Liveness properties are safety properties of the augmented system built
+  /// for each of them, which calls `sys`. They are analyzed after the other
+  /// properties, on their augmented system, with the invariants known for
+  /// `sys` and the properties proved.
+  ///
+  /// The timeout of `conf` is for the whole analysis, sub-systems and
+  /// liveness properties included. The times of the report are relative to
+  /// the beginning of the analysis.
+  ///
+  /// Returns a report of the analysis.
   pub fn launch<F: Formatter, S: Styler>(
     log: & MasterLog<F,S>, c: & mut Context,
     sys: Sys, props: Vec<Prop>,
-    _assumptions: Option<Vec<Term>>,
+    assumptions: Option<Vec<Term>>,
     conf: conf::Master
-  ) -> Result<(), ()> {
-    use std::time::Instant ;
+  ) -> Result<AnalysisReport, ()> {
+    let assumptions = assumptions.unwrap_or( vec![] ) ;
+
+    let start_time = Instant::now() ;
+    let global_deadline = conf.timeout.map(
+      |secs| start_time + Duration::from_secs(secs as u64)
+    ) ;
+    let clock = Clock { start: start_time, deadline: global_deadline } ;
 
     let mut invar_map = HashMap::new() ;
     invar_map.insert(sys.sym().get().clone(), STermSet::new()) ;
@@ -80,120 +182,235 @@ impl Master {
       invar_map.insert(sub, STermSet::new()) ; ()
     }
 
+    let mut conf = conf ;
+    let teks = Teks {
+      bmc: conf.bmc.take().map(Arc::new),
+      kind: conf.kind.take().map(Arc::new),
+      twind: conf.twind.take().map(Arc::new),
+      tig: conf.tig.take().map(Arc::new),
+      pruner: conf.pruner.take().map(Arc::new),
+      pdr: conf.pdr.take().map(Arc::new),
+    } ;
+
+    let mut run = Run::empty() ;
+
+    // Invariants lifted from the properties of the sub-systems.
+    let invs = if conf.compositional {
+      try!(
+        Master::sub_invariants(
+          log, c, & sys, & teks, & conf, & clock, & mut HashMap::new(),
+          & mut run
+        )
+      )
+    } else { STermSet::new() } ;
+
     log.title( & format!("Running on {}", sys.sym().sym()) ) ;
     log.nl() ;
 
-    // Creating manager for techniques.
-    let mut manager = KidManager::mk() ;
+    if ! invs.is_empty() {
+      log.log(
+        & Kino, & format!(
+          "{} invariant{} lifted from the sub-systems",
+          invs.len(), if invs.len() == 1 { "" } else { "s" }
+        )
+      )
+    }
 
-    // Launching BMC.
-    match conf.bmc {
-      None => (),
-      Some(conf) => if * conf.is_on() {
-        match manager.launch(
-          bmc::Bmc, sys.clone(), props.clone(), c.factory(), Arc::new(conf)
-        ) {
-          Ok(()) => (),
-          Err(errors) => {
-            log.bad(& Kino, "Error launching BMC:") ;
-            for err in errors.iter() {
-              log.bad(& Kino, & format!("> {}", err))
-            }
-            return Err(())
-          },
-        }
-      },
-    } ;
+    // Liveness properties are over their own augmented system.
+    let (safety, live): (Vec<Prop>, Vec<Prop>) = props.iter().cloned(
+    ).partition( |prop| prop.sys().sym() == sys.sym() ) ;
 
-    // Launching k-induction.
-    match conf.kind {
-      None => (),
-      Some(conf) => if * conf.is_on() {
-        match manager.launch(
-          kind::KInd, sys.clone(), props.clone(), c.factory(), Arc::new(conf)
-        ) {
-          Ok(()) => (),
-          Err(errors) => {
-            log.bad(& Kino, "Error launching K-induction:") ;
-            for err in errors.iter() {
-              log.bad(& Kino, & format!("> {}", err))
-            }
-            return Err(())
-          },
-        }
-      },
-    } ;
+    run.merge(
+      try!(
+        Master::analyze(
+          log, c, & sys, & safety, & assumptions, & invs, & teks, & conf,
+          & clock
+        )
+      )
+    ) ;
+    if ! live.is_empty() {
+      try!(
+        Master::analyze_live(
+          log, c, & sys, & safety, & live, & assumptions, & teks, & conf,
+          & clock, & mut run
+        )
+      )
+    }
+    let Run { decisions, time, result } = run ;
 
-    // Launching 2-induction.
-    match conf.twind {
-      None => (),
-      Some(conf) => if * conf.is_on() {
-        match manager.launch(
-          twind::Twind, sys.clone(), props.clone(), c.factory(), Arc::new(conf)
-        ) {
-          Ok(()) => (),
-          Err(errors) => {
-            log.bad(& Kino, "Error launching 2-induction:") ;
-            for err in errors.iter() {
-              log.bad(& Kino, & format!("> {}", err))
-            }
-            return Err(())
-          },
-        }
-      },
-    } ;
+    let some_prop_disproved = try_log_run!(
+      c.some_prop_disproved(& props), log, {
+        log.just_log_unknown() ;
+        return Err(())
+      }, "during post-run analysis"
+    ) ;
+    let some_prop_unknown = try_log_run!(
+      c.some_prop_unknown(& props), log, {
+        log.just_log_unknown() ;
+        return Err(())
+      }, "during post-run analysis"
+    ) ;
 
-    // Launching invgen.
-    match conf.tig {
-      None => (),
-      Some(conf) => if * conf.is_on() {
-        match manager.launch(
-          tig::Tig, sys.clone(), props.clone(), c.factory(), Arc::new(conf)
-        ) {
-          Ok(()) => (),
-          Err(errors) => {
-            log.bad(& Kino, "Error launching invariant generation:") ;
-            for err in errors.iter() {
-              log.bad(& Kino, & format!("> {}", err))
-            }
-            return Err(())
-          },
-        }
-      },
-    } ;
+    if ! some_prop_disproved {
+      if ! some_prop_unknown {
+        log.log_safe(time)
+      } else {
+        log.log_unknown(
+          try_log_run!(
+            c.unknown_props(& props), log, {
+              log.just_log_unknown() ;
+              return Err(())
+            }, "during post-run analysis"
+          ).into_iter(),
+          time
+        )
+      }
+    } else {
+      log.log_unsafe(time)
+    }
 
-    // Launching invgen.
-    match conf.pruner {
-      None => (),
-      Some(conf) => if * conf.is_on() {
-        match manager.launch(
-          pruner::Pruner, sys.clone(), props.clone(),
-          c.factory(), Arc::new(conf)
-        ) {
-          Ok(()) => (),
-          Err(errors) => {
-            log.bad(& Kino, "Error launching invariant pruner:") ;
-            for err in errors.iter() {
-              log.bad(& Kino, & format!("> {}", err))
-            }
-            return Err(())
-          },
+    if let Some(ref dir) = conf.certif {
+      let kind_conf = teks.kind.clone().unwrap_or_else(
+        || Arc::new( conf::Kind::default() )
+      ) ;
+      Master::certify(
+        log, c, & sys, & safety, & assumptions, & kind_conf, dir
+      )
+    }
+
+    if let Some(ref pruner_conf) = teks.pruner {
+      if * pruner_conf.minimize() {
+        Master::minimize(
+          log, c, & sys, & safety, & assumptions, & pruner_conf
+        )
+      }
+    }
+
+    log.trail() ;
+
+    result.map(
+      |()| AnalysisReport::mk(c, & sys, & props, & decisions, time)
+    )
+
+  }
+
+  /// Analyzes some properties of a system, see [`launch`](#method.launch).
+  ///
+  /// The invariants `lifted` are known to hold on `sys`. The techniques are
+  /// given the ones over the system they run on when they are launched.
+  fn analyze<F: Formatter, S: Styler>(
+    log: & MasterLog<F,S>, c: & mut Context,
+    sys: & Sys, props: & [Prop], assumptions: & [Term], lifted: & STermSet,
+    teks: & Teks, conf: & conf::Master, clock: & Clock
+  ) -> Result<Run, ()> {
+    if props.is_empty() {
+      return Ok( Run::empty() )
+    }
+
+    // System the techniques run on.
+    let sliced = if conf.coi {
+      coi::slice(c.factory(), sys, props, assumptions)
+    } else { sys.clone() } ;
+    if sliced.state().len() < sys.state().len() {
+      log.log(
+        & Kino, & format!(
+          "cone of influence: {} of {} state variables",
+          sliced.state().len(), sys.state().len()
+        )
+      )
+    }
+
+    // Invariants lifted from the sub-systems, the ones outside the slice
+    // are useless.
+    let lifted = coi::restrict(& sliced, lifted) ;
+    if ! lifted.is_empty() {
+      try_log!(
+        c.add_invs( sys.sym().get(), lifted.clone() ), log,
+        "while adding {} lifted invariants for {} to context",
+        lifted.len(), sys.sym()
+      )
+    }
+
+    // Groups of properties, each group has its own technique instances and
+    // runs on its own slice of the system.
+    let groups: Vec<Group> = if conf.schedule {
+      coi::groups(sys, props, assumptions).into_iter().map(
+        |props| Group {
+          sys: if conf.coi {
+            coi::slice(c.factory(), sys, & props, assumptions)
+          } else { sys.clone() },
+          props: props,
         }
-      },
+      ).collect()
+    } else {
+      vec![ Group { sys: sliced.clone(), props: props.to_vec() } ]
     } ;
+    if groups.len() > 1 {
+      log.log(
+        & Kino, & format!(
+          "{} groups of properties with disjoint cones of influence",
+          groups.len()
+        )
+      )
+    }
+    // Maximum number of groups running at the same time.
+    let max_running = conf.jobs.unwrap_or( groups.len() ) ;
 
-    // Result returned when exting the loop.
-    let mut result = Ok(()) ;
+    // Creating manager for techniques.
+    let mut manager = KidManager::mk() ;
 
     let start_time = Instant::now() ;
 
+    // Global deadline and technique-specific deadlines.
+    let global_deadline = clock.deadline ;
+    let mut deadlines = HashMap::new() ;
+
+    // Technique that decided each property.
+    let mut decisions = HashMap::with_capacity( props.len() ) ;
+
+    // Groups running, and index of the next group to launch.
+    let mut running = HashSet::with_capacity( max_running ) ;
+    let mut next_group = 0 ;
+
+    // Result returned when exting the loop.
+    let mut result = Ok(()) ;
+
     // Entering message loop.
     'msg_loop: loop {
+      // Forgetting groups that are done, their techniques are not running
+      // anymore or all their properties are decided.
+      let mut finished = vec![] ;
+      for group in running.iter() {
+        let done = manager.group_done(* group) || ! try_log_run!(
+          c.some_prop_unknown(& groups[* group].props), log, {
+            result = Err(()) ;
+            break 'msg_loop
+          },
+          "while checking for unknown properties"
+        ) ;
+        if done { finished.push(* group) }
+      }
+      for group in finished.into_iter() {
+        manager.stop_group(group) ;
+        deadlines.retain( |& (g, _), _| g != group ) ;
+        running.remove(& group) ; ()
+      }
+      // Launching groups.
+      while running.len() < max_running && next_group < groups.len() {
+        if Master::launch_group(
+          log, c, & mut manager, teks, next_group, & groups[next_group],
+          assumptions, & lifted, global_deadline, & mut deadlines
+        ).is_err() {
+          return Err(())
+        }
+        running.insert(next_group) ;
+        next_group += 1
+      }
       // Stopping if no more kids running.
       if manager.kids_done() { break } ;
       // Stopping if no property left to prove.
       if ! try_log_run!(
-        c.some_prop_unknown(& props), log, {
+        c.some_prop_unknown(props), log, {
           result = Err(()) ;
           break 'msg_loop
         },
@@ -202,8 +419,54 @@ impl Master {
         break 'msg_loop
       }
 
-      // Receiving a message.
-      match manager.recv() {
+      // Next deadline, if any.
+      let deadline = deadlines.values().chain(
+        global_deadline.iter()
+      ).min().cloned() ;
+
+      // Receiving a message, or handling timeouts.
+      let msg = match deadline {
+        None => manager.recv(),
+        Some(deadline) => {
+          let now = Instant::now() ;
+          let msg = if deadline > now {
+            manager.recv_timeout(deadline - now)
+          } else { Ok(None) } ;
+          match msg {
+            Ok( Some(msg) ) => Ok(msg),
+            Ok(None) => {
+              let now = Instant::now() ;
+              if global_deadline.map_or(false, |deadline| deadline <= now) {
+                log.sad(& Kino, "timeout, stopping all techniques") ;
+                manager.stop_all() ;
+                break 'msg_loop
+              }
+              let expired: Vec<(usize, Tek)> = deadlines.iter().filter_map(
+                |(kid, deadline)| if * deadline <= now {
+                  Some(* kid)
+                } else { None }
+              ).collect() ;
+              for (group, tek) in expired {
+                deadlines.remove(& (group, tek)) ;
+                log.sad(& tek, "timeout, stopping") ;
+                try_log!(
+                  manager.stop(group, & tek), log,
+                  "while stopping {} after timeout", tek
+                )
+              }
+              continue 'msg_loop
+            },
+            Err(e) => Err(e),
+          }
+        },
+      } ;
+
+      let (group, msg) = match msg {
+        Ok( (group, msg) ) => (group, Ok(msg)),
+        Err(e) => (0, Err(e)),
+      } ;
+
+      match msg {
 
         Ok( Bla(from, bla) ) => log.log(& from, & bla),
 
@@ -217,7 +480,45 @@ impl Master {
         Ok( Warning(from, bla) ) => log.sad(& from, & bla),
 
         Ok( Disproved(model, props, from, _) ) => {
-          let cex = c.cex_of(& model, & sys) ;
+          let group_sys = & groups[group].sys ;
+          let cex = c.cex_of(& model, group_sys) ;
+          // Replayed on the original system, the state variables sliced away
+          // might not have legal values.
+          let cex = if group_sys.state().len() < sys.state().len() {
+            cex.lift(c.factory(), sys)
+          } else { cex } ;
+          let replayed: Vec<Prop> = props.iter().filter_map(
+            |prop| c.get_prop(prop).map( |& (ref prop, _)| prop.clone() )
+          ).collect() ;
+          match cex.replay(c.factory(), & replayed) {
+            Ok(()) => (),
+            Err( Inconsistency::Eval(off, blah) ) => log.sad(
+              & Kino, & format!(
+                "could not replay counterexample from {} at state {}:\n> {}",
+                from, off, blah
+              )
+            ),
+            Err(e) => {
+              log.bad(
+                & Kino, & format!(
+                  "ignoring counterexample from {}, replay failed: {}",
+                  from, e
+                )
+              ) ;
+              // `from` is done with these properties, they are not
+              // forgotten by the rest of the group which might still decide
+              // them. Their status stays what it was, unknown or k-true.
+              for prop in props.iter() {
+                log.sad(
+                  & from, & format!(
+                    "{} is not decided by this technique, \
+                    it stays unknown", prop
+                  )
+                )
+              }
+              continue 'msg_loop
+            },
+          }
           for prop in props.iter() {
             try_log_run!(
               c.set_prop_false(prop, cex.clone()), log, {
@@ -225,10 +526,15 @@ impl Master {
                 break 'msg_loop
               },
               "on disproved message from {}", from
-            )
+            ) ;
+            decisions.insert(
+              prop.clone(), (from, clock.start.elapsed())
+            ) ;
           }
           log.log_cex(& from, & cex, & props) ;
-          manager.broadcast( MsgDown::Forget(props, Status::Disproved) ) ;
+          manager.broadcast_to(
+            group, MsgDown::Forget(props, Status::Disproved)
+          ) ;
         },
 
         Ok( Proved(props, from, info) ) => {
@@ -254,11 +560,16 @@ impl Master {
                 break 'msg_loop
               },
               "on proved message from {}", from
-            )
+            ) ;
+            decisions.insert(
+              prop.clone(), (from, clock.start.elapsed())
+            ) ;
           } ;
-          manager.broadcast( MsgDown::Forget(props, Status::Proved) ) ;
-          manager.broadcast(
-            MsgDown::Invariants(sys.sym().get().clone(), invs)
+          manager.broadcast_to(
+            group, MsgDown::Forget(props, Status::Proved)
+          ) ;
+          manager.broadcast_to(
+            group, MsgDown::Invariants(sys.sym().get().clone(), invs)
           ) ;
         },
 
@@ -270,16 +581,19 @@ impl Master {
                 break 'msg_loop
               },
               "on proved message from {}", from
-            )
+            ) ;
+            decisions.insert(
+              prop.clone(), (from, clock.start.elapsed())
+            ) ;
           }
-          manager.broadcast(
-            MsgDown::KTrue(props, o)
+          manager.broadcast_to(
+            group, MsgDown::KTrue(props, o)
           )
         },
 
         Ok( Invariants(from, sym, set, at) ) => {
           let prune_msg_sent = manager.prune_if_possible(
-            from, sym.clone(), set.clone(), at,
+            group, from, sym.clone(), set.clone(), at,
             |blah| log.bad(& Kino, blah)
           ) ;
           if ! prune_msg_sent {
@@ -300,8 +614,8 @@ impl Master {
               "while adding {} invariants for {} from {} to context",
               set.len(), sym, from
             ) ;
-            manager.broadcast(
-              MsgDown::Invariants( sym, set )
+            manager.broadcast_to(
+              group, MsgDown::Invariants( sym, set )
             )
           }
         },
@@ -344,25 +658,32 @@ impl Master {
             "while adding {} invariants for {} from {} to context",
             set.len(), sym, from
           ) ;
-          manager.broadcast(
-            MsgDown::Invariants( sym, set )
+          manager.broadcast_to(
+            group, MsgDown::Invariants( sym, set )
           )
         },
 
         Ok( Done(from, Info::At(k)) ) => {
           log.log( & from, & format!("done at {}", k) ) ;
-          try_log!(
-            manager.forget(& from), log,
-            "after reception of a `Done` at {} message from {}", k, from
-          )
+          deadlines.remove( & (group, from) ) ;
+          // Groups done are forgotten with all their techniques.
+          if running.contains(& group) {
+            try_log!(
+              manager.forget(group, & from), log,
+              "after reception of a `Done` at {} message from {}", k, from
+            )
+          }
         },
 
         Ok( Done(from, info) ) => {
           log.log(& from, & format!("done {}", info)) ;
-          try_log!(
-            manager.forget(& from), log,
-            "after reception of a `Done` message from {}", from
-          )
+          deadlines.remove( & (group, from) ) ;
+          if running.contains(& group) {
+            try_log!(
+              manager.forget(group, & from), log,
+              "after reception of a `Done` message from {}", from
+            )
+          }
         },
 
         Ok( msg ) => log.bad( & Kino, & format!("unknown message {}", msg) ),
@@ -376,40 +697,347 @@ impl Master {
 
     let time = Instant::now() - start_time ;
 
-    let some_prop_disproved = try_log_run!(
-      c.some_prop_disproved(& props), log, {
-        log.just_log_unknown() ;
-        return Err(())
-      }, "during post-run analysis"
-    ) ;
-    let some_prop_unknown = try_log_run!(
-      c.some_prop_unknown(& props), log, {
-        log.just_log_unknown() ;
-        return Err(())
-      }, "during post-run analysis"
-    ) ;
+    Ok(
+      Run { decisions: decisions, time: time, result: result }
+    )
+  }
 
-    if ! some_prop_disproved {
-      if ! some_prop_unknown {
-        log.log_safe(time)
-      } else {
-        log.log_unknown(
-          try_log_run!(
-            c.unknown_props(& props), log, {
-              log.just_log_unknown() ;
+  /// Analyzes the liveness properties of a system, see
+  /// [`launch`](#method.launch).
+  ///
+  /// The invariants known for `sys` and the properties of `props` proved are
+  /// lifted to the augmented system of each liveness property. Decisions,
+  /// durations and results are merged into `run`.
+  fn analyze_live<F: Formatter, S: Styler>(
+    log: & MasterLog<F,S>, c: & mut Context, sys: & Sys, props: & [Prop],
+    live: & [Prop], assumptions: & [Term], teks: & Teks,
+    conf: & conf::Master, clock: & Clock, run: & mut Run
+  ) -> Result<(), ()> {
+    use system::PropStatus::* ;
+
+    let mut invs = match c.get_invs( sys.sym().get() ) {
+      Some(invs) => invs.clone(),
+      None => STermSet::new(),
+    } ;
+    for prop in props.iter() {
+      match c.get_prop( prop.sym().get() ) {
+        Some( & (_, Invariant(_)) ) |
+        Some( & (_, MinInvariant(_, _)) ) => {
+          invs.insert( prop.body().clone() ) ; ()
+        },
+        _ => (),
+      }
+    }
+
+    for prop in live.iter() {
+      let live_sys = prop.sys() ;
+      // The augmented system only calls `sys`.
+      let mut lifted = STermSet::with_capacity( invs.len() ) ;
+      for inv in invs.iter() {
+        match live_sys.lift_inv(c.factory(), 0, inv) {
+          Ok(inv) => {
+            lifted.insert(inv) ; ()
+          },
+          Err(e) => {
+            log.bad(
+              & Kino, & format!(
+                "could not lift invariant {} of {} to {}:\n> {}",
+                inv, sys.sym(), live_sys.sym(), e
+              )
+            ) ;
+            return Err(())
+          },
+        }
+      }
+
+      log.title( & format!("Running on {}", live_sys.sym().sym()) ) ;
+      log.nl() ;
+      let live_run = try!(
+        Master::analyze(
+          log, c, live_sys, & [ prop.clone() ], assumptions, & lifted,
+          teks, conf, clock
+        )
+      ) ;
+      log.trail() ;
+      run.merge(live_run)
+    }
+
+    Ok(())
+  }
+
+  /// Invariants of a system lifted from the properties declared over its
+  /// sub-systems, recursively.
+  ///
+  /// The undecided properties of each sub-system are analyzed first, with the
+  /// invariants lifted from its own sub-systems. `known` maps the
+  /// sub-systems already handled to their invariants. Decisions, durations
+  /// and results are merged into `run`.
+  fn sub_invariants<F: Formatter, S: Styler>(
+    log: & MasterLog<F,S>, c: & mut Context, sys: & Sys,
+    teks: & Teks, conf: & conf::Master, clock: & Clock,
+    known: & mut HashMap<Sym, STermSet>, run: & mut Run
+  ) -> Result<STermSet, ()> {
+    use system::PropStatus::* ;
+
+    let mut lifted = STermSet::new() ;
+    for (index, & (ref sub, _)) in sys.subsys().iter().enumerate() {
+      let sym = sub.sym().get().clone() ;
+
+      if ! known.contains_key(& sym) {
+        let mut invs = try!(
+          Master::sub_invariants(
+            log, c, sub, teks, conf, clock, known, run
+          )
+        ) ;
+        let props = c.props_of(& sym) ;
+        let todo: Vec<Prop> = {
+          let unknown = try_log_run!(
+            c.unknown_props(& props), log, return Err(()),
+            "while checking the properties of {}", sym
+          ) ;
+          props.iter().filter(
+            |prop| unknown.contains( & prop.sym().get() )
+          ).cloned().collect()
+        } ;
+
+        if ! todo.is_empty() {
+          log.title( & format!("Running on sub-system {}", sub.sym().sym()) ) ;
+          log.nl() ;
+          let sub_run = try!(
+            Master::analyze(
+              log, c, sub, & todo, & [], & invs, teks, conf, clock
+            )
+          ) ;
+          log.trail() ;
+          if sub_run.result.is_err() { return Err(()) }
+          run.merge(sub_run)
+        }
+
+        for prop in props.iter() {
+          match c.get_prop( prop.sym().get() ) {
+            Some( & (_, Invariant(_)) ) |
+            Some( & (_, MinInvariant(_, _)) ) => {
+              invs.insert( prop.body().clone() ) ; ()
+            },
+            _ => (),
+          }
+        }
+        known.insert(sym.clone(), invs) ;
+      }
+
+      if let Some(invs) = known.get(& sym) {
+        for inv in invs.iter() {
+          match sys.lift_inv(c.factory(), index, inv) {
+            Ok(inv) => {
+              lifted.insert(inv) ; ()
+            },
+            Err(e) => {
+              log.bad(
+                & Kino, & format!(
+                  "could not lift invariant {} of {} to {}:\n> {}",
+                  inv, sym, sys.sym(), e
+                )
+              ) ;
               return Err(())
-            }, "during post-run analysis"
-          ).into_iter(),
-          time
+            },
+          }
+        }
+      }
+    }
+
+    Ok(lifted)
+  }
+
+  /// Launches the techniques on a group of properties.
+  ///
+  /// The solvers of a technique cannot run past its own deadline, if any, or
+  /// `global_deadline`.
+  fn launch_group<F: Formatter, S: Styler>(
+    log: & MasterLog<F,S>, c: & Context, manager: & mut KidManager,
+    teks: & Teks, index: usize, group: & Group, assumptions: & [Term],
+    lifted: & STermSet, global_deadline: Option<Instant>,
+    deadlines: & mut HashMap<(usize, Tek), Instant>
+  ) -> Result<(), ()> {
+    let start_time = Instant::now() ;
+
+    // Launches a technique if it is on, registers its deadline if any.
+    macro_rules! launch {
+      ($conf:expr, $tek:expr, $tec:expr, $desc:expr) => (
+        if let Some(ref conf) = $conf {
+          if * conf.is_on() {
+            let deadline = conf.timeout().map(
+              |secs| start_time + Duration::from_secs(secs as u64)
+            ) ;
+            if let Some(deadline) = deadline {
+              deadlines.insert( (index, $tek), deadline ) ;
+            }
+            let deadline = deadline.into_iter().chain(
+              global_deadline
+            ).min() ;
+            match manager.launch(
+              index, $tec, group.sys.clone(), group.props.clone(),
+              assumptions, c.factory(), conf.clone(), deadline
+            ) {
+              Ok(()) => (),
+              Err(errors) => {
+                log.bad(& Kino, & format!("Error launching {}:", $desc)) ;
+                for err in errors.iter() {
+                  log.bad(& Kino, & format!("> {}", err))
+                }
+                return Err(())
+              },
+            }
+          }
+        }
+      ) ;
+    }
+
+    launch!(teks.bmc, Tek::Bmc, bmc::Bmc, "BMC") ;
+    launch!(teks.kind, Tek::KInd, kind::KInd, "K-induction") ;
+    launch!(teks.twind, Tek::Twind, twind::Twind, "2-induction") ;
+    launch!(teks.tig, Tek::Tig, tig::Tig, "invariant generation") ;
+    launch!(teks.pruner, Tek::Pruner, pruner::Pruner, "invariant pruner") ;
+    launch!(teks.pdr, Tek::Pdr, pdr::Pdr, "PDR") ;
+
+    // Invariants lifted from the sub-systems, over the slice of the group.
+    let lifted = coi::restrict(& group.sys, lifted) ;
+    if ! lifted.is_empty() {
+      manager.broadcast_to(
+        index, MsgDown::Invariants(group.sys.sym().get().clone(), lifted)
+      )
+    }
+
+    Ok(())
+  }
+
+  /// Writes a certificate for each proved property in `<dir>/<prop>.smt2`,
+  /// see `system::file_names` for the file name of a property.
+  ///
+  /// The strengthening is the conjunction of all the invariants discovered
+  /// for the system and all the properties proved, `k` is the maximal `k` of
+  /// the properties proved. Each certificate is checked by the solver of
+  /// `kind_conf` while it is written.
+  fn certify<F: Formatter, S: Styler>(
+    log: & MasterLog<F,S>, c: & Context, sys: & Sys, props: & [Prop],
+    assumptions: & [Term], kind_conf: & conf::Kind, dir: & str
+  ) {
+    use std::fs::DirBuilder ;
+    use system::PropStatus::* ;
+
+    let mut invs = match c.get_invs( sys.sym().get() ) {
+      Some(invs) => invs.clone(),
+      None => STermSet::new(),
+    } ;
+    let mut proved = Vec::with_capacity( props.len() ) ;
+    let mut k = 1 ;
+    for prop in props.iter() {
+      match c.get_prop( prop.sym().get() ) {
+        Some( & (_, Invariant(n)) ) |
+        Some( & (_, MinInvariant(n, _)) ) => {
+          invs.insert( prop.body().clone() ) ;
+          if n > k { k = n }
+          proved.push( prop.clone() )
+        },
+        _ => (),
+      }
+    }
+    if proved.is_empty() { return () }
+
+    if let Err(e) = DirBuilder::new().recursive(true).create(dir) {
+      log.bad(
+        & Kino, & format!(
+          "could not create certificate directory \"{}\":\n> {}", dir, e
         )
+      ) ;
+      return ()
+    }
+
+    // Property names giving the same file name get an index suffix.
+    let syms: Vec<String> = proved.iter().map(
+      |prop| format!("{}", prop.sym())
+    ).collect() ;
+    let names = file_names( syms.iter().map( |sym| sym as & str ) ) ;
+
+    for (prop, name) in proved.iter().zip( names.iter() ) {
+      match kind::certify(
+        kind_conf, c.factory(), sys, & proved, assumptions, & invs, k,
+        dir, name
+      ) {
+        Ok(true) => log.log(
+          & Kino, & format!(
+            "certificate for {} written to \"{}/{}.smt2\"",
+            prop.sym(), dir, name
+          )
+        ),
+        Ok(false) => log.bad(
+          & Kino, & format!(
+            "certificate for {} does not check, \
+            the strengthening is not {}-inductive", prop.sym(), k
+          )
+        ),
+        Err(e) => log.bad(
+          & Kino, & format!(
+            "error while writing certificate for {}:\n{}", prop.sym(), e
+          )
+        ),
       }
-    } else {
-      log.log_unsafe(time)
     }
+  }
 
-    log.trail() ;
+  /// Minimizes the lemmas of each property proved, see
+  /// [`pruner::minimize`](../../pruner/fn.minimize.html).
+  ///
+  /// The lemmas of a property are the invariants discovered for the system
+  /// and the other properties proved.
+  fn minimize<F: Formatter, S: Styler>(
+    log: & MasterLog<F,S>, c: & mut Context, sys: & Sys, props: & [Prop],
+    assumptions: & [Term], pruner_conf: & conf::Pruner
+  ) {
+    use system::PropStatus::* ;
 
-    result
+    let mut lemmas = match c.get_invs( sys.sym().get() ) {
+      Some(invs) => invs.clone(),
+      None => STermSet::new(),
+    } ;
+    let mut proved = Vec::with_capacity( props.len() ) ;
+    for prop in props.iter() {
+      match c.get_prop( prop.sym().get() ) {
+        Some( & (_, Invariant(k)) ) |
+        Some( & (_, MinInvariant(k, _)) ) => {
+          lemmas.insert( prop.body().clone() ) ;
+          proved.push( (prop.clone(), k) )
+        },
+        _ => (),
+      }
+    }
 
+    for (prop, k) in proved.into_iter() {
+      let res = pruner::minimize(
+        pruner_conf, c.factory(), sys, props, assumptions, & prop, & lemmas, k
+      ) ;
+      match res {
+        Ok( (min_k, min_lemmas) ) => {
+          // The property itself is not one of its lemmas.
+          let lemma_count = lemmas.iter().filter(
+            |lemma| * lemma != prop.body()
+          ).count() ;
+          log.log(
+            & Tek::Pruner, & format!(
+              "{} is {}-inductive with {} of {} lemmas",
+              prop.sym(), min_k, min_lemmas.len(), lemma_count
+            )
+          ) ;
+          try_log!(
+            c.set_prop_min_inv( prop.sym().get(), min_k, min_lemmas ), log,
+            "while updating property {}", prop.sym()
+          )
+        },
+        Err(e) => log.bad(
+          & Tek::Pruner, & format!(
+            "could not minimize the lemmas of {}:\n{}", prop.sym(), e
+          )
+        ),
+      }
+    }
   }
 }
\ No newline at end of file
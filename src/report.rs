@@ -0,0 +1,140 @@
+// Copyright 2015 Adrien Champion. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Structured results of an analysis.
+
+use std::time::Duration ;
+use std::collections::HashMap ;
+
+use term::{ Sym, STermSet } ;
+
+use system::{ Prop, Sys, Cex, PropStatus } ;
+use system::ctxt::Context ;
+
+use common::Tek ;
+
+/// Result of the analysis for a property.
+#[derive(Clone)]
+pub struct PropReport {
+  /// The property.
+  pub prop: Prop,
+  /// Final status of the property.
+  pub status: PropStatus,
+  /// Technique that decided the status, `None` if the property is unknown.
+  pub tek: Option<Tek>,
+  /// Time elapsed between the beginning of the analysis and the moment the
+  /// status was decided, `None` if the property is unknown.
+  pub time: Option<Duration>,
+}
+impl PropReport {
+  /// Identifier of the property.
+  #[inline]
+  pub fn sym(& self) -> & Sym { self.prop.sym().get() }
+  /// Counterexample for the property, if it was falsified.
+  #[inline]
+  pub fn cex(& self) -> Option<& Cex> {
+    match self.status {
+      PropStatus::Falsified(ref cex) => Some(cex),
+      _ => None,
+    }
+  }
+  /// True iff the property was proved.
+  #[inline]
+  pub fn is_invariant(& self) -> bool {
+    match self.status {
+      PropStatus::Invariant(_) | PropStatus::MinInvariant(_, _) => true,
+      _ => false,
+    }
+  }
+  /// True iff the property was falsified.
+  #[inline]
+  pub fn is_falsified(& self) -> bool {
+    self.cex().is_some()
+  }
+}
+
+/// Result of an analysis.
+#[derive(Clone)]
+pub struct AnalysisReport {
+  /// One report per property, in the order of the query.
+  pub props: Vec<PropReport>,
+  /// Invariants discovered for the system analyzed and its subsystems.
+  pub invariants: HashMap<Sym, STermSet>,
+  /// Total runtime of the analysis.
+  pub time: Duration,
+}
+impl AnalysisReport {
+  /// Report for a query with no properties.
+  pub fn empty() -> Self {
+    AnalysisReport {
+      props: vec![],
+      invariants: HashMap::new(),
+      time: Duration::from_secs(0),
+    }
+  }
+
+  /// Creates a report from the context after an analysis.
+  ///
+  /// `decisions` maps properties to the technique that decided their status
+  /// and when.
+  pub fn mk(
+    context: & Context, sys: & Sys, props: & [Prop],
+    decisions: & HashMap<Sym, (Tek, Duration)>, time: Duration
+  ) -> Self {
+    let mut prop_reports = Vec::with_capacity( props.len() ) ;
+    for prop in props.iter() {
+      let sym = prop.sym().get() ;
+      let status = match context.get_prop(sym) {
+        Some( & (_, ref status) ) => status.clone(),
+        None => PropStatus::Unknown,
+      } ;
+      let (tek, time) = match decisions.get(sym) {
+        Some( & (tek, time) ) => ( Some(tek), Some(time) ),
+        None => (None, None),
+      } ;
+      prop_reports.push(
+        PropReport {
+          prop: prop.clone(), status: status, tek: tek, time: time
+        }
+      )
+    }
+
+    let mut invariants = HashMap::new() ;
+    let mut syms = sys.subsys_syms() ;
+    syms.insert( sys.sym().get().clone() ) ;
+    for sym in syms.into_iter() {
+      if let Some(invs) = context.get_invs(& sym) {
+        invariants.insert( sym, invs.clone() ) ; ()
+      }
+    }
+
+    AnalysisReport {
+      props: prop_reports, invariants: invariants, time: time
+    }
+  }
+
+  /// Report of a property.
+  pub fn get(& self, sym: & Sym) -> Option<& PropReport> {
+    self.props.iter().find( |prop| prop.sym() == sym )
+  }
+
+  /// True iff all the properties were proved.
+  pub fn is_safe(& self) -> bool {
+    self.props.iter().all( |prop| prop.is_invariant() )
+  }
+  /// True iff some property was falsified.
+  pub fn is_unsafe(& self) -> bool {
+    self.props.iter().any( |prop| prop.is_falsified() )
+  }
+  /// True iff the analysis is inconclusive: no property was falsified, and
+  /// some were not proved.
+  pub fn is_unknown(& self) -> bool {
+    ! self.is_safe() && ! self.is_unsafe()
+  }
+}
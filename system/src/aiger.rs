@@ -0,0 +1,533 @@
+// Copyright 2016 Adrien Champion. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*! AIGER front-end.
+
+Translates an and-inverter graph, in the ASCII (`aag`) or binary (`aig`)
+AIGER format, to VMT-LIB. The graph becomes a single system `aiger` over
+Booleans:
+
+* inputs and latches are state variables, named after their symbol when it
+  is legal and unique, `i<k>` and `l<k>` otherwise; inputs are
+  unconstrained,
+* latches are initialized to their reset value, latches reset to themselves
+  are unconstrained initially,
+* constraints hold in all the states of the traces,
+* each bad state literal is a property stating it is false, named after its
+  symbol or `b<k>` otherwise. Graphs with no bad state section use their
+  outputs instead, named `o<k>` by default,
+* the and gates used more than once are state variables `%<var>`, defined
+  in the init and trans predicates. They are hidden in counterexamples.
+
+Justice and fairness sections are not supported. */
+
+use std::collections::{ HashMap, HashSet } ;
+
+use { Error, Line } ;
+use translation::{ Translation, legal_sym, quote, conj } ;
+
+/// Result of a translation step, errors are a position in the graph and a
+/// message.
+type AigRes<T> = Result<T, (usize, String)> ;
+
+/// Name of the system of a graph.
+static sys_name: & 'static str = "aiger" ;
+
+/// Reads the lines and the binary and gates of a graph.
+struct Reader<'a> {
+  /// The graph.
+  src: & 'a [u8],
+  /// Current position.
+  pos: usize,
+}
+impl<'a> Reader<'a> {
+  /// Next line and its position, `None` at the end of the graph.
+  fn line(& mut self) -> AigRes< Option<(usize, & 'a str)> > {
+    let bgn = self.pos ;
+    if bgn >= self.src.len() { return Ok(None) }
+    let end = self.src[bgn..].iter().position(
+      |c| * c == b'\n'
+    ).map( |n| bgn + n ).unwrap_or( self.src.len() ) ;
+    self.pos = end + 1 ;
+    match ::std::str::from_utf8(& self.src[bgn..end]) {
+      Ok(line) => Ok( Some( (bgn, line) ) ),
+      Err(_) => Err( (bgn, "illegal UTF-8 line".into()) ),
+    }
+  }
+
+  /// Next line as a list of between `min` and `max` numbers, and its
+  /// position.
+  fn nums(
+    & mut self, min: usize, max: usize, what: & str
+  ) -> AigRes< (usize, Vec<usize>) > {
+    let (pos, line) = match try!( self.line() ) {
+      Some(line) => line,
+      None => return Err(
+        (self.src.len(), format!("expected {}, reached end of file", what))
+      ),
+    } ;
+    let mut nums = Vec::with_capacity(max) ;
+    for tok in line.split_whitespace() {
+      match tok.parse::<usize>() {
+        Ok(n) => nums.push(n),
+        Err(_) => return Err(
+          (pos, format!("expected {}, got `{}`", what, tok))
+        ),
+      }
+    }
+    if nums.len() < min || nums.len() > max {
+      return Err( (pos, format!("illegal {}", what)) )
+    }
+    Ok( (pos, nums) )
+  }
+
+  /// Next binary-encoded number.
+  fn delta(& mut self) -> AigRes<usize> {
+    let bgn = self.pos ;
+    let mut n = 0usize ;
+    let mut shift = 0 ;
+    loop {
+      let byte = match self.src.get(self.pos) {
+        Some(byte) => * byte,
+        None => return Err(
+          (bgn, "reached end of file while reading and gates".into())
+        ),
+      } ;
+      self.pos += 1 ;
+      if shift >= 63 {
+        return Err( (bgn, "illegal binary and gate".into()) )
+      }
+      n |= ( (byte & 0x7f) as usize ) << shift ;
+      if byte & 0x80 == 0 { return Ok(n) }
+      shift += 7
+    }
+  }
+}
+
+/// A variable of the graph.
+#[derive(Debug, Clone, Copy)]
+enum Node {
+  /// Input, its index.
+  Input(usize),
+  /// Latch, its index.
+  Latch(usize),
+  /// And gate over two literals.
+  And(usize, usize),
+}
+
+/// A parsed graph.
+struct Aig {
+  /// Variables, indexed by their identifier.
+  nodes: Vec< Option<Node> >,
+  /// Latches: literal, next literal, reset value and position.
+  latches: Vec<(usize, usize, usize, usize)>,
+  /// Outputs and their position.
+  outputs: Vec<(usize, usize)>,
+  /// Bad states and their position.
+  bads: Vec<(usize, usize)>,
+  /// Constraints and their position.
+  constraints: Vec<(usize, usize)>,
+  /// Symbols, indexed by kind and index.
+  symbols: HashMap<(char, usize), String>,
+}
+impl Aig {
+  /// Defines a variable.
+  fn define(& mut self, lit: usize, node: Node, pos: usize) -> AigRes<()> {
+    let var = lit / 2 ;
+    if lit % 2 != 0 || var == 0 || var >= self.nodes.len() {
+      return Err( (pos, format!("illegal literal {}", lit)) )
+    }
+    if self.nodes[var].is_some() {
+      return Err( (pos, format!("variable {} is defined twice", var)) )
+    }
+    self.nodes[var] = Some(node) ;
+    Ok(())
+  }
+
+  /// Checks a literal is defined.
+  fn check(& self, lit: usize, pos: usize) -> AigRes<()> {
+    let var = lit / 2 ;
+    if var == 0 {
+      Ok(())
+    } else if var < self.nodes.len() && self.nodes[var].is_some() {
+      Ok(())
+    } else {
+      Err( (pos, format!("undefined literal {}", lit)) )
+    }
+  }
+}
+
+/// Parses a graph.
+fn parse(src: & [u8]) -> AigRes<Aig> {
+  let mut reader = Reader { src: src, pos: 0 } ;
+
+  // Header.
+  let (binary, header) = match try!( reader.line() ) {
+    Some( (_, line) ) if line.starts_with("aag ") => (false, & line[4..]),
+    Some( (_, line) ) if line.starts_with("aig ") => (true, & line[4..]),
+    _ => return Err( (0, "expected `aag` or `aig` header".into()) ),
+  } ;
+  let mut counts = Vec::with_capacity(9) ;
+  for tok in header.split_whitespace() {
+    match tok.parse::<usize>() {
+      Ok(n) => counts.push(n),
+      Err(_) => return Err( (4, format!("illegal header count `{}`", tok)) ),
+    }
+  }
+  if counts.len() < 5 || counts.len() > 9 {
+    return Err( (4, "expected 5 to 9 header counts".into()) )
+  }
+  while counts.len() < 9 { counts.push(0) }
+  let (max, i, l, o) = (counts[0], counts[1], counts[2], counts[3]) ;
+  let (a, b, c) = (counts[4], counts[5], counts[6]) ;
+  if counts[7] > 0 || counts[8] > 0 {
+    return Err(
+      (4, "justice and fairness properties are not supported".into())
+    )
+  }
+  if binary && max != i + l + a {
+    return Err( (4, "binary header count M should be I + L + A".into()) )
+  }
+
+  let mut aig = Aig {
+    nodes: vec![ None ; max + 1 ], latches: Vec::with_capacity(l),
+    outputs: Vec::with_capacity(o), bads: Vec::with_capacity(b),
+    constraints: Vec::with_capacity(c), symbols: HashMap::new(),
+  } ;
+
+  for k in 0..i {
+    if binary {
+      try!( aig.define( 2 * (k + 1), Node::Input(k), 0 ) )
+    } else {
+      let (pos, lits) = try!( reader.nums(1, 1, "input literal") ) ;
+      try!( aig.define( lits[0], Node::Input(k), pos ) )
+    }
+  }
+  for k in 0..l {
+    let (pos, mut lits) = if binary {
+      let (pos, mut lits) = try!( reader.nums(1, 2, "latch") ) ;
+      lits.insert( 0, 2 * (i + k + 1) ) ;
+      (pos, lits)
+    } else {
+      try!( reader.nums(2, 3, "latch") )
+    } ;
+    if lits.len() < 3 { lits.push(0) }
+    try!( aig.define( lits[0], Node::Latch(k), pos ) ) ;
+    if lits[2] != 0 && lits[2] != 1 && lits[2] != lits[0] {
+      return Err( (pos, format!("illegal reset value {}", lits[2])) )
+    }
+    aig.latches.push( (lits[0], lits[1], lits[2], pos) )
+  }
+  for _ in 0..o {
+    let (pos, lits) = try!( reader.nums(1, 1, "output literal") ) ;
+    aig.outputs.push( (lits[0], pos) )
+  }
+  for _ in 0..b {
+    let (pos, lits) = try!( reader.nums(1, 1, "bad state literal") ) ;
+    aig.bads.push( (lits[0], pos) )
+  }
+  for _ in 0..c {
+    let (pos, lits) = try!( reader.nums(1, 1, "constraint literal") ) ;
+    aig.constraints.push( (lits[0], pos) )
+  }
+  let mut ands = Vec::with_capacity(a) ;
+  for k in 0..a {
+    if binary {
+      let pos = reader.pos ;
+      let lhs = 2 * (i + l + k + 1) ;
+      let delta0 = try!( reader.delta() ) ;
+      let delta1 = try!( reader.delta() ) ;
+      if delta0 > lhs || delta1 > lhs - delta0 {
+        return Err( (pos, "illegal binary and gate".into()) )
+      }
+      let rhs0 = lhs - delta0 ;
+      ands.push( (lhs, rhs0, rhs0 - delta1, pos) )
+    } else {
+      let (pos, lits) = try!( reader.nums(3, 3, "and gate") ) ;
+      ands.push( (lits[0], lits[1], lits[2], pos) )
+    }
+  }
+  for & (lhs, rhs0, rhs1, pos) in ands.iter() {
+    try!( aig.define( lhs, Node::And(rhs0, rhs1), pos ) )
+  }
+
+  // Checking all literals are defined.
+  for & (_, rhs0, rhs1, pos) in ands.iter() {
+    try!( aig.check(rhs0, pos) ) ;
+    try!( aig.check(rhs1, pos) )
+  }
+  for & (_, next, _, pos) in aig.latches.iter() {
+    try!( aig.check(next, pos) )
+  }
+  for & (lit, pos) in aig.outputs.iter().chain(
+    aig.bads.iter()
+  ).chain( aig.constraints.iter() ) {
+    try!( aig.check(lit, pos) )
+  }
+
+  // Symbol table, until the end of file or the comment section.
+  while let Some( (pos, line) ) = try!( reader.line() ) {
+    if line == "c" { break }
+    let kind = line.chars().next().unwrap_or(' ') ;
+    let space = match line.find(' ') {
+      Some(space) if "ilobc".contains(kind) && space > 0 => space,
+      _ => return Err( (pos, "illegal symbol".into()) ),
+    } ;
+    let idx = match line[1..space].parse::<usize>() {
+      Ok(idx) => idx,
+      _ => return Err( (pos, "illegal symbol".into()) ),
+    } ;
+    aig.symbols.insert( (kind, idx), line[space + 1 ..].to_string() ) ; ()
+  }
+
+  Ok(aig)
+}
+
+/// Translation context of a graph.
+struct Ctx<'a> {
+  /// The graph.
+  aig: & 'a Aig,
+  /// State variable of each variable, if any.
+  svars: Vec< Option<String> >,
+  /// Names used so far.
+  names: HashSet<String>,
+}
+impl<'a> Ctx<'a> {
+  /// Legal and unique name for a symbol.
+  fn name(& mut self, kind: char, idx: usize) -> String {
+    let mut name = match self.aig.symbols.get( & (kind, idx) ) {
+      Some(sym) if legal_sym(sym) => sym.clone(),
+      _ => format!("{}{}", kind, idx),
+    } ;
+    while self.names.contains(& name) {
+      name = format!("{}#{}", name, idx)
+    }
+    self.names.insert( name.clone() ) ;
+    quote(& name)
+  }
+
+  /// Writes a literal, its state variables being in state `st`.
+  fn lit(& self, lit: usize, st: & str, s: & mut String) {
+    if lit == 0 {
+      s.push_str("false")
+    } else if lit == 1 {
+      s.push_str("true")
+    } else if lit % 2 == 1 {
+      s.push_str("(not ") ;
+      self.lit(lit - 1, st, s) ;
+      s.push(')')
+    } else if let Some(ref svar) = self.svars[lit / 2] {
+      s.push_str("(_ ") ;
+      s.push_str(st) ;
+      s.push(' ') ;
+      s.push_str(svar) ;
+      s.push(')')
+    } else if let Some( Node::And(rhs0, rhs1) ) = self.aig.nodes[lit / 2] {
+      s.push_str("(and ") ;
+      self.lit(rhs0, st, s) ;
+      s.push(' ') ;
+      self.lit(rhs1, st, s) ;
+      s.push(')')
+    } else {
+      unreachable!()
+    }
+  }
+
+  /// String of a literal, its state variables being in state `st`.
+  fn lit_str(& self, lit: usize, st: & str) -> String {
+    let mut s = String::new() ;
+    self.lit(lit, st, & mut s) ;
+    s
+  }
+}
+
+/// Translates a graph, errors are positions in the graph.
+fn translate_internal(src: & [u8]) -> AigRes<Translation> {
+  let aig = try!( parse(src) ) ;
+
+  let props: Vec<(char, usize)> = if ! aig.bads.is_empty() {
+    aig.bads.iter().map( |& (lit, _)| ('b', lit) ).collect()
+  } else {
+    aig.outputs.iter().map( |& (lit, _)| ('o', lit) ).collect()
+  } ;
+  if props.is_empty() {
+    return Err( (src.len(), "no bad state or output to check".into()) )
+  }
+
+  // Counting the references to each variable.
+  let mut refs = vec![ 0usize ; aig.nodes.len() ] ;
+  for node in aig.nodes.iter() {
+    if let Some( Node::And(rhs0, rhs1) ) = * node {
+      refs[rhs0 / 2] += 1 ;
+      refs[rhs1 / 2] += 1
+    }
+  }
+  for & (_, next, _, _) in aig.latches.iter() {
+    refs[next / 2] += 1
+  }
+  for & (_, lit) in props.iter() {
+    refs[lit / 2] += 1
+  }
+  for & (lit, _) in aig.constraints.iter() {
+    refs[lit / 2] += 1
+  }
+
+  let mut ctx = Ctx {
+    aig: & aig, svars: vec![ None ; aig.nodes.len() ], names: HashSet::new(),
+  } ;
+  ctx.names.insert( sys_name.to_string() ) ;
+  let mut vmt = format!("(define-sys {}\n  (\n", sys_name) ;
+  let mut wires = vec![] ;
+  let mut hidden = vec![] ;
+  for (var, node) in aig.nodes.iter().enumerate() {
+    let svar = match * node {
+      Some( Node::Input(k) ) => ctx.name('i', k),
+      Some( Node::Latch(k) ) => ctx.name('l', k),
+      Some( Node::And(_, _) ) if refs[var] > 1 => {
+        wires.push(var) ;
+        hidden.push( format!("%{}", var) ) ;
+        quote( & format!("%{}", var) )
+      },
+      _ => continue,
+    } ;
+    vmt.push_str( & format!("    ({} Bool)\n", svar) ) ;
+    ctx.svars[var] = Some(svar)
+  }
+  vmt.push_str("  )\n") ;
+
+  let mut init = vec![] ;
+  let mut trans = vec![] ;
+  for & (lit, next, reset, _) in aig.latches.iter() {
+    if reset != lit {
+      init.push(
+        format!(
+          "(= {} {})", ctx.lit_str(lit, "curr"), ctx.lit_str(reset, "curr")
+        )
+      )
+    }
+    trans.push(
+      format!(
+        "(= {} {})", ctx.lit_str(lit, "next"), ctx.lit_str(next, "curr")
+      )
+    )
+  }
+  for var in wires.iter() {
+    if let Some( Node::And(rhs0, rhs1) ) = aig.nodes[* var] {
+      let def = |st: & str| format!(
+        "(= {} (and {} {}))", ctx.lit_str(2 * var, st),
+        ctx.lit_str(rhs0, st), ctx.lit_str(rhs1, st)
+      ) ;
+      init.push( def("curr") ) ;
+      trans.push( def("next") )
+    }
+  }
+  for & (lit, _) in aig.constraints.iter() {
+    init.push( ctx.lit_str(lit, "curr") ) ;
+    trans.push( ctx.lit_str(lit, "next") )
+  }
+  vmt.push_str( & format!("{}\n{}\n  ()\n)\n\n", conj(init), conj(trans)) ) ;
+
+  let mut names = Vec::with_capacity( props.len() ) ;
+  for (idx, & (kind, lit)) in props.iter().enumerate() {
+    let name = ctx.name(kind, idx) ;
+    vmt.push_str(
+      & format!(
+        "(define-prop {} {}\n  {}\n)\n", name, sys_name,
+        ctx.lit_str(lit ^ 1, "curr")
+      )
+    ) ;
+    names.push(name)
+  }
+  vmt.push_str( & format!("\n(verify {} ({}))\n", sys_name, names.join(" ")) ) ;
+
+  let mut hidden_map = HashMap::new() ;
+  hidden_map.insert( sys_name.to_string(), hidden ) ;
+  Ok( Translation { vmt: vmt, hidden: hidden_map } )
+}
+
+/// Translates an AIGER graph, ASCII or binary, to VMT-LIB.
+pub fn translate(src: & [u8]) -> Result<Translation, Error> {
+  translate_internal(src).map_err(
+    |(pos, blah)| Error::parse_mk( Line::of_byte_pos(src, pos), blah, vec![] )
+  )
+}
+
+
+#[cfg(test)]
+mod test {
+  use super::translate ;
+
+  /// Translates a graph, prints the error if any.
+  fn tr(src: & [u8]) -> Result<String, String> {
+    translate(src).map( |t| t.vmt ).map_err(
+      |e| { e.print() ; format!("{}", e) }
+    )
+  }
+
+  #[test]
+  fn ascii() {
+    let vmt = tr(b"\
+      aag 6 1 2 0 3 1 1\n\
+      2\n\
+      4 11 0\n\
+      6 6 6\n\
+      6\n\
+      3\n\
+      8 4 2\n\
+      10 9 3\n\
+      12 8 8\n\
+      i0 en\n\
+      l1 free\n\
+      b0 free_is_false\n\
+      c\n\
+      Some comment.\n\
+    ").unwrap() ;
+    assert!( vmt.contains("(en Bool)") ) ;
+    assert!( vmt.contains("(l0 Bool)") ) ;
+    assert!( vmt.contains("(free Bool)") ) ;
+    // Gate 4 is used twice.
+    assert!( vmt.contains("(|%4| Bool)") ) ;
+    assert!( ! vmt.contains("(|%5| Bool)") ) ;
+    assert!( vmt.contains("(= (_ curr l0) false)") ) ;
+    assert!( ! vmt.contains("(= (_ curr free)") ) ;
+    assert!(
+      vmt.contains(
+        "(= (_ next l0) (not (and (not (_ curr |%4|)) (not (_ curr en)))))"
+      )
+    ) ;
+    assert!(
+      vmt.contains("(= (_ next |%4|) (and (_ next l0) (_ next en)))")
+    ) ;
+    assert!(
+      vmt.contains(
+        "(define-prop free_is_false aiger\n  (not (_ curr free))\n)"
+      )
+    ) ;
+    assert!( vmt.contains("(verify aiger (free_is_false))") )
+  }
+
+  #[test]
+  fn binary() {
+    let ascii = tr(b"aag 3 1 1 0 1 1\n2\n4 6\n6\n6 4 2\n").unwrap() ;
+    let binary = tr(b"aig 3 1 1 0 1 1\n6\n6\n\x02\x02").unwrap() ;
+    assert_eq!(ascii, binary)
+  }
+
+  #[test]
+  fn errors() {
+    let err = tr(b"aag 1 1 0 0 0 0 0 1\n2\n").unwrap_err() ;
+    assert!( err.contains("justice and fairness") ) ;
+    let err = tr(b"aag 2 1 0 0 1 1\n2\n4\n4 2 6\n").unwrap_err() ;
+    assert!( err.contains("undefined literal 6") ) ;
+    let err = tr(b"aag 1 1 0 1 0\n2\n").unwrap_err() ;
+    assert!( err.contains("expected output literal") ) ;
+    let err = tr(b"aig 1 0 0 0 1 1\n2\n\x02").unwrap_err() ;
+    assert!( err.contains("end of file") )
+  }
+}
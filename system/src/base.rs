@@ -11,10 +11,10 @@ use std::fmt ;
 use std::hash::{ Hash, Hasher } ;
 use std::cmp::{ PartialEq, Eq } ;
 use std::iter::Iterator ;
-use std::collections::HashSet ;
+use std::collections::{ HashSet, HashMap } ;
 
 use term::{
-  Sym, Var, Type, Term, STerm, STermSet
+  Sym, Var, Type, Term, STerm, STermSet, State, Factory, VarMaker, UnTermOps
 } ;
 use term::real_term::Cst ;
 use term::parsing::Spnd ;
@@ -384,6 +384,7 @@ impl fmt::Display for Callable {
 }
 
 /// Status of a property.
+#[derive(Clone)]
 pub enum PropStatus {
   /// Unknown.
   Unknown,
@@ -539,6 +540,44 @@ impl Sys {
   #[inline(always)]
   pub fn calls(& self) -> & CallSet { & self.calls }
 
+  /// Lifts an invariant of the `index`-th sub-system of a system to the
+  /// system itself, replacing the state variables of the sub-system by the
+  /// actual parameters of the call.
+  pub fn lift_inv(
+    & self, factory: & Factory, index: usize, inv: & STerm
+  ) -> Result<STerm, String> {
+    let (ref sub, ref params) = match self.subsys.get(index) {
+      Some(call) => call,
+      None => return Err(
+        format!("[sys] {} has no sub-system number {}", self.sym, index)
+      ),
+    } ;
+    let mut map = HashMap::with_capacity( 2 * params.len() ) ;
+    for (& (ref sym, _), param) in sub.state().args().iter().zip(params) {
+      let curr: Term = factory.svar(sym.get().clone(), State::Curr) ;
+      let next: Term = factory.svar(sym.get().clone(), State::Next) ;
+      let bumped = try!(
+        factory.bump(param).map_err( |e| format!("{}", e) )
+      ) ;
+      map.insert(curr, param.clone()) ;
+      map.insert(next, bumped) ;
+    }
+    match * inv {
+      STerm::One(ref curr, _) => {
+        let curr = try!(
+          factory.subst(curr, & map).map_err( |e| format!("{}", e) )
+        ) ;
+        let next = try!(
+          factory.bump(& curr).map_err( |e| format!("{}", e) )
+        ) ;
+        Ok( STerm::One(curr, next) )
+      },
+      STerm::Two(ref next) => factory.subst(next, & map).map(
+        STerm::Two
+      ).map_err( |e| format!("{}", e) ),
+    }
+  }
+
   /// Default value for a symbol.
   pub fn default_value(& self, sym: & Sym) -> Result<Cst, String> {
     for & (ref sym, ref typ) in self.state().args() {
@@ -0,0 +1,800 @@
+// Copyright 2016 Adrien Champion. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*! BTOR2 front-end.
+
+Builds a system from a BTOR2 model with the term factory of a
+[`Context`](../ctxt/struct.Context.html). The model becomes a single system
+`btor`:
+
+* sorts `bitvec` are bit-vectors, width `1` included, and sorts `array` are
+  arrays,
+* `input`s and `state`s are state variables, named after their symbol when
+  it is legal and unique; inputs are unconstrained,
+* `init` and `next` constrain the first and next value of a state, states
+  without `next` are unconstrained,
+* `constraint`s hold in all the states of the traces: they are conjoined to
+  the init and trans predicates,
+* `bad` lines are properties stating that their argument is `#b0`, named
+  after their symbol or `bad_<nid>` otherwise,
+* the operator nodes used more than once are state variables `%<nid>`,
+  defined in the init and trans predicates. They are hidden in
+  counterexamples.
+
+Comparisons and Boolean operators produce bit-vectors of width `1`. Nodes
+are type checked as they are built, and their sort must be the one they
+declare. The initial value of an array state can be a bit-vector constant,
+the array is then constant.
+
+`output`s are ignored. Operators `rol`, `ror`, `smod`, the overflow
+detection ones and `fair`/`justice` lines are not supported. */
+
+use std::collections::{ HashMap, HashSet } ;
+
+use term::{
+  Sym, Var, Type, Term, STerm, State, Int, Operator, Factory,
+  SymMaker, VarMaker, CstMaker, AppMaker, OpMaker, UnTermOps
+} ;
+use term::real_term::{ Cst as RealCst, Term as RealTerm } ;
+use term::parsing::{ Spn, Spnd } ;
+
+use { Error, Line } ;
+use base ;
+use translation::legal_sym ;
+use ctxt::Context ;
+
+/// Result of a translation step, errors are a position in the model and a
+/// message.
+type BtorRes<T> = Result<T, (usize, String)> ;
+
+/// Name of the system of a model.
+static sys_name: & 'static str = "btor" ;
+
+/// Unary operators.
+static unary: [ & 'static str ; 7 ] = [
+  "not", "inc", "dec", "neg", "redand", "redor", "redxor",
+] ;
+
+/// Binary operators.
+static binary: [ & 'static str ; 30 ] = [
+  "iff", "implies", "eq", "neq", "sgt", "ugt", "sgte", "ugte", "slt", "ult",
+  "slte", "ulte", "and", "nand", "nor", "or", "xnor", "xor", "sll", "sra",
+  "srl", "add", "mul", "sdiv", "udiv", "srem", "urem", "sub", "concat",
+  "read",
+] ;
+
+/// A BTOR2 model, as a system and some properties over it.
+pub struct Model {
+  /// System of the model.
+  pub sys: base::Sys,
+  /// Properties from the `bad` lines, over the current and next state.
+  pub props: Vec<(Sym, STerm)>,
+  /// State variables of the operator nodes, hidden in counterexamples.
+  pub hidden: Vec<Sym>,
+}
+
+/// A BTOR2 command.
+#[derive(Debug, Clone)]
+enum Cmd {
+  /// Bit-vector sort.
+  SortBv(usize),
+  /// Array sort.
+  SortArray(usize, usize),
+  /// Input or state (if `true`), its sort and symbol.
+  Var(bool, usize, Option<String>),
+  /// Constant: kind, sort and value.
+  Cst(String, usize, String),
+  /// Init (if `true`) or next: sort, state and value.
+  Def(bool, usize, usize, i64),
+  /// Bad state property and its symbol.
+  Bad(i64, Option<String>),
+  /// Invariant constraint.
+  Constraint(i64),
+  /// Ignored command.
+  Ignored,
+  /// Operator application: operator, sort, arguments and indices.
+  Op(String, usize, Vec<i64>, Vec<usize>),
+}
+impl Cmd {
+  /// Nodes a command refers to, the states of `init` and `next` excluded.
+  fn refs(& self) -> Vec<usize> {
+    match * self {
+      Cmd::Def(_, _, _, val) => vec![ val.abs() as usize ],
+      Cmd::Bad(arg, _) | Cmd::Constraint(arg) => vec![ arg.abs() as usize ],
+      Cmd::Op(_, _, ref args, _) => args.iter().map(
+        |arg| arg.abs() as usize
+      ).collect(),
+      _ => vec![],
+    }
+  }
+}
+
+/// Parses a number.
+fn num<T: ::std::str::FromStr>(tok: Option<& str>, pos: usize) -> BtorRes<T> {
+  match tok.map( |tok| tok.parse::<T>() ) {
+    Some( Ok(n) ) => Ok(n),
+    Some( Err(_) ) => Err(
+      (pos, format!("expected a number, got `{}`", tok.unwrap()))
+    ),
+    None => Err( (pos, "expected a number".into()) ),
+  }
+}
+
+/// Parses a line, returns its node identifier and command. `None` on empty
+/// lines.
+fn parse_line(line: & str, pos: usize) -> BtorRes< Option<(usize, Cmd)> > {
+  let line = match line.find(';') {
+    Some(n) => & line[..n],
+    None => line,
+  } ;
+  let mut toks = line.split_whitespace() ;
+  let nid = match toks.next() {
+    None => return Ok(None),
+    tok => try!( num::<usize>(tok, pos) ),
+  } ;
+  let kind = match toks.next() {
+    Some(kind) => kind,
+    None => return Err( (pos, "expected a command".into()) ),
+  } ;
+  let sym = |tok: Option<& str>| tok.map( |s| s.to_string() ) ;
+
+  let cmd = match kind {
+    "sort" => match toks.next() {
+      Some("bitvec") => Cmd::SortBv( try!( num(toks.next(), pos) ) ),
+      Some("array") => {
+        let idx = try!( num(toks.next(), pos) ) ;
+        Cmd::SortArray( idx, try!( num(toks.next(), pos) ) )
+      },
+      _ => return Err( (pos, "expected `bitvec` or `array`".into()) ),
+    },
+    "input" | "state" => {
+      let sid = try!( num(toks.next(), pos) ) ;
+      Cmd::Var( kind == "state", sid, sym( toks.next() ) )
+    },
+    "const" | "constd" | "consth" => {
+      let sid = try!( num(toks.next(), pos) ) ;
+      match toks.next() {
+        Some(val) => Cmd::Cst( kind.to_string(), sid, val.to_string() ),
+        None => return Err( (pos, "expected a value".into()) ),
+      }
+    },
+    "zero" | "one" | "ones" => Cmd::Cst(
+      kind.to_string(), try!( num(toks.next(), pos) ), String::new()
+    ),
+    "init" | "next" => {
+      let sid = try!( num(toks.next(), pos) ) ;
+      let state = try!( num(toks.next(), pos) ) ;
+      Cmd::Def( kind == "init", sid, state, try!( num(toks.next(), pos) ) )
+    },
+    "bad" => {
+      let arg = try!( num(toks.next(), pos) ) ;
+      Cmd::Bad( arg, sym( toks.next() ) )
+    },
+    "constraint" => Cmd::Constraint( try!( num(toks.next(), pos) ) ),
+    "output" => Cmd::Ignored,
+    "fair" | "justice" => return Err(
+      (pos, format!("`{}` lines are not supported", kind))
+    ),
+    _ => {
+      let (args, idxs) = if unary.iter().any( |op| * op == kind ) {
+        (1, 0)
+      } else if binary.iter().any( |op| * op == kind ) {
+        (2, 0)
+      } else if kind == "ite" || kind == "write" {
+        (3, 0)
+      } else if kind == "sext" || kind == "uext" {
+        (1, 1)
+      } else if kind == "slice" {
+        (1, 2)
+      } else {
+        return Err( (pos, format!("unsupported command `{}`", kind)) )
+      } ;
+      let sid = try!( num(toks.next(), pos) ) ;
+      let mut arg_vec = Vec::with_capacity(args) ;
+      for _ in 0..args {
+        arg_vec.push( try!( num(toks.next(), pos) ) )
+      }
+      let mut idx_vec = Vec::with_capacity(idxs) ;
+      for _ in 0..idxs {
+        idx_vec.push( try!( num(toks.next(), pos) ) )
+      }
+      Cmd::Op( kind.to_string(), sid, arg_vec, idx_vec )
+    },
+  } ;
+  Ok( Some( (nid, cmd) ) )
+}
+
+/// An integer from its digits, which are assumed legal.
+fn int(digits: & str, radix: u32) -> Int {
+  Int::parse_bytes(digits.as_bytes(), radix).unwrap()
+}
+
+/// Translation context of a model.
+struct Ctx<'a> {
+  /// Context the model is read in.
+  ctxt: & 'a Context,
+  /// Sorts.
+  sorts: HashMap<usize, Type>,
+  /// Translated nodes, over the current state.
+  nodes: HashMap<usize, (Type, Term)>,
+  /// Number of references to each node.
+  refs: HashMap<usize, usize>,
+  /// State identifiers.
+  states: HashSet<usize>,
+  /// Names used so far.
+  names: HashSet<String>,
+  /// State variables and their type.
+  svars: Vec<(Sym, Type)>,
+  /// Internal state variables.
+  hidden: Vec<Sym>,
+  /// Conjuncts of the init predicate, over the current state.
+  init: Vec<Term>,
+  /// Conjuncts of the trans predicate over the next state, their
+  /// current-state version is also a conjunct of the init predicate.
+  both: Vec<Term>,
+  /// Conjuncts of the trans predicate defining the next states.
+  next: Vec<Term>,
+  /// Properties, over the current state.
+  props: Vec<(Sym, Term)>,
+}
+impl<'a> Ctx<'a> {
+  /// Term factory.
+  fn factory(& self) -> & Factory { self.ctxt.factory() }
+  /// Sort of an identifier.
+  fn sort(& self, sid: usize, pos: usize) -> BtorRes<Type> {
+    match self.sorts.get(& sid) {
+      Some(sort) => Ok( sort.clone() ),
+      None => Err( (pos, format!("unknown sort {}", sid)) ),
+    }
+  }
+  /// Width of a bit-vector sort.
+  fn width(& self, sid: usize, pos: usize) -> BtorRes<usize> {
+    match try!( self.sort(sid, pos) ) {
+      Type::BitVec(w) => Ok(w),
+      sort => Err(
+        (pos, format!("expected bit-vector sort, got {}", sort))
+      ),
+    }
+  }
+  /// Bit-vector constant, `val` is taken modulo `2^w`.
+  fn bv(& self, w: usize, val: Int) -> Term {
+    let cst = self.factory().mk_rcst( RealCst::bv(w, val) ) ;
+    self.factory().mk_cst(cst)
+  }
+  /// Bit-vector of width `1`.
+  fn bit(& self, val: & str) -> (Type, Term) {
+    ( Type::BitVec(1), self.bv( 1, int(val, 2) ) )
+  }
+  /// State variable in some state.
+  fn state_var(& self, sym: & Sym, st: State) -> Term {
+    self.factory().svar(sym.clone(), st)
+  }
+  /// Type checks an operator application.
+  fn apply(
+    & self, op: Operator, kids: Vec<(Type, Term)>, pos: usize
+  ) -> BtorRes<(Type, Term)> {
+    let (sig, kids): (Vec<Type>, Vec<Term>) = kids.into_iter().unzip() ;
+    match op.type_check(& sig) {
+      Ok(typ) => Ok( (typ, self.factory().op(op, kids)) ),
+      Err( (_, blah) ) => Err( (pos, blah) ),
+    }
+  }
+  /// Bit-vector of width `1` from a Boolean term.
+  fn of_bool(& self, term: (Type, Term)) -> (Type, Term) {
+    debug_assert!( term.0 == Type::Bool ) ;
+    let (one, zero) = ( self.bit("1").1, self.bit("0").1 ) ;
+    ( Type::BitVec(1), self.factory().ite(term.1, one, zero) )
+  }
+  /// Boolean term from a bit-vector of width `1`.
+  fn into_bool(& self, term: (Type, Term), pos: usize) -> BtorRes<Term> {
+    let one = self.bit("1") ;
+    self.apply( Operator::Eq, vec![ term, one ], pos ).map( |(_, term)| term )
+  }
+  /// Argument of an operator, negated if negative.
+  fn arg(& self, arg: i64, pos: usize) -> BtorRes<(Type, Term)> {
+    let nid = arg.abs() as usize ;
+    let (sort, term) = match self.nodes.get(& nid) {
+      Some(node) => node.clone(),
+      None => return Err( (pos, format!("unknown node {}", nid)) ),
+    } ;
+    if arg >= 0 { return Ok( (sort, term) ) }
+    // Negating constants directly, so that they stay constants.
+    if let RealTerm::C(ref cst) = * term.get() {
+      if let RealCst::BitVec(w, ref val) = * cst.get() {
+        let neg = - ( val.clone() + int("1", 10) ) ;
+        return Ok( ( sort, self.bv(w, neg) ) )
+      }
+    }
+    self.apply( Operator::BvNot, vec![ (sort, term) ], pos )
+  }
+  /// Width of an argument.
+  fn arg_width(& self, arg: i64, pos: usize) -> BtorRes<usize> {
+    match try!( self.arg(arg, pos) ).0 {
+      Type::BitVec(w) => Ok(w),
+      sort => Err(
+        (pos, format!("expected bit-vector argument, got {}", sort))
+      ),
+    }
+  }
+
+  /// Picks a fresh name for a state variable or a property.
+  fn name(& mut self, nid: usize, sym: Option<String>, prefix: & str) -> Sym {
+    let mut name = match sym {
+      Some(ref sym) if legal_sym(sym) => sym.clone(),
+      _ => format!("{}{}", prefix, nid),
+    } ;
+    loop {
+      if ! self.names.contains(& name) {
+        let sym = self.factory().sym( name.clone() ) ;
+        if self.ctxt.sym_unused(& sym).is_none() {
+          self.names.insert(name) ;
+          return sym
+        }
+      }
+      name = format!("{}#{}", name, nid)
+    }
+  }
+
+  /// Translates a constant.
+  fn constant(
+    & self, kind: & str, sid: usize, val: & str, pos: usize
+  ) -> BtorRes<Term> {
+    let w = try!( self.width(sid, pos) ) ;
+    let bad_val = || Err( (pos, format!("illegal value `{}`", val)) ) ;
+    let val = match kind {
+      "zero" => int("0", 10),
+      "one" => int("1", 10),
+      "ones" => int("-1", 10),
+      "const" => {
+        if val.len() != w || ! val.chars().all( |c| c == '0' || c == '1' ) {
+          return bad_val()
+        }
+        int(val, 2)
+      },
+      "constd" => {
+        let digits = if val.starts_with('-') { & val[1..] } else { val } ;
+        if digits.is_empty() || ! digits.chars().all( |c| c.is_digit(10) ) {
+          return bad_val()
+        }
+        int(val, 10)
+      },
+      "consth" => {
+        if val.is_empty() || ! val.chars().all( |c| c.is_digit(16) ) {
+          return bad_val()
+        }
+        // Dropped bits should be zeros.
+        let n = int(val, 16) ;
+        if RealCst::bv( w, n.clone() ) != RealCst::BitVec( w, n.clone() ) {
+          return bad_val()
+        }
+        n
+      },
+      _ => unreachable!(),
+    } ;
+    Ok( self.bv(w, val) )
+  }
+
+  /// Translates an operator application.
+  fn operator(
+    & self, op: & str, args: & [i64], idxs: & [usize], pos: usize
+  ) -> BtorRes<(Type, Term)> {
+    use term::Operator::* ;
+    let mut kids = Vec::with_capacity( args.len() ) ;
+    for arg in args.iter() {
+      kids.push( try!( self.arg(* arg, pos) ) )
+    }
+    let bv_op = |op: Operator| self.apply( op, kids.clone(), pos ) ;
+    let cmp_op = |op: Operator| self.apply( op, kids.clone(), pos ).map(
+      |term| self.of_bool(term)
+    ) ;
+    let not = |term: BtorRes<(Type, Term)>| self.apply(
+      BvNot, vec![ try!(term) ], pos
+    ) ;
+
+    match op {
+      "not" => bv_op(BvNot),
+      "neg" => bv_op(BvNeg),
+      "inc" | "dec" => {
+        let w = try!( self.arg_width(args[0], pos) ) ;
+        let one = ( Type::BitVec(w), self.bv( w, int("1", 10) ) ) ;
+        self.apply(
+          if op == "inc" { BvAdd } else { BvSub },
+          vec![ kids[0].clone(), one ], pos
+        )
+      },
+      "redand" | "redor" => {
+        let w = try!( self.arg_width(args[0], pos) ) ;
+        let (cst, cmp) = if op == "redand" {
+          ( int("-1", 10), Eq )
+        } else {
+          ( int("0", 10), Distinct )
+        } ;
+        let cst = ( Type::BitVec(w), self.bv(w, cst) ) ;
+        self.apply( cmp, vec![ kids[0].clone(), cst ], pos ).map(
+          |term| self.of_bool(term)
+        )
+      },
+      "redxor" => {
+        let w = try!( self.arg_width(args[0], pos) ) ;
+        let mut acc = try!( self.apply( Extract(0, 0), kids.clone(), pos ) ) ;
+        for i in 1..w {
+          let bit = try!( self.apply( Extract(i, i), kids.clone(), pos ) ) ;
+          acc = try!( self.apply( BvXor, vec![acc, bit], pos ) )
+        }
+        Ok(acc)
+      },
+      "and" => bv_op(BvAnd),
+      "or" => bv_op(BvOr),
+      "xor" => bv_op(BvXor),
+      "nand" => not( bv_op(BvAnd) ),
+      "nor" => not( bv_op(BvOr) ),
+      "xnor" | "iff" => not( bv_op(BvXor) ),
+      "implies" => {
+        let lhs = try!( not( Ok( kids[0].clone() ) ) ) ;
+        self.apply( BvOr, vec![ lhs, kids[1].clone() ], pos )
+      },
+      "eq" => cmp_op(Eq),
+      "neq" => cmp_op(Distinct),
+      "sgt" => cmp_op(BvSGt),
+      "ugt" => cmp_op(BvUGt),
+      "sgte" => cmp_op(BvSGe),
+      "ugte" => cmp_op(BvUGe),
+      "slt" => cmp_op(BvSLt),
+      "ult" => cmp_op(BvULt),
+      "slte" => cmp_op(BvSLe),
+      "ulte" => cmp_op(BvULe),
+      "sll" => bv_op(BvShl),
+      "sra" => bv_op(BvAShr),
+      "srl" => bv_op(BvLShr),
+      "add" => bv_op(BvAdd),
+      "sub" => bv_op(BvSub),
+      "mul" => bv_op(BvMul),
+      "udiv" => bv_op(BvUDiv),
+      "urem" => bv_op(BvURem),
+      "sdiv" => bv_op(BvSDiv),
+      "srem" => bv_op(BvSRem),
+      "concat" => bv_op(Concat),
+      "read" => bv_op(Select),
+      "write" => bv_op(Store),
+      "ite" => {
+        let cnd = try!( self.into_bool( kids[0].clone(), pos ) ) ;
+        self.apply(
+          Ite, vec![
+            (Type::Bool, cnd), kids[1].clone(), kids[2].clone()
+          ], pos
+        )
+      },
+      "sext" | "uext" => if idxs[0] == 0 {
+        Ok( kids[0].clone() )
+      } else if op == "sext" {
+        bv_op( SignExtend(idxs[0]) )
+      } else {
+        bv_op( ZeroExtend(idxs[0]) )
+      },
+      "slice" => bv_op( Extract(idxs[0], idxs[1]) ),
+      _ => unreachable!(),
+    }
+  }
+
+  /// Translates a command.
+  fn cmd(& mut self, nid: usize, cmd: Cmd, pos: usize) -> BtorRes<()> {
+    match cmd {
+      Cmd::SortBv(w) => {
+        if w == 0 {
+          return Err( (pos, "bit-vectors have a positive width".into()) )
+        }
+        self.sorts.insert( nid, Type::BitVec(w) ) ; ()
+      },
+      Cmd::SortArray(idx, elm) => {
+        let idx = try!( self.sort(idx, pos) ) ;
+        let elm = try!( self.sort(elm, pos) ) ;
+        self.sorts.insert( nid, Type::array(idx, elm) ) ; ()
+      },
+      Cmd::Var(is_state, sid, sym) => {
+        let sort = try!( self.sort(sid, pos) ) ;
+        let sym = self.name(
+          nid, sym, if is_state { "state" } else { "input" }
+        ) ;
+        if is_state { self.states.insert(nid) ; () }
+        self.svars.push( ( sym.clone(), sort.clone() ) ) ;
+        let svar = self.state_var(& sym, State::Curr) ;
+        self.nodes.insert( nid, (sort, svar) ) ; ()
+      },
+      Cmd::Cst(kind, sid, val) => {
+        let term = try!( self.constant(& kind, sid, & val, pos) ) ;
+        let sort = try!( self.sort(sid, pos) ) ;
+        self.nodes.insert( nid, (sort, term) ) ; ()
+      },
+      Cmd::Def(is_init, sid, state, val) => {
+        if ! self.states.contains(& state) {
+          return Err( (pos, format!("node {} is not a state", state)) )
+        }
+        let (state_sort, svar) = try!( self.arg(state as i64, pos) ) ;
+        let (val_sort, val) = try!( self.arg(val, pos) ) ;
+        try!( self.sort(sid, pos) ) ;
+        let val = match state_sort {
+          // Constant arrays.
+          Type::Array(ref idx, ref elm) if is_init && * * elm == val_sort => {
+            let cst = match * val.get() {
+              RealTerm::C(ref cst) => cst.get().clone(),
+              _ => return Err(
+                (pos, "the initial value of an array should be a \
+                constant".into())
+              ),
+            } ;
+            let cst = self.factory().mk_rcst(
+              RealCst::Array( (* * idx).clone(), Box::new(cst), vec![] )
+            ) ;
+            ( state_sort.clone(), self.factory().mk_cst(cst) )
+          },
+          _ => (val_sort, val),
+        } ;
+        if is_init {
+          let init = try!(
+            self.apply( Operator::Eq, vec![ (state_sort, svar), val ], pos )
+          ) ;
+          self.init.push(init.1)
+        } else {
+          // Unwrap cannot fail, `svar` is in the current state.
+          let nxt = ( state_sort, self.factory().bump(svar).unwrap() ) ;
+          let next = try!(
+            self.apply( Operator::Eq, vec![ nxt, val ], pos )
+          ) ;
+          self.next.push(next.1)
+        }
+      },
+      Cmd::Bad(arg, sym) => {
+        let term = try!( self.arg(arg, pos) ) ;
+        let zero = self.bit("0") ;
+        let (_, body) = try!(
+          self.apply( Operator::Eq, vec![ term, zero ], pos )
+        ) ;
+        let sym = self.name(nid, sym, "bad_") ;
+        self.props.push( (sym, body) )
+      },
+      Cmd::Constraint(arg) => {
+        let term = try!( self.arg(arg, pos) ) ;
+        let term = try!( self.into_bool(term, pos) ) ;
+        self.both.push(term)
+      },
+      Cmd::Ignored => (),
+      Cmd::Op(op, sid, args, idxs) => {
+        let (typ, term) = try!( self.operator(& op, & args, & idxs, pos) ) ;
+        let sort = try!( self.sort(sid, pos) ) ;
+        if typ != sort {
+          return Err(
+            (pos, format!("node has sort {} but is declared {}", typ, sort))
+          )
+        }
+        let shared = self.refs.get(& nid).map( |n| * n > 1 ).unwrap_or(false) ;
+        let term = if shared {
+          let sym = self.factory().sym( format!("%{}", nid) ) ;
+          self.svars.push( ( sym.clone(), sort.clone() ) ) ;
+          self.hidden.push( sym.clone() ) ;
+          let svar = self.state_var(& sym, State::Curr) ;
+          let def = self.factory().eq( vec![ svar.clone(), term ] ) ;
+          self.both.push(def) ;
+          svar
+        } else { term } ;
+        self.nodes.insert( nid, (sort, term) ) ; ()
+      },
+    }
+    Ok(())
+  }
+}
+
+/// Translates a model, errors are positions in the model.
+fn translate_internal(ctxt: & Context, src: & str) -> BtorRes<Model> {
+  let mut cmds = vec![] ;
+  let mut pos = 0 ;
+  for line in src.split('\n') {
+    if let Some( (nid, cmd) ) = try!( parse_line(line, pos) ) {
+      cmds.push( (nid, cmd, pos) )
+    }
+    pos += line.len() + 1
+  }
+
+  let factory = ctxt.factory() ;
+  let sys_sym = factory.sym(sys_name) ;
+  if let Some(desc) = ctxt.sym_unused(& sys_sym) {
+    return Err(
+      (0, format!("cannot define system `{}`, it is a {}", sys_name, desc))
+    )
+  }
+
+  let mut ctx = Ctx {
+    ctxt: ctxt, sorts: HashMap::new(), nodes: HashMap::new(),
+    refs: HashMap::new(), states: HashSet::new(), names: HashSet::new(),
+    svars: vec![], hidden: vec![], init: vec![], both: vec![], next: vec![],
+    props: vec![],
+  } ;
+  ctx.names.insert( sys_name.to_string() ) ;
+  for & (_, ref cmd, _) in cmds.iter() {
+    for nid in cmd.refs() {
+      * ctx.refs.entry(nid).or_insert(0) += 1
+    }
+  }
+  for (nid, cmd, pos) in cmds.into_iter() {
+    if ctx.nodes.contains_key(& nid) || ctx.sorts.contains_key(& nid) {
+      return Err( (pos, format!("node {} is defined twice", nid)) )
+    }
+    try!( ctx.cmd(nid, cmd, pos) )
+  }
+
+  if ctx.props.is_empty() {
+    return Err( (src.len(), "no `bad` property to check".into()) )
+  }
+
+  // Unwraps cannot fail below, the terms are over the current state.
+  let mut init = ctx.init ;
+  let mut trans = Vec::with_capacity( ctx.both.len() + ctx.next.len() ) ;
+  for term in ctx.both.into_iter() {
+    trans.push( factory.bump( term.clone() ).unwrap() ) ;
+    init.push(term)
+  }
+  trans.extend( ctx.next ) ;
+  let and = |terms: Vec<Term>| if terms.is_empty() {
+    factory.cst(true)
+  } else { factory.and(terms) } ;
+  let (init, trans) = ( and(init), and(trans) ) ;
+
+  // Init and trans predicates, as in `check_sys`.
+  let span = Spn::dummy() ;
+  let mut state = Vec::with_capacity( ctx.svars.len() ) ;
+  let mut init_state = Vec::with_capacity( ctx.svars.len() ) ;
+  let mut trans_state = Vec::with_capacity( 2 * ctx.svars.len() ) ;
+  let mut next_state = Vec::with_capacity( ctx.svars.len() ) ;
+  for (sym, typ) in ctx.svars.into_iter() {
+    let curr: Var = factory.svar(sym.clone(), State::Curr) ;
+    init_state.push( (curr.clone(), typ.clone()) ) ;
+    trans_state.push( (curr.clone(), typ.clone()) ) ;
+    let next: Var = factory.svar(sym.clone(), State::Next) ;
+    next_state.push( (next, typ.clone()) ) ;
+    try!(
+      factory.set_var_type(
+        Some( sys_sym.clone() ), curr, typ.clone()
+      ).map_err( |e| (0, format!("{}", e)) )
+    ) ;
+    state.push( ( Spnd::mk(sym, span.clone()), Spnd::mk(typ, span.clone()) ) )
+  }
+  trans_state.extend(next_state) ;
+
+  let init_sym = factory.sym( format!("init[{}]", sys_name) ) ;
+  let trans_sym = factory.sym( format!("trans[{}]", sys_name) ) ;
+  let init_app = factory.app(
+    init_sym.clone(), init_state.iter().map(
+      |& (ref var, _)| factory.mk_var( var.clone() )
+    ).collect()
+  ) ;
+  let trans_app = factory.app(
+    trans_sym.clone(), trans_state.iter().map(
+      |& (ref var, _)| factory.mk_var( var.clone() )
+    ).collect()
+  ) ;
+
+  let props = ctx.props.into_iter().map(
+    |(sym, body)| {
+      let nxt = factory.bump( body.clone() ).unwrap() ;
+      ( sym, STerm::One(body, nxt) )
+    }
+  ).collect() ;
+
+  Ok(
+    Model {
+      sys: base::Sys::mk(
+        Spnd::mk(sys_sym, span), base::Args::mk(state), vec![],
+        (init_sym, init_state, init, init_app),
+        (trans_sym, trans_state, trans, trans_app),
+        vec![], base::CallSet::empty(),
+      ),
+      props: props,
+      hidden: ctx.hidden,
+    }
+  )
+}
+
+/// Builds the system of a BTOR2 model in a context. The system and its
+/// properties are not added to the context.
+pub fn translate(ctxt: & Context, src: & str) -> Result<Model, Error> {
+  translate_internal(ctxt, src).map_err(
+    |(pos, blah)| Error::parse_mk( Line::of_pos(src, pos), blah, vec![] )
+  )
+}
+
+
+#[cfg(test)]
+mod test {
+  use term::{ Factory, Type } ;
+  use ctxt::Context ;
+  use super::{ translate, Model } ;
+
+  /// Translates a model, prints the error if any.
+  fn tr(src: & str) -> Result<Model, String> {
+    let context = Context::mk(Factory::mk(), 100) ;
+    translate(& context, src).map_err(
+      |e| { e.print() ; format!("{}", e) }
+    )
+  }
+
+  #[test]
+  fn counter() {
+    let model = tr("\
+      ; Counter incremented when `en` is set.\n\
+      1 sort bitvec 1\n\
+      2 sort bitvec 3\n\
+      3 input 1 en\n\
+      4 state 2 cnt\n\
+      5 zero 2\n\
+      6 init 2 4 5\n\
+      7 one 2\n\
+      8 add 2 4 7\n\
+      9 ite 2 3 8 4\n\
+      10 next 2 4 9\n\
+      11 constd 2 -1\n\
+      12 eq 1 4 11\n\
+      13 bad 12 cnt_is_max\n\
+      14 ult 1 4 11\n\
+      15 and 1 14 14\n\
+      16 bad -15\n\
+    ").unwrap() ;
+    let state: Vec<_> = model.sys.state().args().iter().map(
+      |& (ref sym, ref typ)| ( sym.get().sym().to_string(), typ.get().clone() )
+    ).collect() ;
+    // Node 14 is used twice.
+    assert_eq!(
+      state, vec![
+        ( "en".to_string(), Type::BitVec(1) ),
+        ( "cnt".to_string(), Type::BitVec(3) ),
+        ( "%14".to_string(), Type::BitVec(1) ),
+      ]
+    ) ;
+    assert_eq!( model.hidden.len(), 1 ) ;
+    assert_eq!( model.hidden[0].sym(), "%14" ) ;
+    let init = format!("{}", model.sys.init().2) ;
+    assert!( init.contains("(= (_ state |cnt|) #b000)") ) ;
+    let trans = format!("{}", model.sys.trans().2) ;
+    assert!(
+      trans.contains(
+        "(= (_ next |cnt|) \
+        (ite (= (_ state |en|) #b1) \
+        (bvadd (_ state |cnt|) #b001) (_ state |cnt|)))"
+      )
+    ) ;
+    let props: Vec<_> = model.props.iter().map(
+      |& (ref sym, ref body)| ( sym.sym().to_string(), format!("{}", body) )
+    ).collect() ;
+    assert_eq!( props.len(), 2 ) ;
+    assert_eq!( props[0].0, "cnt_is_max" ) ;
+    assert!(
+      props[0].1.contains("(= (ite (= (_ state |cnt|) #b111) #b1 #b0) #b0)")
+    ) ;
+    assert_eq!( props[1].0, "bad_16" ) ;
+    assert!(
+      props[1].1.contains(
+        "(= (bvnot (bvand (_ state |%14|) (_ state |%14|))) #b0)"
+      )
+    )
+  }
+
+  #[test]
+  fn errors() {
+    let err = tr(
+      "1 sort bitvec 1\n2 input 1\n3 rol 1 2 2\n4 bad 3\n"
+    ).err().unwrap() ;
+    assert!( err.contains("unsupported command `rol`") ) ;
+    let err = tr("1 sort bitvec 1\n2 input 1\n3 bad 4\n").err().unwrap() ;
+    assert!( err.contains("unknown node 4") ) ;
+    let err = tr("1 sort bitvec 1\n2 input 1\n").err().unwrap() ;
+    assert!( err.contains("no `bad` property") ) ;
+    let err = tr(
+      "1 sort bitvec 1\n2 sort bitvec 2\n3 input 1\n4 add 2 3 3\n5 bad 4\n"
+    ).err().unwrap() ;
+    assert!( err.contains("node has sort") )
+  }
+}
@@ -0,0 +1,327 @@
+// Copyright 2016 Adrien Champion. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*! Constrained Horn clause export.
+
+Writes a system and some properties as an SMT-LIB 2 `HORN` problem, for
+cross-checking with CHC solvers. The problem
+
+* declares the enumerated sorts and declares/defines the functions used,
+* defines the init and trans predicates of each system as the unroller
+  does, sub-systems first,
+* declares a predicate `reach[<sys>]` per system over its state,
+* for each system, has a clause stating that its initial states are
+  reachable, and one stating that the successors of reachable states are
+  reachable,
+* has a query per property stating that no reachable state (transition for
+  two-state properties) of its system falsifies it.
+
+The assumptions of a `verify-assuming` check hold in all the states of the
+system checked: they are conjoined to the bodies of its init and step
+clauses, over the state they reach, and to the bodies of the queries over
+it.
+
+The problem is satisfiable iff all the properties hold. Declared functions
+are left uninterpreted, which not all CHC solvers support. */
+
+use std::io ;
+use std::io::Write ;
+use std::collections::{ BTreeSet, HashSet } ;
+
+use term::{ Sym, Var, Type, Term, STerm, Offset2, PrintSmt2 } ;
+use term::smt::Sym2Smt ;
+
+use real_sys::Callable::* ;
+use { Sys, Prop, Callable } ;
+
+/// SMT-LIB representation of something implementing `Sym2Smt`.
+fn smt_str<T: Sym2Smt<Offset2>>(t: & T) -> io::Result<String> {
+  let mut buf = vec![] ;
+  try!(
+    t.sym_to_smt2(& mut buf, & Offset2::init()).map_err(
+      |e| io::Error::new( io::ErrorKind::Other, format!("{}", e) )
+    )
+  ) ;
+  String::from_utf8(buf).map_err(
+    |e| io::Error::new( io::ErrorKind::Other, format!("{}", e) )
+  )
+}
+
+/// Name of the predicate of the reachable states of a system.
+fn reach(sys: & Sys) -> String {
+  format!("|reach[{}]|", sys.sym().get().sym())
+}
+
+/// Writes a term.
+fn write_term<W: io::Write>(w: & mut W, term: & Term) -> io::Result<()> {
+  term.to_smt2(w, & Offset2::init())
+}
+
+/// Writes some assumptions, each preceded by a space, over the current state
+/// or over the next one if `next`.
+fn write_assumptions<W: io::Write>(
+  w: & mut W, assumptions: & [Term], next: bool
+) -> io::Result<()> {
+  let offset = if next { Offset2::init().nxt() } else { Offset2::init() } ;
+  for assumption in assumptions.iter() {
+    try!( write!(w, " ") ) ;
+    try!( assumption.to_smt2(w, & offset) )
+  }
+  Ok(())
+}
+
+/// Writes a sorted list of variables.
+fn write_vars<W: io::Write>(
+  w: & mut W, vars: & [ (Var, Type) ]
+) -> io::Result<()> {
+  try!( write!(w, "(") ) ;
+  for & (ref var, ref typ) in vars.iter() {
+    try!( write!(w, " ({} {})", try!( smt_str(var) ), typ) )
+  }
+  write!(w, " )")
+}
+
+/// Writes an application of a predicate to some variables.
+fn write_app<W: io::Write>(
+  w: & mut W, pred: & str, vars: & [ (Var, Type) ]
+) -> io::Result<()> {
+  if vars.is_empty() { return write!(w, "{}", pred) }
+  try!( write!(w, "({}", pred) ) ;
+  for & (ref var, _) in vars.iter() {
+    try!( write!(w, " {}", try!( smt_str(var) )) )
+  }
+  write!(w, ")")
+}
+
+/// Writes a clause universally quantified over some variables.
+fn write_clause<W: io::Write>(
+  w: & mut W, vars: & [ (Var, Type) ], clause: & str
+) -> io::Result<()> {
+  if vars.is_empty() {
+    write!(w, "(assert\n  {}\n)\n", clause)
+  } else {
+    try!( write!(w, "(assert\n  (forall ") ) ;
+    try!( write_vars(w, vars) ) ;
+    write!(w, "\n    {}\n  )\n)\n", clause)
+  }
+}
+
+/// Adds the enumerated sorts appearing in a type to a set.
+fn enums_of(typ: & Type, set: & mut BTreeSet<Type>) {
+  match * typ {
+    Type::Enum(_) => {
+      set.insert( typ.clone() ) ; ()
+    },
+    Type::Array(ref idx, ref elm) => {
+      enums_of(idx, set) ;
+      enums_of(elm, set)
+    },
+    _ => (),
+  }
+}
+
+/// Adds the systems a system depends on to a list, sub-systems first.
+fn sys_deps(sys: & Sys, known: & mut HashSet<Sym>, deps: & mut Vec<Sys>) {
+  if known.contains( sys.sym().get() ) { return () }
+  for & (ref sub, _) in sys.subsys() {
+    sys_deps(sub, known, deps)
+  }
+  known.insert( sys.sym().get().clone() ) ;
+  deps.push( sys.clone() )
+}
+
+/// Adds a function and the ones it calls to a list, callees first.
+fn fun_deps(
+  fun: & Callable, known: & mut HashSet<Sym>, deps: & mut Vec<Callable>
+) {
+  if known.contains( fun.sym() ) { return () }
+  for callee in fun.calls() {
+    fun_deps(callee, known, deps)
+  }
+  known.insert( fun.sym().clone() ) ;
+  deps.push( fun.clone() )
+}
+
+/// Writes the defining function of the init or trans predicate of a system.
+fn write_pred_def<W: io::Write>(
+  w: & mut W, pred: & (Sym, Vec<(Var, Type)>, Term, Term)
+) -> io::Result<()> {
+  try!( write!(w, "(define-fun {} ", try!( smt_str(& pred.0) )) ) ;
+  try!( write_vars(w, & pred.1) ) ;
+  try!( write!(w, " Bool\n  ") ) ;
+  try!( write_term(w, & pred.2) ) ;
+  write!(w, "\n)\n")
+}
+
+/// Writes a system and some properties over it as a CHC problem, under some
+/// assumptions over the state of the system.
+///
+/// See the [module-level documentation](index.html) for the encoding.
+pub fn write<W: io::Write>(
+  w: & mut W, sys: & Sys, props: & [ Prop ], assumptions: & [ Term ]
+) -> io::Result<()> {
+  let checked = sys ;
+  let mut systems = vec![] ;
+  sys_deps(sys, & mut HashSet::new(), & mut systems) ;
+  for prop in props.iter() {
+    let mut known = systems.iter().map(
+      |sys| sys.sym().get().clone()
+    ).collect() ;
+    sys_deps(prop.sys(), & mut known, & mut systems)
+  }
+
+  let mut funs = vec![] ;
+  let mut known = HashSet::new() ;
+  for sys in systems.iter() {
+    for fun in sys.calls().get() {
+      fun_deps(fun, & mut known, & mut funs)
+    }
+  }
+  for prop in props.iter() {
+    for fun in prop.calls().get() {
+      fun_deps(fun, & mut known, & mut funs)
+    }
+  }
+
+  try!( write!(w, "(set-logic HORN)\n\n") ) ;
+
+  // Enumerated sorts.
+  let mut enums = BTreeSet::new() ;
+  for sys in systems.iter() {
+    for & (_, ref typ) in sys.init().1.iter() {
+      enums_of(typ, & mut enums)
+    }
+  }
+  for fun in funs.iter() {
+    match * * fun {
+      Dec(ref fun) => {
+        for typ in fun.sig() { enums_of(typ, & mut enums) }
+        enums_of(fun.typ(), & mut enums)
+      },
+      Def(ref fun) => {
+        for & (_, ref typ) in fun.args() { enums_of(typ, & mut enums) }
+        enums_of(fun.typ(), & mut enums)
+      },
+    }
+  }
+  for typ in enums.iter() {
+    if let Type::Enum(ref e) = * typ {
+      try!( write!(w, "(declare-datatypes ( ({} 0) ) ( (", e.name()) ) ;
+      for cons in e.cons() {
+        try!( write!(w, " ({})", cons) )
+      }
+      try!( write!(w, " ) ))\n") )
+    }
+  }
+  if ! enums.is_empty() { try!( write!(w, "\n") ) }
+
+  // Functions.
+  for fun in funs.iter() {
+    match * * fun {
+      Dec(ref fun) => {
+        try!( write!(w, "(declare-fun {} (", try!( smt_str(fun.sym()) )) ) ;
+        for typ in fun.sig() {
+          try!( write!(w, " {}", typ.get()) )
+        }
+        try!( write!(w, " ) {})\n", fun.typ().get()) )
+      },
+      Def(ref fun) => {
+        try!( write!(w, "(define-fun {} (", try!( smt_str(fun.sym()) )) ) ;
+        for & (ref sym, ref typ) in fun.args() {
+          try!(
+            write!(w, " ({} {})", try!( smt_str(sym.get()) ), typ.get())
+          )
+        }
+        try!( write!(w, " ) {}\n  ", fun.typ().get()) ) ;
+        try!( write_term(w, fun.body()) ) ;
+        try!( write!(w, "\n)\n") )
+      },
+    }
+  }
+  if ! funs.is_empty() { try!( write!(w, "\n") ) }
+
+  // Systems.
+  for sys in systems.iter() {
+    let state = & sys.init().1 ;
+    let trans_state = & sys.trans().1 ;
+    let next_state = & trans_state[ state.len() .. ] ;
+    let assumptions = if sys.sym() == checked.sym() {
+      assumptions
+    } else { & [] } ;
+
+    try!( write!(w, "; System {}.\n", sys.sym().get().sym()) ) ;
+    try!( write_pred_def(w, sys.init()) ) ;
+    try!( write_pred_def(w, sys.trans()) ) ;
+    try!( write!(w, "(declare-fun {} (", reach(sys)) ) ;
+    for & (_, ref typ) in state.iter() {
+      try!( write!(w, " {}", typ) )
+    }
+    try!( write!(w, " ) Bool)\n") ) ;
+
+    let mut init = vec![] ;
+    try!( write!(init, "(=> ") ) ;
+    if assumptions.is_empty() {
+      try!( write_term(& mut init, sys.init_term()) )
+    } else {
+      try!( write!(init, "(and ") ) ;
+      try!( write_term(& mut init, sys.init_term()) ) ;
+      try!( write_assumptions(& mut init, assumptions, false) ) ;
+      try!( write!(init, ")") )
+    }
+    try!( write!(init, " ") ) ;
+    try!( write_app(& mut init, & reach(sys), state) ) ;
+    try!( write!(init, ")") ) ;
+    try!( write_clause(w, state, & String::from_utf8_lossy(& init)) ) ;
+
+    let mut step = vec![] ;
+    try!( write!(step, "(=> (and ") ) ;
+    try!( write_app(& mut step, & reach(sys), state) ) ;
+    try!( write!(step, " ") ) ;
+    try!( write_term(& mut step, sys.trans_term()) ) ;
+    try!( write_assumptions(& mut step, assumptions, true) ) ;
+    try!( write!(step, ") ") ) ;
+    try!( write_app(& mut step, & reach(sys), next_state) ) ;
+    try!( write!(step, ")") ) ;
+    try!( write_clause(w, trans_state, & String::from_utf8_lossy(& step)) ) ;
+    try!( write!(w, "\n") )
+  }
+
+  // Queries.
+  for prop in props.iter() {
+    let sys = prop.sys() ;
+    let assumptions = if sys.sym() == checked.sym() {
+      assumptions
+    } else { & [] } ;
+    try!( write!(w, "; Property {}.\n", prop.sym().get().sym()) ) ;
+    let mut query = vec![] ;
+    try!( write!(query, "(=> (and ") ) ;
+    try!( write_app(& mut query, & reach(sys), & sys.init().1) ) ;
+    let vars = match * prop.body() {
+      STerm::One(ref curr, _) => {
+        try!( write_assumptions(& mut query, assumptions, false) ) ;
+        try!( write!(query, " (not ") ) ;
+        try!( write_term(& mut query, curr) ) ;
+        & sys.init().1
+      },
+      STerm::Two(ref next) => {
+        try!( write!(query, " ") ) ;
+        try!( write_term(& mut query, sys.trans_term()) ) ;
+        try!( write_assumptions(& mut query, assumptions, true) ) ;
+        try!( write!(query, " (not ") ) ;
+        try!( write_term(& mut query, next) ) ;
+        & sys.trans().1
+      },
+    } ;
+    try!( write!(query, ")) false)") ) ;
+    try!( write_clause(w, vars, & String::from_utf8_lossy(& query)) ) ;
+    try!( write!(w, "\n") )
+  }
+
+  write!(w, "(check-sat)\n")
+}
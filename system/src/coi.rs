@@ -0,0 +1,538 @@
+// Copyright 2016 Adrien Champion. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*! Cone of influence slicing.
+
+The init and trans predicates of a system, sub-system calls included, are
+split into their top-level conjuncts. A conjunct `(= (_ next v) <term>)` of
+the trans predicate, or `(= (_ curr v) <term>)` of the init predicate, with
+the equality in any order, is a *definition* of `v` when
+
+* `<term>` does not mention `v` in the same state,
+* no other conjunct of its predicate mentions `v`,
+* no conjunct of the other predicate mentions `v`, except a definition of
+  `v`,
+
+where the current state variables of the definitions of the trans
+predicate do not count as mentioned: the definitions only read them.
+
+A definition then leaves `v` free of any other constraint, it can be dropped
+when `v` is not in the cone. The cone of influence of some properties is the
+smallest set of state variables that
+
+* contains the state variables of the properties (and of the assumptions),
+* contains the state variables of the definitions of its state variables,
+* contains all the state variables of any other conjunct mentioning one of
+  its state variables.
+
+The sliced system only has the state variables of the cone, and only the
+conjuncts over them. Conjuncts mentioning no state variable at all are
+kept. Every trace of the system is a trace of the sliced system once
+restricted to the cone, so properties proved on the sliced system hold on
+the original one. Counterexamples are lifted back with
+[`Cex::lift`](../struct.Cex.html#method.lift), which evaluates the
+definitions dropped. The conjuncts dropped that are not definitions only
+mention state variables outside the cone, the lifted counterexample must be
+replayed on the original system in case they do not hold. */
+
+use std::collections::{ HashMap, HashSet } ;
+use std::sync::Arc ;
+
+use term::{
+  Sym, Var, Term, STermSet, State, Factory, Operator, AppMaker, CstMaker
+} ;
+use term::zip::{ Step, fold } ;
+
+use base ;
+use { Sys, Prop } ;
+
+/// Symbols of the state variables appearing in a term.
+fn svars_of(term: & Term) -> HashSet<Sym> {
+  svars_at(term, None)
+}
+
+/// Symbols of the state variables appearing in a term in some state, in any
+/// state if none.
+fn svars_at(term: & Term, st: Option<State>) -> HashSet<Sym> {
+  use term::real_term::Var::SVar ;
+  fold(
+    |step: Step<HashSet<Sym>>| match step {
+      Step::V(var) => {
+        let mut set = HashSet::new() ;
+        if let SVar(ref sym, var_st) = * var.get() {
+          if st.map_or(true, |st| st == var_st) {
+            set.insert( sym.clone() ) ; ()
+          }
+        }
+        set
+      },
+      Step::App(_, kids) | Step::Op(_, kids) => kids.into_iter().fold(
+        HashSet::new(), |mut set, kid| { set.extend(kid) ; set }
+      ),
+      Step::Let(bindings, mut body) => {
+        for (_, set) in bindings.into_iter() {
+          body.extend(set)
+        }
+        body
+      },
+      Step::Forall(_, body) | Step::Exists(_, body) => body,
+      Step::C(_) => HashSet::new(),
+    },
+    term.clone()
+  )
+}
+
+/// True if a term mentions a variable.
+fn mentions(term: & Term, var: & Var) -> bool {
+  fold(
+    |step| match step {
+      Step::V(v) => & v == var,
+      Step::App(_, kids) | Step::Op(_, kids) => kids.into_iter().any(
+        |kid| kid
+      ),
+      Step::Let(bindings, body) => body || bindings.into_iter().any(
+        |(_, kid)| kid
+      ),
+      Step::Forall(_, body) | Step::Exists(_, body) => body,
+      Step::C(_) => false,
+    },
+    term.clone()
+  )
+}
+
+/// The state variable a conjunct has the shape of a definition of in some
+/// state, if any, and the term defining it.
+fn definition(conj: & Term, st: State) -> Option<(Sym, Term)> {
+  use term::real_term::Term::{ Op, V } ;
+  use term::real_term::Var::SVar ;
+  if let Op(Operator::Eq, ref kids) = * conj.get() {
+    if kids.len() == 2 {
+      for & (lhs, rhs) in [ (0, 1), (1, 0) ].iter() {
+        if let V(ref var) = * kids[lhs].get() {
+          if let SVar(ref sym, var_st) = * var.get() {
+            if var_st == st && ! mentions(& kids[rhs], var) {
+              return Some( ( sym.clone(), kids[rhs].clone() ) )
+            }
+          }
+        }
+      }
+    }
+  }
+  None
+}
+
+/// A conjunct of the init or trans predicate, with the state variable it is
+/// a definition of, if any, and the term defining it.
+type Conj = (Term, Option<(Sym, Term)>) ;
+
+/// The conjuncts of the init and trans predicates of a system, see the
+/// [module documentation](index.html).
+fn dependencies(sys: & Sys) -> (Vec<Conj>, Vec<Conj>) {
+  let (mut init, mut trans) = (vec![], vec![]) ;
+  conjuncts(& sys.init().2, & mut init) ;
+  conjuncts(& sys.trans().2, & mut trans) ;
+  let mut preds: Vec< Vec<Conj> > = vec![
+    init.into_iter().map(
+      |conj| { let def = definition(& conj, State::Curr) ; (conj, def) }
+    ).collect(),
+    trans.into_iter().map(
+      |conj| { let def = definition(& conj, State::Next) ; (conj, def) }
+    ).collect(),
+  ] ;
+
+  // Dropping the definitions that are not, until fixed point.
+  let mut fixed_point = false ;
+  while ! fixed_point {
+    fixed_point = true ;
+
+    // Number of conjuncts of init and trans mentioning each state variable,
+    // and whether one of them is a definition of it. A definition of the
+    // trans predicate only reads the current state: its current state
+    // variables are not mentioned.
+    let mut uses: HashMap<Sym, [ (usize, bool) ; 2 ]> = HashMap::new() ;
+    for pred in 0..2 {
+      for & (ref conj, ref def) in preds[pred].iter() {
+        let svars = match * def {
+          Some( (ref sym, ref term) ) => {
+            uses.entry(
+              sym.clone()
+            ).or_insert( [ (0, false) ; 2 ] )[pred].1 = true ;
+            if pred == 1 {
+              let mut svars = svars_at(term, Some(State::Next)) ;
+              svars.insert( sym.clone() ) ;
+              svars
+            } else { svars_of(conj) }
+          },
+          None => svars_of(conj),
+        } ;
+        for sym in svars.into_iter() {
+          uses.entry(sym).or_insert( [ (0, false) ; 2 ] )[pred].0 += 1
+        }
+      }
+    }
+
+    for pred in 0..2 {
+      for conj in preds[pred].iter_mut() {
+        let is_def = match conj.1 {
+          Some( (ref sym, _) ) => match uses.get(sym) {
+            Some(uses) => uses[pred].0 == 1 && match uses[1 - pred] {
+              (0, _) | (1, true) => true,
+              _ => false,
+            },
+            None => false,
+          },
+          None => true,
+        } ;
+        if ! is_def {
+          conj.1 = None ;
+          fixed_point = false
+        }
+      }
+    }
+  }
+
+  let trans = preds.pop().unwrap() ;
+  let init = preds.pop().unwrap() ;
+  (init, trans)
+}
+
+/// The definitions of the init and trans predicates of a system, see the
+/// [module documentation](index.html).
+pub fn definitions(sys: & Sys) -> (Vec<(Sym, Term)>, Vec<(Sym, Term)>) {
+  let (init, trans) = dependencies(sys) ;
+  let defs = |pred: Vec<Conj>| pred.into_iter().filter_map(
+    |(_, def)| def
+  ).collect() ;
+  ( defs(init), defs(trans) )
+}
+
+/// Pushes the top-level conjuncts of a term on a vector.
+fn conjuncts(term: & Term, conjs: & mut Vec<Term>) {
+  use term::real_term::Term::Op ;
+  if let Op(Operator::And, ref kids) = * term.get() {
+    for kid in kids.iter() {
+      conjuncts(kid, conjs)
+    }
+  } else {
+    conjs.push( term.clone() )
+  }
+}
+
+/// Cone of influence of some properties and assumptions over a system.
+pub fn cone(
+  sys: & Sys, props: & [Prop], assumptions: & [Term]
+) -> HashSet<Sym> {
+  let mut cone = HashSet::new() ;
+  for prop in props.iter() {
+    cone.extend( svars_of( prop.body().next() ) )
+  }
+  for term in assumptions.iter() {
+    cone.extend( svars_of(term) )
+  }
+
+  let (init, trans) = dependencies(sys) ;
+  let mut conjs: Vec<(Option<Sym>, HashSet<Sym>)> = init.into_iter().chain(
+    trans.into_iter()
+  ).map(
+    |(conj, def)| ( def.map( |(sym, _)| sym ), svars_of(& conj) )
+  ).collect() ;
+
+  // Merging conjuncts into the cone until fixed point.
+  let mut fixed_point = false ;
+  while ! fixed_point {
+    fixed_point = true ;
+    let mut rest = Vec::with_capacity( conjs.len() ) ;
+    for (def, svars) in conjs.into_iter() {
+      let relevant = match def {
+        Some(ref sym) => cone.contains(sym),
+        None => svars.iter().any( |sym| cone.contains(sym) ),
+      } ;
+      if relevant {
+        cone.extend(svars) ;
+        fixed_point = false
+      } else {
+        rest.push( (def, svars) )
+      }
+    }
+    conjs = rest
+  }
+
+  cone
+}
+
+/// Partitions some properties so that properties in different groups have
+/// disjoint cones of influence. Groups are in the order of their first
+/// property.
+pub fn groups(
+  sys: & Sys, props: & [Prop], assumptions: & [Term]
+) -> Vec< Vec<Prop> > {
+  let mut groups: Vec< (HashSet<Sym>, Vec<Prop>) > = vec![] ;
+  for prop in props.iter() {
+    let mut svars = cone(sys, & [ prop.clone() ], assumptions) ;
+    let mut group = vec![] ;
+    let mut rest = Vec::with_capacity( groups.len() ) ;
+    for (other_svars, other) in groups.into_iter() {
+      if other_svars.iter().any( |sym| svars.contains(sym) ) {
+        svars.extend(other_svars) ;
+        group.extend(other)
+      } else {
+        rest.push( (other_svars, other) )
+      }
+    }
+    group.push( prop.clone() ) ;
+    rest.push( (svars, group) ) ;
+    groups = rest
+  }
+  let mut groups: Vec< Vec<Prop> > = groups.into_iter().map(
+    |(_, group)| group
+  ).collect() ;
+  groups.sort_by_key(
+    |group| props.iter().position( |prop| prop == & group[0] )
+  ) ;
+  groups
+}
+
+/// Slices a system with respect to the cone of influence of some properties
+/// and assumptions.
+///
+/// The sliced system has the same name as the original one, as do its init
+/// and trans predicates. Returns the system itself if nothing can be sliced
+/// away.
+pub fn slice(
+  factory: & Factory, sys: & Sys, props: & [Prop], assumptions: & [Term]
+) -> Sys {
+  let cone = cone(sys, props, assumptions) ;
+  if sys.state().args().iter().all(
+    |& (ref sym, _)| cone.contains( sym.get() )
+  ) {
+    return sys.clone()
+  }
+
+  // Keeps a conjunct if it mentions no state variable outside the cone. It
+  // then mentions none or only ones from the cone.
+  let keep = |term: & Term| svars_of(term).iter().all(
+    |sym| cone.contains(sym)
+  ) ;
+  let keep_var = |var: & Var| cone.contains( var.sym() ) ;
+
+  let state = base::Args::mk(
+    sys.state().args().iter().filter(
+      |& & (ref sym, _)| cone.contains( sym.get() )
+    ).cloned().collect()
+  ) ;
+
+  let mut preds = vec![] ;
+  for pred in vec![ sys.init(), sys.trans() ].into_iter() {
+    let mut conjs = vec![] ;
+    conjuncts(& pred.2, & mut conjs) ;
+    let conjs: Vec<Term> = conjs.into_iter().filter( |t| keep(t) ).collect() ;
+    let body: Term = if conjs.is_empty() {
+      factory.cst(true)
+    } else {
+      factory.and(conjs)
+    } ;
+    let params: Vec<_> = pred.1.iter().filter(
+      |& & (ref var, _)| keep_var(var)
+    ).cloned().collect() ;
+    let app = factory.app(
+      pred.0.clone(), params.iter().map(
+        |& (ref var, _)| factory.mk_var( var.clone() )
+      ).collect()
+    ) ;
+    preds.push( (pred.0.clone(), params, body, app) )
+  }
+  let trans = preds.pop().unwrap() ;
+  let init = preds.pop().unwrap() ;
+
+  let subsys = sys.subsys().iter().filter(
+    |& & (_, ref params)| params.iter().all( |t| keep(t) )
+  ).cloned().collect() ;
+
+  Arc::new(
+    base::Sys::mk(
+      sys.sym().clone(), state, sys.locals().to_vec(), init, trans, subsys,
+      sys.calls().clone()
+    )
+  )
+}
+
+
+/// The invariants of a system that only mention state variables of one of
+/// its slices.
+pub fn restrict(sliced: & Sys, invs: & STermSet) -> STermSet {
+  invs.iter().filter(
+    |inv| svars_of( inv.next() ).iter().all(
+      |sym| sliced.state().contains(sym)
+    )
+  ).cloned().collect()
+}
+
+#[cfg(test)]
+mod test {
+  use term::{ Factory, SymMaker } ;
+  use ctxt::{ Context, Res } ;
+
+  #[test]
+  fn slice() {
+    let factory = Factory::mk() ;
+    let mut context = Context::mk(factory.clone(), 1000) ;
+    let src = "\
+      (define-sys sys ( (x Int) (y Int) (z Int) (w Int) )\n\
+        (and (= (_ curr x) 0) (= (_ curr y) 0) (= (_ curr z) 0))\n\
+        (and\n\
+          (= (_ next x) (+ (_ curr x) (_ curr y)))\n\
+          (= (_ next y) (+ (_ curr y) 1))\n\
+          (= (_ next z) (+ (_ curr z) (_ curr w)))\n\
+          (= (_ next w) (_ curr x))\n\
+        )\n\
+        ()\n\
+      )\n\
+      (define-prop on_x sys (>= (_ curr x) 0))\n\
+      (define-prop on_z sys (>= (_ curr z) 0))\n\
+      (verify sys (on_x on_z))\n\
+    " ;
+    let (sys, props) = match context.read(& mut src.as_bytes()) {
+      Ok( Res::Check(sys, props) ) => (sys, props),
+      Ok(res) => panic!("unexpected result {}", res.lines()),
+      Err(e) => panic!("{}", e),
+    } ;
+    let (x, y, z, w) = (
+      factory.sym("x"), factory.sym("y"), factory.sym("z"), factory.sym("w")
+    ) ;
+
+    let on_x: Vec<_> = props.iter().filter(
+      |prop| prop.sym().get() == & factory.sym("on_x")
+    ).cloned().collect() ;
+    let cone = super::cone(& sys, & on_x, & []) ;
+    assert!( cone.contains(& x) && cone.contains(& y) ) ;
+    assert!( ! cone.contains(& z) && ! cone.contains(& w) ) ;
+    let sliced = super::slice(& factory, & sys, & on_x, & []) ;
+    assert_eq!( sliced.state().len(), 2 ) ;
+    assert_eq!( sliced.init().1.len(), 2 ) ;
+    assert_eq!( sliced.trans().1.len(), 4 ) ;
+
+    // `z` depends on `w` which depends on `x`.
+    let on_z: Vec<_> = props.iter().filter(
+      |prop| prop.sym().get() == & factory.sym("on_z")
+    ).cloned().collect() ;
+    assert_eq!( super::cone(& sys, & on_z, & []).len(), 4 ) ;
+    let cone = super::cone(& sys, & props, & []) ;
+    assert_eq!( cone.len(), 4 ) ;
+    let sliced = super::slice(& factory, & sys, & props, & []) ;
+    assert_eq!( sliced.state().len(), 4 ) ;
+    assert_eq!( super::groups(& sys, & props, & []).len(), 1 )
+  }
+
+  #[test]
+  fn side_constraint() {
+    let factory = Factory::mk() ;
+    let mut context = Context::mk(factory.clone(), 1000) ;
+    let src = "\
+      (define-sys sys ( (x Int) (v Int) (w Int) )\n\
+        (and (= (_ curr v) 0) (= (_ curr w) 0))\n\
+        (and\n\
+          (= (_ next v) (* 2 (_ next x)))\n\
+          (>= (_ next v) 0)\n\
+          (= (+ (_ curr w) (_ next x)) (_ next w))\n\
+        )\n\
+        ()\n\
+      )\n\
+      (define-prop on_x sys (>= (_ curr x) 0))\n\
+      (verify sys (on_x))\n\
+    " ;
+    let (sys, props) = match context.read(& mut src.as_bytes()) {
+      Ok( Res::Check(sys, props) ) => (sys, props),
+      Ok(res) => panic!("unexpected result {}", res.lines()),
+      Err(e) => panic!("{}", e),
+    } ;
+    // The constraint `(>= (_ next v) 0)` restricts `x` through `v`, `w` is
+    // defined, with the equality in any order.
+    let cone = super::cone(& sys, & props, & []) ;
+    assert!( cone.contains(& factory.sym("x")) ) ;
+    assert!( cone.contains(& factory.sym("v")) ) ;
+    assert!( ! cone.contains(& factory.sym("w")) ) ;
+    let (init, trans) = super::definitions(& sys) ;
+    assert_eq!( init.len(), 1 ) ;
+    assert_eq!( init[0].0, factory.sym("w") ) ;
+    assert_eq!( trans.len(), 1 ) ;
+    assert_eq!( trans[0].0, factory.sym("w") ) ;
+    let sliced = super::slice(& factory, & sys, & props, & []) ;
+    assert_eq!( sliced.state().len(), 2 ) ;
+    assert!( format!("{}", sliced.trans().2).contains(">=") )
+  }
+
+  #[test]
+  fn groups() {
+    let factory = Factory::mk() ;
+    let mut context = Context::mk(factory.clone(), 1000) ;
+    let src = "\
+      (define-sys sys ( (x Int) (y Int) (z Int) )\n\
+        (and (= (_ curr x) 0) (= (_ curr y) 0) (= (_ curr z) 0))\n\
+        (and\n\
+          (= (_ next x) (+ (_ curr x) 1))\n\
+          (= (_ next y) (+ (_ curr y) 1))\n\
+          (= (_ next z) (- (_ curr z) 1))\n\
+        )\n\
+        ()\n\
+      )\n\
+      (define-prop on_x sys (>= (_ curr x) 0))\n\
+      (define-prop on_z sys (<= (_ curr z) 0))\n\
+      (define-prop on_xy sys (= (_ curr x) (_ curr y)))\n\
+      (verify sys (on_x on_z on_xy))\n\
+    " ;
+    let (sys, props) = match context.read(& mut src.as_bytes()) {
+      Ok( Res::Check(sys, props) ) => (sys, props),
+      Ok(res) => panic!("unexpected result {}", res.lines()),
+      Err(e) => panic!("{}", e),
+    } ;
+    let groups = super::groups(& sys, & props, & []) ;
+    assert_eq!( groups.len(), 2 ) ;
+    for group in groups.iter() {
+      let names: Vec<_> = group.iter().map(
+        |prop| prop.sym().get().clone()
+      ).collect() ;
+      if names.contains(& factory.sym("on_z")) {
+        assert_eq!( names.len(), 1 )
+      } else {
+        assert!( names.contains(& factory.sym("on_x")) ) ;
+        assert!( names.contains(& factory.sym("on_xy")) )
+      }
+    }
+  }
+
+  #[test]
+  fn groups_side_constraint() {
+    let factory = Factory::mk() ;
+    let mut context = Context::mk(factory.clone(), 1000) ;
+    let src = "\
+      (define-sys sys ( (x Int) (y Int) (v Int) )\n\
+        (and (= (_ curr x) 0) (= (_ curr y) 0) (= (_ curr v) 0))\n\
+        (and\n\
+          (= (_ next v) (+ (_ next x) (_ next y)))\n\
+          (<= (_ next v) 10)\n\
+        )\n\
+        ()\n\
+      )\n\
+      (define-prop on_x sys (<= (_ curr x) 10))\n\
+      (define-prop on_y sys (<= (_ curr y) 10))\n\
+      (verify sys (on_x on_y))\n\
+    " ;
+    let (sys, props) = match context.read(& mut src.as_bytes()) {
+      Ok( Res::Check(sys, props) ) => (sys, props),
+      Ok(res) => panic!("unexpected result {}", res.lines()),
+      Err(e) => panic!("{}", e),
+    } ;
+    // `x` and `y` are only coupled by the constraint on `v`, which is not a
+    // definition.
+    assert_eq!( super::cone(& sys, & props[0..1], & []).len(), 3 ) ;
+    let groups = super::groups(& sys, & props, & []) ;
+    assert_eq!( groups.len(), 1 ) ;
+    assert_eq!( groups[0].len(), 2 )
+  }
+}
@@ -71,6 +71,30 @@ impl Line {
   pub fn mk(line: String, subline: String, l: usize, c: usize) -> Self {
     Line { line: line, subline: subline, l: l, c: c }
   }
+  /// The line of a text containing the byte at some position, highlighting
+  /// that byte.
+  pub fn of_pos(txt: & str, pos: usize) -> Self {
+    Line::of_byte_pos( txt.as_bytes(), pos )
+  }
+  /// Same as `of_pos` on some bytes, for binary formats. Illegal UTF-8 is
+  /// replaced in the line.
+  pub fn of_byte_pos(txt: & [u8], pos: usize) -> Self {
+    let pos = if pos > txt.len() { txt.len() } else { pos } ;
+    let bgn = txt[..pos].iter().rposition(
+      |c| * c == b'\n'
+    ).map( |n| n + 1 ).unwrap_or(0) ;
+    let end = txt[pos..].iter().position(
+      |c| * c == b'\n'
+    ).map( |n| pos + n ).unwrap_or( txt.len() ) ;
+    let l = txt[..bgn].iter().filter( |c| * * c == b'\n' ).count() + 1 ;
+    let c = pos - bgn + 1 ;
+    let mut subline = String::with_capacity(c) ;
+    for _ in 1..c { subline.push(' ') }
+    subline.push('^') ;
+    Line::mk(
+      String::from_utf8_lossy(& txt[bgn..end]).into_owned(), subline, l, c
+    )
+  }
 }
 impl fmt::Display for Line {
   fn fmt(& self, fmt: & mut fmt::Formatter) -> fmt::Result {
@@ -130,6 +154,14 @@ impl fmt::Display for Error {
 mod base ;
 mod type_check ;
 mod parse ;
+mod translation ;
+mod lustre ;
+mod btor ;
+mod aiger ;
+mod vmtlib ;
+mod live ;
+pub mod chc ;
+pub mod coi ;
 
 /// Real types of the elements of a context.
 pub mod real_sys {
@@ -146,11 +178,71 @@ pub mod ctxt {
   } ;
   pub use super::parse::check::CheckError ;
   pub use type_check::type_check ;
+  pub use vmtlib::is_annotated as is_vmtlib ;
 }
 
 pub use base::{ CallSet, PropStatus } ;
 
-pub use parse::Cex ;
+pub use parse::{ Cex, Inconsistency } ;
+
+/// Formats a string as a JSON string literal, quotes included.
+pub fn json_str(s: & str) -> String {
+  let mut res = String::with_capacity(s.len() + 2) ;
+  res.push('"') ;
+  for c in s.chars() {
+    match c {
+      '"' => res.push_str("\\\""),
+      '\\' => res.push_str("\\\\"),
+      '\n' => res.push_str("\\n"),
+      '\r' => res.push_str("\\r"),
+      '\t' => res.push_str("\\t"),
+      c if (c as u32) < 0x20 => res.push_str(
+        & format!("\\u{:04x}", c as u32)
+      ),
+      c => res.push(c),
+    }
+  }
+  res.push('"') ;
+  res
+}
+
+/// Turns a string into a file name, replacing problematic characters with
+/// `_`.
+pub fn file_name(s: & str) -> String {
+  s.chars().map(
+    |c| if c.is_alphanumeric() || c == '_' || c == '-' || c == '.' {
+      c
+    } else { '_' }
+  ).collect()
+}
+
+/// Turns some strings into distinct file names, see
+/// [`file_name`](fn.file_name.html).
+///
+/// A name already taken gets the first index suffix making it unique: `a b`
+/// and `a_b` become `a_b` and `a_b_1`. Suffixed names are never the file
+/// name of one of the strings.
+pub fn file_names<'a, Strs: IntoIterator<Item = & 'a str>>(
+  strs: Strs
+) -> Vec<String> {
+  use std::collections::HashSet ;
+  let names: Vec<String> = strs.into_iter().map(file_name).collect() ;
+  let mut taken = HashSet::with_capacity( names.len() ) ;
+  let mut res = Vec::with_capacity( names.len() ) ;
+  for name in names.iter() {
+    let mut unique = name.clone() ;
+    let mut index = 1 ;
+    while taken.contains(& unique) || (
+      & unique != name && names.contains(& unique)
+    ) {
+      unique = format!("{}_{}", name, index) ;
+      index += 1
+    }
+    taken.insert( unique.clone() ) ;
+    res.push(unique)
+  }
+  res
+}
 
 /// A signature, a list of types. Used only in `Uf`.
 pub type Sig = Arc<base::Sig> ;
@@ -168,3 +260,16 @@ pub type Prop = Arc<base::Prop> ;
 pub type Sys = Arc<base::Sys> ;
 
 
+
+#[cfg(test)]
+mod test {
+  use super::file_names ;
+
+  #[test]
+  fn distinct_file_names() {
+    assert_eq!(
+      file_names( vec![ "a b", "a_b", "a_b_1", "c", "a(b" ] ),
+      vec![ "a_b", "a_b_2", "a_b_1", "c", "a_b_3" ]
+    )
+  }
+}
@@ -0,0 +1,303 @@
+// Copyright 2016 Adrien Champion. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*! Liveness-to-safety reduction.
+
+A liveness property `(define-live <name> <sys> <request> <response>
+(<justice>*))` states that, on the paths of `<sys>` where each `<justice>`
+term holds infinitely often, every state where `<request>` holds is followed,
+now or later, by a state where `<response>` holds.
+
+It is reduced to a safety property of an augmented system `live[<name>]`,
+which calls `<sys>` and adds a monitor to its state:
+
+* `%pending` holds iff a request is not answered yet,
+* `%save` is an input, the state where it first holds is saved as the start
+  of a loop, and `%saved` holds after that,
+* `%copy_<v>` is the value of the state variable `<v>` of `<sys>` in the
+  saved state,
+* `%unanswered` holds iff `%pending` held in all the states since the saved
+  one,
+* `%fair_<i>` holds iff the `i`-th justice term held in some state since the
+  saved one.
+
+The safety property states that no state, after the saved one, is equal to
+the saved one while all the justice terms and `%pending` held in the loop in
+between. A counterexample is thus a lasso: its last state is equal to the
+first state of the loop, and repeating the loop forever gives a path where a
+request is never answered. */
+
+use term::{
+  Sym, Var, Type, Term, STerm, State, Factory,
+  SymMaker, VarMaker, AppMaker, UnTermOps
+} ;
+use term::parsing::{ Spn, Spnd } ;
+
+use base ;
+
+/// A liveness property reduced to a safety property.
+pub struct Reduction {
+  /// Augmented system.
+  pub sys: base::Sys,
+  /// Body of the safety property over the augmented system.
+  pub body: STerm,
+  /// State variables of the monitor, hidden in counterexamples.
+  pub monitor: Vec<Sym>,
+  /// State variable holding in the first state of the loop.
+  pub save: Sym,
+}
+
+/// Adds a state variable to the monitor. Fails if `sys` already has a state
+/// variable with the same name.
+fn monitor_svar(
+  factory: & Factory, sys: & ::Sys, span: & Spn,
+  state: & mut Vec<(Spnd<Sym>, Spnd<Type>)>, monitor: & mut Vec<Sym>,
+  name: String, typ: Type
+) -> Result<Sym, String> {
+  let sym = factory.sym(name) ;
+  if sys.state().contains(& sym) {
+    return Err(
+      format!(
+        "state variable `{}` of system {} clashes with the liveness monitor",
+        sym, sys.sym()
+      )
+    )
+  }
+  state.push(
+    ( Spnd::mk(sym.clone(), span.clone()), Spnd::mk(typ, span.clone()) )
+  ) ;
+  monitor.push( sym.clone() ) ;
+  Ok(sym)
+}
+
+/// Reduces a liveness property of a system to a safety property, see the
+/// module-level documentation.
+///
+/// The terms only mention state variables of `sys` in the current state.
+/// `calls` are the callables they use.
+pub fn reduce(
+  factory: & Factory, sym: & Spnd<Sym>, sys: & ::Sys,
+  request: Term, response: Term, justice: Vec<Term>, calls: & base::CallSet
+) -> Result<Reduction, String> {
+  use term::State::* ;
+
+  let span = sym.span.clone() ;
+  let live_sym = factory.sym( format!("live[{}]", sym.get().sym()) ) ;
+  let svar = |sym: & Sym, st: State| -> Term {
+    factory.svar(sym.clone(), st)
+  } ;
+  let bump = |term: & Term| factory.bump(term).map_err(
+    |e| format!("{}", e)
+  ) ;
+
+  // State of the augmented system, the one of `sys` and the monitor.
+  let mut state = sys.state().args().to_vec() ;
+  let mut monitor = vec![] ;
+  let pending = try!(
+    monitor_svar(
+      factory, sys, & span, & mut state, & mut monitor,
+      "%pending".into(), Type::Bool
+    )
+  ) ;
+  let save = try!(
+    monitor_svar(
+      factory, sys, & span, & mut state, & mut monitor,
+      "%save".into(), Type::Bool
+    )
+  ) ;
+  let saved = try!(
+    monitor_svar(
+      factory, sys, & span, & mut state, & mut monitor,
+      "%saved".into(), Type::Bool
+    )
+  ) ;
+  let unanswered = try!(
+    monitor_svar(
+      factory, sys, & span, & mut state, & mut monitor,
+      "%unanswered".into(), Type::Bool
+    )
+  ) ;
+  let mut fair = Vec::with_capacity( justice.len() ) ;
+  for (index, term) in justice.into_iter().enumerate() {
+    let fair_sym = try!(
+      monitor_svar(
+        factory, sys, & span, & mut state, & mut monitor,
+        format!("%fair_{}", index), Type::Bool
+      )
+    ) ;
+    fair.push( (fair_sym, term) )
+  }
+  let mut copies = Vec::with_capacity( sys.state().len() ) ;
+  for & (ref v_sym, ref typ) in sys.state().args() {
+    let copy = try!(
+      monitor_svar(
+        factory, sys, & span, & mut state, & mut monitor,
+        format!("%copy_{}", v_sym.get().sym()), typ.get().clone()
+      )
+    ) ;
+    copies.push( (v_sym.get().clone(), copy) )
+  }
+
+  // `sys` is called on its own state variables.
+  let params: Vec<Term> = sys.state().args().iter().map(
+    |& (ref v_sym, _)| svar(v_sym.get(), Curr)
+  ).collect() ;
+  let mut trans_params = params.clone() ;
+  for param in params.iter() {
+    trans_params.push( try!( bump(param) ) )
+  }
+
+  let mut init = vec![
+    factory.eq(
+      vec![
+        svar(& pending, Curr),
+        factory.and(
+          vec![ request.clone(), factory.not( response.clone() ) ]
+        )
+      ]
+    ),
+    factory.not( svar(& saved, Curr) ),
+    svar(& unanswered, Curr),
+  ] ;
+  for & (ref fair_sym, _) in fair.iter() {
+    init.push( factory.not( svar(fair_sym, Curr) ) )
+  }
+  init.push( factory.app( sys.init().0.clone(), params.clone() ) ) ;
+
+  // The current state is in the loop.
+  let in_loop = factory.or(
+    vec![ svar(& saved, Curr), svar(& save, Curr) ]
+  ) ;
+  let mut trans = vec![
+    factory.eq(
+      vec![
+        svar(& pending, Next),
+        factory.and(
+          vec![
+            factory.or(
+              vec![ svar(& pending, Curr), try!( bump(& request) ) ]
+            ),
+            factory.not( try!( bump(& response) ) )
+          ]
+        )
+      ]
+    ),
+    factory.eq( vec![ svar(& saved, Next), in_loop.clone() ] ),
+    factory.eq(
+      vec![
+        svar(& unanswered, Next),
+        factory.and(
+          vec![
+            svar(& unanswered, Curr),
+            factory.or(
+              vec![
+                factory.not( in_loop.clone() ), svar(& pending, Curr)
+              ]
+            )
+          ]
+        )
+      ]
+    ),
+  ] ;
+  for & (ref fair_sym, ref term) in fair.iter() {
+    trans.push(
+      factory.eq(
+        vec![
+          svar(fair_sym, Next),
+          factory.and(
+            vec![
+              in_loop.clone(),
+              factory.or( vec![ svar(fair_sym, Curr), term.clone() ] )
+            ]
+          )
+        ]
+      )
+    )
+  }
+  // Saving happens in the first state where `%save` holds.
+  let saving = factory.and(
+    vec![ svar(& save, Curr), factory.not( svar(& saved, Curr) ) ]
+  ) ;
+  for & (ref v_sym, ref copy) in copies.iter() {
+    trans.push(
+      factory.eq(
+        vec![
+          svar(copy, Next),
+          factory.ite(
+            saving.clone(), svar(v_sym, Curr), svar(copy, Curr)
+          )
+        ]
+      )
+    )
+  }
+  trans.push( factory.app( sys.trans().0.clone(), trans_params ) ) ;
+
+  // Loop closed, fair and unanswered.
+  let mut bad = vec![ svar(& saved, Curr), svar(& unanswered, Curr) ] ;
+  for & (ref fair_sym, _) in fair.iter() {
+    bad.push( svar(fair_sym, Curr) )
+  }
+  for & (ref v_sym, ref copy) in copies.iter() {
+    bad.push( factory.eq( vec![ svar(v_sym, Curr), svar(copy, Curr) ] ) )
+  }
+  let body = factory.not( factory.and(bad) ) ;
+  let body = STerm::One( body.clone(), try!( bump(& body) ) ) ;
+
+  // Init and trans predicates, as in `check_sys`.
+  let mut init_state = Vec::with_capacity( state.len() ) ;
+  let mut trans_state = Vec::with_capacity( 2 * state.len() ) ;
+  let mut next_state = Vec::with_capacity( state.len() ) ;
+  for & (ref v_sym, ref typ) in state.iter() {
+    let curr: Var = factory.svar(v_sym.get().clone(), Curr) ;
+    init_state.push( (curr.clone(), typ.get().clone()) ) ;
+    trans_state.push( (curr.clone(), typ.get().clone()) ) ;
+    let next: Var = factory.svar(v_sym.get().clone(), Next) ;
+    next_state.push( (next, typ.get().clone()) ) ;
+    try!(
+      factory.set_var_type(
+        Some( live_sym.clone() ), curr, typ.get().clone()
+      ).map_err( |e| format!("{}", e) )
+    )
+  }
+  trans_state.extend(next_state) ;
+
+  let init_sym = factory.sym( format!("init[{}]", live_sym.sym()) ) ;
+  let trans_sym = factory.sym( format!("trans[{}]", live_sym.sym()) ) ;
+  let init_app = factory.app(
+    init_sym.clone(), init_state.iter().map(
+      |& (ref var, _)| factory.mk_var( var.clone() )
+    ).collect()
+  ) ;
+  let trans_app = factory.app(
+    trans_sym.clone(), trans_state.iter().map(
+      |& (ref var, _)| factory.mk_var( var.clone() )
+    ).collect()
+  ) ;
+
+  let mut live_calls = sys.calls().clone() ;
+  for call in calls.get() {
+    live_calls.insert( call.clone() )
+  }
+
+  let init = factory.and(init) ;
+  let trans = factory.and(trans) ;
+  Ok(
+    Reduction {
+      sys: base::Sys::mk(
+        Spnd::mk(live_sym, span), base::Args::mk(state), vec![],
+        (init_sym, init_state, init, init_app),
+        (trans_sym, trans_state, trans, trans_app),
+        vec![ (sys.clone(), params) ], live_calls,
+      ),
+      body: body,
+      monitor: monitor,
+      save: save,
+    }
+  )
+}
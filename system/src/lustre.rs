@@ -0,0 +1,1283 @@
+// Copyright 2016 Adrien Champion. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*! Lustre front-end.
+
+Translates a Lustre program to VMT-LIB, which is then read by a
+[`Context`](../ctxt/struct.Context.html). The subset supported is
+
+* `node`s (or `function`s) over `bool`, `int` and `real` streams, with
+  `const` inputs and local `var`iables,
+* top-level constants `const n = 10 ;`,
+* equations, multi-output ones included `(a, b) = f(x) ;`, and `assert`s,
+* `if then else`, `pre`, `->`, node calls and the usual operators,
+* `--%PROPERTY <expr> ;` and `--%MAIN ;` annotations.
+
+Each node becomes a system. Its state is its inputs, outputs and locals,
+followed by internal state variables whose name starts with `%`:
+
+* `%init` is true in the first instant only and encodes `->`,
+* `%pre.<n>` holds the previous value of the `n`-th `pre` expression,
+* `%<node>.<n>.<svar>` is state variable `<svar>` of the `n`-th call to
+  `<node>`.
+
+Node calls are compositions with the system of the callee. Internal state
+variables are hidden in counterexamples, which thus only mention Lustre
+streams.
+
+The properties of the main node, the one annotated with `--%MAIN` or the
+last one otherwise, are checked. They are named after their text. The
+properties of the other nodes are ignored. */
+
+use std::fmt ;
+use std::collections::{ HashMap, HashSet } ;
+
+use { Error, Line } ;
+use translation::Translation ;
+
+/// Result of a translation step, errors are a position in the program and a
+/// message.
+type LusRes<T> = Result<T, (usize, String)> ;
+
+/// Operators and punctuation, longest first.
+static symbols: [ & 'static str ; 18 ] = [
+  "->", "=>", "<>", "<=", ">=", "<", ">", "=", "+", "-", "*", "/",
+  "(", ")", ",", ";", ":", ".",
+] ;
+
+/// Keywords, cannot be used as identifiers.
+static keywords: [ & 'static str ; 23 ] = [
+  "node", "function", "returns", "var", "let", "tel", "const", "assert",
+  "if", "then", "else", "pre", "not", "and", "or", "xor", "div", "mod",
+  "true", "false", "bool", "int", "real",
+] ;
+
+/// True if a byte can start an identifier.
+fn id_head(c: u8) -> bool {
+  (b'a' <= c && c <= b'z') || (b'A' <= c && c <= b'Z') || c == b'_'
+}
+/// True if a byte is a digit.
+fn digit(c: u8) -> bool {
+  b'0' <= c && c <= b'9'
+}
+
+/// SMT-LIB symbol for a name, quoted unless it is a Lustre identifier.
+fn smt_sym(name: & str) -> String {
+  let bytes = name.as_bytes() ;
+  if ! bytes.is_empty() && id_head(bytes[0]) && bytes.iter().all(
+    |c| id_head(* c) || digit(* c)
+  ) {
+    name.to_string()
+  } else {
+    format!("|{}|", name)
+  }
+}
+
+/// Lustre tokens.
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+  /// Identifier or keyword.
+  Id(String),
+  /// Integer constant.
+  Int(String),
+  /// Real constant.
+  Real(String),
+  /// Operator or punctuation.
+  Sym(& 'static str),
+  /// `--%PROPERTY` annotation.
+  Prop,
+  /// `--%MAIN` annotation.
+  Main,
+}
+
+/// Splits a Lustre program in tokens, with their position.
+fn lex(src: & str) -> LusRes< Vec<(Tok, usize)> > {
+  let bytes = src.as_bytes() ;
+  let len = bytes.len() ;
+  let mut toks = Vec::with_capacity(len / 3) ;
+  let mut i = 0 ;
+  while i < len {
+    let c = bytes[i] ;
+    if c == b' ' || c == b'\t' || c == b'\n' || c == b'\r' {
+      i += 1 ; continue
+    }
+
+    // Annotations and line comments.
+    if src[i..].starts_with("--%PROPERTY") {
+      toks.push( (Tok::Prop, i) ) ;
+      i += 11 ; continue
+    }
+    if src[i..].starts_with("--%MAIN") {
+      toks.push( (Tok::Main, i) ) ;
+      i += 7 ; continue
+    }
+    if src[i..].starts_with("--") {
+      while i < len && bytes[i] != b'\n' { i += 1 }
+      continue
+    }
+    // Block comments.
+    if src[i..].starts_with("(*") || src[i..].starts_with("/*") {
+      let close = if c == b'(' { "*)" } else { "*/" } ;
+      match src[i + 2 ..].find(close) {
+        Some(n) => { i += n + 4 ; continue },
+        None => return Err( (i, "unterminated comment".into()) ),
+      }
+    }
+
+    // Identifiers and keywords.
+    if id_head(c) {
+      let start = i ;
+      while i < len && ( id_head(bytes[i]) || digit(bytes[i]) ) { i += 1 }
+      toks.push( ( Tok::Id( src[start..i].to_string() ), start ) ) ;
+      continue
+    }
+
+    // Numbers.
+    if digit(c) {
+      let start = i ;
+      while i < len && digit(bytes[i]) { i += 1 }
+      if i < len && bytes[i] == b'.' {
+        let int = & src[start..i] ;
+        i += 1 ;
+        let frac_start = i ;
+        while i < len && digit(bytes[i]) { i += 1 }
+        let frac = if frac_start == i { "0" } else { & src[frac_start..i] } ;
+        toks.push( ( Tok::Real( format!("{}.{}", int, frac) ), start ) )
+      } else {
+        toks.push( ( Tok::Int( src[start..i].to_string() ), start ) )
+      }
+      if i < len && id_head(bytes[i]) {
+        return Err( (start, "illegal numeric constant".into()) )
+      }
+      continue
+    }
+
+    // Operators and punctuation.
+    match symbols.iter().find( |sym| src[i..].starts_with(* * sym) ) {
+      Some(sym) => {
+        toks.push( (Tok::Sym(* sym), i) ) ;
+        i += sym.len()
+      },
+      None => return Err(
+        (
+          i, format!(
+            "unexpected character `{}`", src[i..].chars().next().unwrap()
+          )
+        )
+      ),
+    }
+  }
+  Ok(toks)
+}
+
+/// Sorts of the streams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Sort {
+  /// Booleans.
+  Bool,
+  /// Integers.
+  Int,
+  /// Reals.
+  Real,
+}
+impl Sort {
+  /// SMT-LIB sort.
+  fn smt(& self) -> & 'static str {
+    match * self {
+      Sort::Bool => "Bool",
+      Sort::Int => "Int",
+      Sort::Real => "Real",
+    }
+  }
+}
+impl fmt::Display for Sort {
+  fn fmt(& self, fmt: & mut fmt::Formatter) -> fmt::Result {
+    match * self {
+      Sort::Bool => write!(fmt, "bool"),
+      Sort::Int => write!(fmt, "int"),
+      Sort::Real => write!(fmt, "real"),
+    }
+  }
+}
+
+/// Lustre expressions. Positions are used for error reporting.
+#[derive(Debug, Clone)]
+enum Expr {
+  /// Boolean constant.
+  Bool(bool),
+  /// Integer constant.
+  Int(String),
+  /// Real constant.
+  Real(String),
+  /// Stream or constant identifier.
+  Id(String, usize),
+  /// Negation.
+  Not(Box<Expr>, usize),
+  /// Unary minus.
+  Neg(Box<Expr>, usize),
+  /// Previous value.
+  Pre(Box<Expr>),
+  /// Binary operator.
+  Bin(& 'static str, Box<Expr>, Box<Expr>, usize),
+  /// If then else.
+  Ite(Box<Expr>, Box<Expr>, Box<Expr>, usize),
+  /// Initialization `->`.
+  Arrow(Box<Expr>, Box<Expr>, usize),
+  /// Node call.
+  Call(String, Vec<Expr>, usize),
+}
+impl Expr {
+  /// Adds the nodes called by an expression to a set.
+  fn calls(& self, set: & mut HashSet<String>) {
+    match * self {
+      Expr::Bool(_) | Expr::Int(_) | Expr::Real(_) | Expr::Id(_, _) => (),
+      Expr::Not(ref e, _) | Expr::Neg(ref e, _) | Expr::Pre(ref e) => {
+        e.calls(set)
+      },
+      Expr::Bin(_, ref lft, ref rgt, _) | Expr::Arrow(ref lft, ref rgt, _) => {
+        lft.calls(set) ;
+        rgt.calls(set)
+      },
+      Expr::Ite(ref c, ref t, ref e, _) => {
+        c.calls(set) ;
+        t.calls(set) ;
+        e.calls(set)
+      },
+      Expr::Call(ref node, ref args, _) => {
+        set.insert( node.clone() ) ;
+        for arg in args.iter() { arg.calls(set) }
+      },
+    }
+  }
+}
+
+/// A stream declaration.
+#[derive(Debug, Clone)]
+struct Decl {
+  /// Identifier.
+  id: String,
+  /// Position of the identifier.
+  pos: usize,
+  /// Sort.
+  sort: Sort,
+  /// True for `const` inputs.
+  cst: bool,
+}
+
+/// A node.
+#[derive(Debug, Clone)]
+struct Node {
+  /// Name.
+  name: String,
+  /// Position of the name.
+  pos: usize,
+  /// Inputs.
+  inputs: Vec<Decl>,
+  /// Outputs.
+  outputs: Vec<Decl>,
+  /// Local streams.
+  locals: Vec<Decl>,
+  /// Equations: the streams defined with their position, and the definition.
+  eqs: Vec<( Vec<(String, usize)>, Expr )>,
+  /// Assertions.
+  asserts: Vec<Expr>,
+  /// Properties and their text.
+  props: Vec<(String, Expr)>,
+  /// True if the node is annotated with `--%MAIN`.
+  main: bool,
+}
+
+/// A Lustre program.
+struct Program {
+  /// Top-level constants, with their position and optional sort.
+  consts: Vec<(String, usize, Option<Sort>, Expr)>,
+  /// Nodes.
+  nodes: Vec<Node>,
+}
+
+/// Recursive descent parser over some tokens.
+struct Parser<'a> {
+  /// Program parsed, used to retrieve the text of the properties.
+  src: & 'a str,
+  /// Tokens of the program.
+  toks: Vec<(Tok, usize)>,
+  /// Index of the next token.
+  cursor: usize,
+}
+impl<'a> Parser<'a> {
+  /// Creates a parser.
+  fn mk(src: & 'a str) -> LusRes<Self> {
+    let toks = try!( lex(src) ) ;
+    Ok( Parser { src: src, toks: toks, cursor: 0 } )
+  }
+
+  /// Position of the next token.
+  fn pos(& self) -> usize {
+    self.toks.get(self.cursor).map(
+      |& (_, pos)| pos
+    ).unwrap_or( self.src.len() )
+  }
+  /// Next token.
+  fn peek(& self) -> Option<Tok> {
+    self.toks.get(self.cursor).map( |& (ref tok, _)| tok.clone() )
+  }
+  /// True if the next token is a keyword.
+  fn is_kw(& self, kw: & str) -> bool {
+    match self.peek() {
+      Some( Tok::Id(ref id) ) => id == kw,
+      _ => false,
+    }
+  }
+  /// True if the next token is an operator or some punctuation.
+  fn is_sym(& self, sym: & str) -> bool {
+    match self.peek() {
+      Some( Tok::Sym(s) ) => s == sym,
+      _ => false,
+    }
+  }
+  /// Consumes a keyword or a symbol if it is the next token.
+  fn eat(& mut self, s: & str) -> bool {
+    let is_it = self.is_kw(s) || self.is_sym(s) ;
+    if is_it { self.cursor += 1 }
+    is_it
+  }
+  /// Consumes a keyword or a symbol, fails if it is not the next token.
+  fn expect(& mut self, s: & str) -> LusRes<()> {
+    if self.eat(s) { Ok(()) } else {
+      Err( ( self.pos(), format!("expected `{}`", s) ) )
+    }
+  }
+  /// True if the next token is an identifier.
+  fn is_id(& self) -> bool {
+    match self.peek() {
+      Some( Tok::Id(ref id) ) => ! keywords.contains( & id.as_str() ),
+      _ => false,
+    }
+  }
+  /// Parses an identifier.
+  fn id(& mut self) -> LusRes<(String, usize)> {
+    let pos = self.pos() ;
+    match self.peek() {
+      Some( Tok::Id(id) ) => if self.is_id() {
+        self.cursor += 1 ;
+        Ok( (id, pos) )
+      } else {
+        Err( (pos, format!("expected identifier, got keyword `{}`", id)) )
+      },
+      _ => Err( (pos, "expected identifier".into()) ),
+    }
+  }
+
+  /// Parses a program.
+  fn program(& mut self) -> LusRes<Program> {
+    let mut consts = vec![] ;
+    let mut nodes = vec![] ;
+    while self.peek().is_some() {
+      if self.eat("const") {
+        loop {
+          let (id, pos) = try!( self.id() ) ;
+          let sort = if self.eat(":") {
+            Some( try!( self.sort() ) )
+          } else { None } ;
+          try!( self.expect("=") ) ;
+          let expr = try!( self.expr() ) ;
+          try!( self.expect(";") ) ;
+          consts.push( (id, pos, sort, expr) ) ;
+          if ! self.is_id() { break }
+        }
+      } else if self.eat("node") || self.eat("function") {
+        nodes.push( try!( self.node() ) )
+      } else {
+        return Err(
+          ( self.pos(), "expected `node`, `function` or `const`".into() )
+        )
+      }
+    }
+    Ok( Program { consts: consts, nodes: nodes } )
+  }
+
+  /// Parses a sort.
+  fn sort(& mut self) -> LusRes<Sort> {
+    if self.eat("bool") {
+      Ok(Sort::Bool)
+    } else if self.eat("int") {
+      Ok(Sort::Int)
+    } else if self.eat("real") {
+      Ok(Sort::Real)
+    } else {
+      Err( ( self.pos(), "expected `bool`, `int` or `real`".into() ) )
+    }
+  }
+
+  /// Parses some declarations sharing a sort, `a, b: int`. Accepts a
+  /// leading `const` if `csts`.
+  fn decl_group(& mut self, csts: bool) -> LusRes< Vec<Decl> > {
+    let cst = csts && self.eat("const") ;
+    let mut ids = vec![ try!( self.id() ) ] ;
+    while self.eat(",") {
+      ids.push( try!( self.id() ) )
+    }
+    try!( self.expect(":") ) ;
+    let sort = try!( self.sort() ) ;
+    Ok(
+      ids.into_iter().map(
+        |(id, pos)| Decl { id: id, pos: pos, sort: sort, cst: cst }
+      ).collect()
+    )
+  }
+
+  /// Parses some parameters, closing parenthesis included.
+  fn params(& mut self, csts: bool) -> LusRes< Vec<Decl> > {
+    let mut decls = vec![] ;
+    if self.eat(")") { return Ok(decls) }
+    loop {
+      decls.extend( try!( self.decl_group(csts) ) ) ;
+      if ! self.eat(";") || self.is_sym(")") { break }
+    }
+    try!( self.expect(")") ) ;
+    Ok(decls)
+  }
+
+  /// Parses a node, keyword excluded.
+  fn node(& mut self) -> LusRes<Node> {
+    let (name, pos) = try!( self.id() ) ;
+    try!( self.expect("(") ) ;
+    let inputs = try!( self.params(true) ) ;
+    try!( self.expect("returns") ) ;
+    try!( self.expect("(") ) ;
+    let outputs = try!( self.params(false) ) ;
+    self.eat(";") ;
+    let mut locals = vec![] ;
+    if self.eat("var") {
+      while ! self.is_kw("let") {
+        locals.extend( try!( self.decl_group(false) ) ) ;
+        try!( self.expect(";") )
+      }
+    }
+    try!( self.expect("let") ) ;
+
+    let mut node = Node {
+      name: name, pos: pos, inputs: inputs, outputs: outputs, locals: locals,
+      eqs: vec![], asserts: vec![], props: vec![], main: false,
+    } ;
+
+    loop {
+      match self.peek() {
+        None => return Err( ( self.pos(), "expected `tel`".into() ) ),
+        Some( Tok::Prop ) => {
+          self.cursor += 1 ;
+          let start = self.pos() ;
+          let expr = try!( self.expr() ) ;
+          let end = self.pos() ;
+          try!( self.expect(";") ) ;
+          let text: Vec<& str> = self.src[start..end].split_whitespace(
+          ).collect() ;
+          node.props.push( (text.join(" "), expr) )
+        },
+        Some( Tok::Main ) => {
+          self.cursor += 1 ;
+          self.eat(";") ;
+          node.main = true
+        },
+        _ => if self.eat("tel") {
+          break
+        } else if self.eat("assert") {
+          node.asserts.push( try!( self.expr() ) ) ;
+          try!( self.expect(";") )
+        } else {
+          let paren = self.eat("(") ;
+          let mut lhs = vec![ try!( self.id() ) ] ;
+          while self.eat(",") {
+            lhs.push( try!( self.id() ) )
+          }
+          if paren { try!( self.expect(")") ) }
+          try!( self.expect("=") ) ;
+          let expr = try!( self.expr() ) ;
+          try!( self.expect(";") ) ;
+          node.eqs.push( (lhs, expr) )
+        },
+      }
+    }
+    if ! self.eat(";") { self.eat(".") ; }
+
+    Ok(node)
+  }
+
+  /// Parses an expression.
+  fn expr(& mut self) -> LusRes<Expr> {
+    let lhs = try!( self.implies() ) ;
+    let pos = self.pos() ;
+    if self.eat("->") {
+      let rhs = try!( self.expr() ) ;
+      Ok( Expr::Arrow( Box::new(lhs), Box::new(rhs), pos ) )
+    } else {
+      Ok(lhs)
+    }
+  }
+
+  /// Parses an implication, right associative.
+  fn implies(& mut self) -> LusRes<Expr> {
+    let lhs = try!( self.or() ) ;
+    let pos = self.pos() ;
+    if self.eat("=>") {
+      let rhs = try!( self.implies() ) ;
+      Ok( Expr::Bin( "=>", Box::new(lhs), Box::new(rhs), pos ) )
+    } else {
+      Ok(lhs)
+    }
+  }
+
+  /// Parses some left associative binary operators.
+  fn left_assoc(
+    & mut self, ops: & [ & 'static str ],
+    sub: fn(& mut Parser<'a>) -> LusRes<Expr>
+  ) -> LusRes<Expr> {
+    let mut lhs = try!( sub(self) ) ;
+    'ops: loop {
+      let pos = self.pos() ;
+      for op in ops.iter() {
+        if self.eat(op) {
+          let rhs = try!( sub(self) ) ;
+          lhs = Expr::Bin( * op, Box::new(lhs), Box::new(rhs), pos ) ;
+          continue 'ops
+        }
+      }
+      return Ok(lhs)
+    }
+  }
+
+  /// Parses a disjunction.
+  fn or(& mut self) -> LusRes<Expr> {
+    self.left_assoc( & ["or", "xor"], Parser::and )
+  }
+  /// Parses a conjunction.
+  fn and(& mut self) -> LusRes<Expr> {
+    self.left_assoc( & ["and"], Parser::cmp )
+  }
+  /// Parses a comparison.
+  fn cmp(& mut self) -> LusRes<Expr> {
+    self.left_assoc( & ["=", "<>", "<=", ">=", "<", ">"], Parser::add )
+  }
+  /// Parses a sum.
+  fn add(& mut self) -> LusRes<Expr> {
+    self.left_assoc( & ["+", "-"], Parser::mul )
+  }
+  /// Parses a product.
+  fn mul(& mut self) -> LusRes<Expr> {
+    self.left_assoc( & ["*", "/", "div", "mod"], Parser::unary )
+  }
+
+  /// Parses a unary operator application.
+  fn unary(& mut self) -> LusRes<Expr> {
+    let pos = self.pos() ;
+    if self.eat("not") {
+      Ok( Expr::Not( Box::new( try!( self.unary() ) ), pos ) )
+    } else if self.eat("-") {
+      Ok( Expr::Neg( Box::new( try!( self.unary() ) ), pos ) )
+    } else if self.eat("pre") {
+      Ok( Expr::Pre( Box::new( try!( self.unary() ) ) ) )
+    } else {
+      self.atom()
+    }
+  }
+
+  /// Parses a constant, an identifier, a call, an if then else or a
+  /// parenthesized expression.
+  fn atom(& mut self) -> LusRes<Expr> {
+    let pos = self.pos() ;
+    match self.peek() {
+      Some( Tok::Int(int) ) => {
+        self.cursor += 1 ;
+        return Ok( Expr::Int(int) )
+      },
+      Some( Tok::Real(real) ) => {
+        self.cursor += 1 ;
+        return Ok( Expr::Real(real) )
+      },
+      _ => (),
+    }
+    if self.eat("(") {
+      let expr = try!( self.expr() ) ;
+      try!( self.expect(")") ) ;
+      Ok(expr)
+    } else if self.eat("true") {
+      Ok( Expr::Bool(true) )
+    } else if self.eat("false") {
+      Ok( Expr::Bool(false) )
+    } else if self.eat("if") {
+      let cnd = try!( self.expr() ) ;
+      try!( self.expect("then") ) ;
+      let thn = try!( self.expr() ) ;
+      try!( self.expect("else") ) ;
+      let els = try!( self.expr() ) ;
+      Ok( Expr::Ite( Box::new(cnd), Box::new(thn), Box::new(els), pos ) )
+    } else if self.is_id() {
+      let (id, _) = try!( self.id() ) ;
+      if self.eat("(") {
+        let mut args = vec![] ;
+        if ! self.eat(")") {
+          loop {
+            args.push( try!( self.expr() ) ) ;
+            if ! self.eat(",") { break }
+          }
+          try!( self.expect(")") )
+        }
+        Ok( Expr::Call(id, args, pos) )
+      } else {
+        Ok( Expr::Id(id, pos) )
+      }
+    } else {
+      Err( (pos, "expected expression".into()) )
+    }
+  }
+}
+
+/// Translated expressions.
+#[derive(Debug, Clone)]
+enum Term {
+  /// Constant, in SMT-LIB.
+  Cst(String),
+  /// State variable.
+  Svar(String),
+  /// Application of an SMT-LIB operator.
+  App(& 'static str, Vec<Term>),
+}
+impl Term {
+  /// Writes a term, its state variables being in state `st`.
+  fn write(& self, st: & str, s: & mut String) {
+    match * self {
+      Term::Cst(ref cst) => s.push_str(cst),
+      Term::Svar(ref svar) => {
+        s.push_str("(_ ") ;
+        s.push_str(st) ;
+        s.push(' ') ;
+        s.push_str( & smt_sym(svar) ) ;
+        s.push(')')
+      },
+      Term::App(op, ref kids) => {
+        s.push('(') ;
+        s.push_str(op) ;
+        for kid in kids.iter() {
+          s.push(' ') ;
+          kid.write(st, s)
+        }
+        s.push(')')
+      },
+    }
+  }
+  /// String of a term, its state variables being in state `st`.
+  fn to_str(& self, st: & str) -> String {
+    let mut s = String::new() ;
+    self.write(st, & mut s) ;
+    s
+  }
+  /// Equality between a state variable and a term.
+  fn eq(svar: & str, term: Term) -> Term {
+    Term::App( "=", vec![ Term::Svar( svar.to_string() ), term ] )
+  }
+}
+
+/// What callers need to know about a translated node.
+struct Sig {
+  /// State variables and their sort, inputs first, then outputs.
+  svars: Vec<(String, Sort)>,
+  /// Number of inputs.
+  inputs: usize,
+  /// Number of outputs.
+  outputs: usize,
+}
+
+/// Translation context of a node.
+struct NodeCtx<'a> {
+  /// Signatures of the nodes translated so far.
+  sigs: & 'a HashMap<String, Sig>,
+  /// Top-level constants.
+  consts: & 'a HashMap<String, (Term, Sort)>,
+  /// Sorts of the streams of the node.
+  streams: HashMap<String, Sort>,
+  /// State variables of the node.
+  svars: Vec<(String, Sort)>,
+  /// True if `%init` was created.
+  init: bool,
+  /// State variables for the `pre` expressions and their argument.
+  pres: Vec<(String, Term)>,
+  /// Number of calls to each node so far.
+  calls: HashMap<String, usize>,
+  /// Compositions, the arguments are current state terms.
+  subs: Vec<(String, Vec<Term>)>,
+}
+impl<'a> NodeCtx<'a> {
+  /// Creates an empty context.
+  fn mk(
+    sigs: & 'a HashMap<String, Sig>, consts: & 'a HashMap<String, (Term, Sort)>
+  ) -> Self {
+    NodeCtx {
+      sigs: sigs, consts: consts, streams: HashMap::new(), svars: vec![],
+      init: false, pres: vec![], calls: HashMap::new(), subs: vec![],
+    }
+  }
+
+  /// Declares a stream.
+  fn declare(& mut self, decl: & Decl) -> LusRes<()> {
+    if self.streams.insert( decl.id.clone(), decl.sort ).is_some() {
+      return Err(
+        ( decl.pos, format!("stream `{}` is declared twice", decl.id) )
+      )
+    }
+    self.svars.push( (decl.id.clone(), decl.sort) ) ;
+    Ok(())
+  }
+
+  /// The `%init` state variable, created if needed.
+  fn init_svar(& mut self) -> Term {
+    if ! self.init {
+      self.init = true ;
+      self.svars.push( ("%init".to_string(), Sort::Bool) )
+    }
+    Term::Svar( "%init".to_string() )
+  }
+
+  /// Translates an expression of some sort.
+  fn tr_sort(& mut self, expr: & Expr, sort: Sort, pos: usize) -> LusRes<Term> {
+    let (term, s) = try!( self.tr(expr) ) ;
+    if s == sort { Ok(term) } else {
+      Err( (pos, format!("expected expression of type {}, got {}", sort, s)) )
+    }
+  }
+
+  /// Translates an expression.
+  fn tr(& mut self, expr: & Expr) -> LusRes<(Term, Sort)> {
+    match * expr {
+      Expr::Bool(b) => Ok( ( Term::Cst( format!("{}", b) ), Sort::Bool ) ),
+      Expr::Int(ref int) => Ok( ( Term::Cst( int.clone() ), Sort::Int ) ),
+      Expr::Real(ref real) => Ok( ( Term::Cst( real.clone() ), Sort::Real ) ),
+      Expr::Id(ref id, pos) => {
+        if let Some(sort) = self.streams.get(id) {
+          return Ok( ( Term::Svar( id.clone() ), * sort ) )
+        }
+        match self.consts.get(id) {
+          Some( & (ref term, sort) ) => Ok( (term.clone(), sort) ),
+          None => Err( (pos, format!("unknown identifier `{}`", id)) ),
+        }
+      },
+      Expr::Not(ref e, pos) => {
+        let term = try!( self.tr_sort(e, Sort::Bool, pos) ) ;
+        Ok( ( Term::App("not", vec![term]), Sort::Bool ) )
+      },
+      Expr::Neg(ref e, pos) => {
+        let (term, sort) = try!( self.tr(e) ) ;
+        if sort == Sort::Bool {
+          return Err( (pos, "unary minus applied to a bool".into()) )
+        }
+        Ok( ( Term::App("-", vec![term]), sort ) )
+      },
+      Expr::Pre(ref e) => {
+        let (term, sort) = try!( self.tr(e) ) ;
+        let svar = format!("%pre.{}", self.pres.len() + 1) ;
+        self.svars.push( (svar.clone(), sort) ) ;
+        self.pres.push( (svar.clone(), term) ) ;
+        Ok( (Term::Svar(svar), sort) )
+      },
+      Expr::Arrow(ref lft, ref rgt, pos) => {
+        let (lft, sort) = try!( self.tr(lft) ) ;
+        let rgt = try!( self.tr_sort(rgt, sort, pos) ) ;
+        let init = self.init_svar() ;
+        Ok( ( Term::App("ite", vec![init, lft, rgt]), sort ) )
+      },
+      Expr::Ite(ref cnd, ref thn, ref els, pos) => {
+        let cnd = try!( self.tr_sort(cnd, Sort::Bool, pos) ) ;
+        let (thn, sort) = try!( self.tr(thn) ) ;
+        let els = try!( self.tr_sort(els, sort, pos) ) ;
+        Ok( ( Term::App("ite", vec![cnd, thn, els]), sort ) )
+      },
+      Expr::Bin(op, ref lft, ref rgt, pos) => {
+        let (lft, sort) = try!( self.tr(lft) ) ;
+        let (rgt, rgt_sort) = try!( self.tr(rgt) ) ;
+        let (smt, legal, res) = match op {
+          "and" | "or" | "xor" | "=>" => (op, sort == Sort::Bool, Sort::Bool),
+          "=" => ("=", true, Sort::Bool),
+          "<>" => ("distinct", true, Sort::Bool),
+          "<" | "<=" | ">" | ">=" => (op, sort != Sort::Bool, Sort::Bool),
+          "+" | "-" | "*" => (op, sort != Sort::Bool, sort),
+          "/" => (
+            if sort == Sort::Int { "div" } else { "/" },
+            sort != Sort::Bool, sort
+          ),
+          "div" | "mod" => (op, sort == Sort::Int, sort),
+          _ => unreachable!(),
+        } ;
+        if ! legal {
+          return Err(
+            (pos, format!("illegal operand type {} for `{}`", sort, op))
+          )
+        }
+        if rgt_sort != sort {
+          return Err(
+            (
+              pos, format!(
+                "operands of `{}` have different types {} and {}",
+                op, sort, rgt_sort
+              )
+            )
+          )
+        }
+        Ok( ( Term::App(smt, vec![lft, rgt]), res ) )
+      },
+      Expr::Call(ref node, ref args, pos) => {
+        let mut outs = try!( self.tr_call(node, args, pos) ) ;
+        if outs.len() != 1 {
+          return Err(
+            (
+              pos, format!(
+                "node `{}` has {} outputs, expected one", node, outs.len()
+              )
+            )
+          )
+        }
+        Ok( outs.pop().unwrap() )
+      },
+    }
+  }
+
+  /// Translates a node call, returns its outputs.
+  fn tr_call(
+    & mut self, node: & str, args: & [Expr], pos: usize
+  ) -> LusRes< Vec<(Term, Sort)> > {
+    let sigs = self.sigs ;
+    let sig = match sigs.get(node) {
+      Some(sig) => sig,
+      None => return Err( (pos, format!("unknown node `{}`", node)) ),
+    } ;
+    if args.len() != sig.inputs {
+      return Err(
+        (
+          pos, format!(
+            "node `{}` has {} inputs but is given {} arguments",
+            node, sig.inputs, args.len()
+          )
+        )
+      )
+    }
+
+    let mut params = Vec::with_capacity( sig.svars.len() ) ;
+    for (arg, & (_, sort)) in args.iter().zip( sig.svars.iter() ) {
+      params.push( try!( self.tr_sort(arg, sort, pos) ) )
+    }
+
+    let cnt = {
+      let cnt = self.calls.entry( node.to_string() ).or_insert(0) ;
+      * cnt += 1 ;
+      * cnt
+    } ;
+    let mut outs = Vec::with_capacity(sig.outputs) ;
+    for (idx, & (ref svar, sort)) in sig.svars.iter().enumerate().skip(
+      sig.inputs
+    ) {
+      let nu = format!("%{}.{}.{}", node, cnt, svar) ;
+      self.svars.push( (nu.clone(), sort) ) ;
+      if idx < sig.inputs + sig.outputs {
+        outs.push( ( Term::Svar( nu.clone() ), sort ) )
+      }
+      params.push( Term::Svar(nu) )
+    }
+    self.subs.push( (node.to_string(), params) ) ;
+
+    Ok(outs)
+  }
+}
+
+/// Conjunction of some terms, written with a base indentation.
+fn conj(terms: Vec<String>, indent: & str) -> String {
+  match terms.len() {
+    0 => format!("{}true", indent),
+    1 => format!("{}{}", indent, terms[0]),
+    _ => {
+      let mut s = format!("{}(and", indent) ;
+      for term in terms.iter() {
+        s = format!("{}\n{}  {}", s, indent, term)
+      }
+      format!("{}\n{})", s, indent)
+    },
+  }
+}
+
+/// Translates a node to a system definition, returns its signature and its
+/// properties if `main`.
+fn node_to_vmt(
+  node: & Node, main: bool,
+  sigs: & HashMap<String, Sig>, consts: & HashMap<String, (Term, Sort)>,
+  vmt: & mut String
+) -> LusRes< (Sig, Vec<(String, Term)>) > {
+  let mut ctx = NodeCtx::mk(sigs, consts) ;
+  for decl in node.inputs.iter().chain(
+    node.outputs.iter()
+  ).chain( node.locals.iter() ) {
+    try!( ctx.declare(decl) )
+  }
+
+  // Streams to define.
+  let mut undefined: HashMap<String, usize> = node.outputs.iter().chain(
+    node.locals.iter()
+  ).map( |decl| (decl.id.clone(), decl.pos) ).collect() ;
+
+  let mut eqs = Vec::with_capacity( node.eqs.len() ) ;
+  for & (ref lhs, ref expr) in node.eqs.iter() {
+    let defs = if lhs.len() == 1 {
+      let sort = match ctx.streams.get(& lhs[0].0) {
+        Some(sort) => * sort,
+        None => return Err(
+          ( lhs[0].1, format!("unknown stream `{}`", lhs[0].0) )
+        ),
+      } ;
+      vec![ try!( ctx.tr_sort(expr, sort, lhs[0].1) ) ]
+    } else {
+      let outs = match * expr {
+        Expr::Call(ref callee, ref args, pos) => try!(
+          ctx.tr_call(callee, args, pos)
+        ),
+        _ => return Err(
+          ( lhs[0].1, "only node calls can define several streams".into() )
+        ),
+      } ;
+      if outs.len() != lhs.len() {
+        return Err(
+          (
+            lhs[0].1, format!(
+              "defining {} streams with {} outputs", lhs.len(), outs.len()
+            )
+          )
+        )
+      }
+      let mut defs = Vec::with_capacity( outs.len() ) ;
+      for (& (ref id, pos), (term, sort)) in lhs.iter().zip(
+        outs.into_iter()
+      ) {
+        match ctx.streams.get(id) {
+          Some(s) if * s == sort => (),
+          Some(s) => return Err(
+            (
+              pos, format!(
+                "stream `{}` has type {} but is defined with type {}",
+                id, s, sort
+              )
+            )
+          ),
+          None => return Err( (pos, format!("unknown stream `{}`", id)) ),
+        }
+        defs.push(term)
+      }
+      defs
+    } ;
+    for (& (ref id, pos), term) in lhs.iter().zip( defs.into_iter() ) {
+      if undefined.remove(id).is_none() {
+        return Err(
+          (
+            pos, format!(
+              "stream `{}` is an input or is defined more than once", id
+            )
+          )
+        )
+      }
+      eqs.push( (id.clone(), term) )
+    }
+  }
+  // Reporting the first undefined stream.
+  if let Some(decl) = node.outputs.iter().chain(
+    node.locals.iter()
+  ).find( |decl| undefined.contains_key(& decl.id) ) {
+    return Err( (decl.pos, format!("stream `{}` is never defined", decl.id)) )
+  }
+
+  let mut asserts = Vec::with_capacity( node.asserts.len() ) ;
+  for expr in node.asserts.iter() {
+    asserts.push( try!( ctx.tr_sort(expr, Sort::Bool, node.pos) ) )
+  }
+
+  let mut props = Vec::with_capacity( node.props.len() ) ;
+  if main {
+    for & (ref text, ref expr) in node.props.iter() {
+      props.push(
+        ( text.clone(), try!( ctx.tr_sort(expr, Sort::Bool, node.pos) ) )
+      )
+    }
+  }
+
+  // Init and trans.
+  let mut init = vec![] ;
+  let mut trans = vec![] ;
+  if ctx.init {
+    init.push( "(_ curr |%init|)".to_string() ) ;
+    trans.push( "(not (_ next |%init|))".to_string() )
+  }
+  for & (ref svar, ref term) in eqs.iter() {
+    let eq = Term::eq( svar, term.clone() ) ;
+    init.push( eq.to_str("curr") ) ;
+    trans.push( eq.to_str("next") )
+  }
+  for & (ref svar, ref term) in ctx.pres.iter() {
+    trans.push(
+      format!(
+        "(= (_ next {}) {})", smt_sym(svar), term.to_str("curr")
+      )
+    )
+  }
+  for decl in node.inputs.iter() {
+    if decl.cst {
+      trans.push(
+        format!(
+          "(= (_ next {0}) (_ curr {0}))", smt_sym(& decl.id)
+        )
+      )
+    }
+  }
+  for term in asserts.iter() {
+    init.push( term.to_str("curr") ) ;
+    trans.push( term.to_str("next") )
+  }
+
+  vmt.push_str( & format!("(define-sys {}\n  (\n", smt_sym(& node.name)) ) ;
+  for & (ref svar, sort) in ctx.svars.iter() {
+    vmt.push_str( & format!("    ({} {})\n", smt_sym(svar), sort.smt()) )
+  }
+  vmt.push_str( & format!("  )\n{}\n", conj(init, "  ")) ) ;
+  vmt.push_str( & format!("{}\n  (\n", conj(trans, "  ")) ) ;
+  for & (ref callee, ref params) in ctx.subs.iter() {
+    vmt.push_str( & format!("    ({}", smt_sym(callee)) ) ;
+    for param in params.iter() {
+      vmt.push_str( & format!(" {}", param.to_str("curr")) )
+    }
+    vmt.push_str(")\n")
+  }
+  vmt.push_str("  )\n)\n\n") ;
+
+  Ok(
+    (
+      Sig {
+        svars: ctx.svars,
+        inputs: node.inputs.len(),
+        outputs: node.outputs.len(),
+      },
+      props
+    )
+  )
+}
+
+/// Translates a program, errors are positions in the program.
+fn translate_internal(src: & str) -> LusRes<Translation> {
+  let mut parser = try!( Parser::mk(src) ) ;
+  let Program { consts: csts, nodes } = try!( parser.program() ) ;
+
+  let main = {
+    let mut mains = nodes.iter().filter( |node| node.main ) ;
+    match ( mains.next(), mains.next() ) {
+      ( Some(node), None ) => node.name.clone(),
+      ( Some(_), Some(node) ) => return Err(
+        ( node.pos, "several nodes are annotated with `--%MAIN`".into() )
+      ),
+      ( None, _ ) => match nodes.last() {
+        Some(node) => node.name.clone(),
+        None => return Err( (0, "no node to check".into()) ),
+      },
+    }
+  } ;
+
+  // Constants, no streams allowed.
+  let no_sigs = HashMap::new() ;
+  let mut consts = HashMap::new() ;
+  for (id, pos, sort, expr) in csts.into_iter() {
+    let res = {
+      let mut ctx = NodeCtx::mk(& no_sigs, & consts) ;
+      let res = try!( ctx.tr(& expr) ) ;
+      if ! ctx.svars.is_empty() {
+        return Err(
+          ( pos, format!("constant `{}` is not a constant expression", id) )
+        )
+      }
+      res
+    } ;
+    if let Some(sort) = sort {
+      if sort != res.1 {
+        return Err(
+          (
+            pos, format!(
+              "constant `{}` has type {} but is defined with type {}",
+              id, sort, res.1
+            )
+          )
+        )
+      }
+    }
+    if consts.insert( id.clone(), res ).is_some() {
+      return Err( (pos, format!("constant `{}` is defined twice", id)) )
+    }
+  }
+
+  let mut names = HashSet::with_capacity( nodes.len() ) ;
+  for node in nodes.iter() {
+    if ! names.insert( node.name.clone() ) {
+      return Err( (node.pos, format!("node `{}` is defined twice", node.name)) )
+    }
+  }
+
+  // Translating nodes after the nodes they call.
+  let mut vmt = String::new() ;
+  let mut sigs = HashMap::with_capacity( nodes.len() ) ;
+  let mut hidden = HashMap::with_capacity( nodes.len() ) ;
+  let mut props = vec![] ;
+  let mut todo: Vec<& Node> = nodes.iter().collect() ;
+  while ! todo.is_empty() {
+    let mut rest = Vec::with_capacity( todo.len() ) ;
+    for node in todo.iter() {
+      let mut callees = HashSet::new() ;
+      for & (_, ref expr) in node.eqs.iter() {
+        expr.calls(& mut callees)
+      }
+      for expr in node.asserts.iter().chain(
+        node.props.iter().map( |& (_, ref expr)| expr )
+      ) {
+        expr.calls(& mut callees)
+      }
+      // Unknown nodes are reported by the translation.
+      if callees.iter().any(
+        |callee| names.contains(callee) && ! sigs.contains_key(callee)
+      ) {
+        rest.push(* node) ;
+        continue
+      }
+      let is_main = node.name == main ;
+      let (sig, node_props) = try!(
+        node_to_vmt(node, is_main, & sigs, & consts, & mut vmt)
+      ) ;
+      hidden.insert(
+        node.name.clone(), sig.svars.iter().filter(
+          |& & (ref svar, _)| svar.starts_with('%')
+        ).map( |& (ref svar, _)| svar.clone() ).collect()
+      ) ;
+      sigs.insert( node.name.clone(), sig ) ;
+      if is_main { props = node_props }
+    }
+    if rest.len() == todo.len() {
+      return Err(
+        ( rest[0].pos, format!("node `{}` is recursive", rest[0].name) )
+      )
+    }
+    todo = rest
+  }
+
+  if props.is_empty() {
+    return Err( (0, format!("main node `{}` has no property", main)) )
+  }
+
+  // Properties are named after their text.
+  let mut prop_names = Vec::with_capacity( props.len() ) ;
+  for (text, term) in props.into_iter() {
+    let mut name = text.clone() ;
+    let mut cnt = 1 ;
+    while ! names.insert( name.clone() ) {
+      cnt += 1 ;
+      name = format!("{} ({})", text, cnt)
+    }
+    vmt.push_str(
+      & format!(
+        "(define-prop {} {}\n  {}\n)\n",
+        smt_sym(& name), smt_sym(& main), term.to_str("curr")
+      )
+    ) ;
+    prop_names.push( smt_sym(& name) )
+  }
+  vmt.push_str(
+    & format!(
+      "\n(verify {} ({}))\n", smt_sym(& main), prop_names.join(" ")
+    )
+  ) ;
+
+  Ok( Translation { vmt: vmt, hidden: hidden } )
+}
+
+/// Translates a Lustre program to VMT-LIB.
+pub fn translate(src: & str) -> Result<Translation, Error> {
+  translate_internal(src).map_err(
+    |(pos, blah)| Error::parse_mk( Line::of_pos(src, pos), blah, vec![] )
+  )
+}
+
+
+#[cfg(test)]
+mod test {
+  use super::translate ;
+
+  /// Translates a program, prints the error if any.
+  fn tr(src: & str) -> Result<String, String> {
+    translate(src).map( |t| t.vmt ).map_err(
+      |e| { e.print() ; format!("{}", e) }
+    )
+  }
+
+  #[test]
+  fn nodes() {
+    let vmt = tr("
+      node count(in: bool) returns (out: int) ;
+      let
+        out = (if in then 1 else 0) -> (if in then pre out + 1 else pre out) ;
+      tel
+
+      node top(in: bool) returns (cnt: int ; ok: bool) ;
+      let
+        cnt = count(in) ;
+        ok = 0 <= cnt ;
+        --%PROPERTY ok ;
+        -- %PROPERTY not ok ;
+        --%PROPERTY cnt <= 10 ;
+      tel
+    ").unwrap() ;
+    // Callee defined first.
+    assert!(
+      vmt.find("(define-sys count").unwrap() <
+      vmt.find("(define-sys top").unwrap()
+    ) ;
+    assert!( vmt.contains("(|%pre.1| Int)") ) ;
+    assert!( vmt.contains("(= (_ next |%pre.1|) (_ curr out))") ) ;
+    assert!( vmt.contains("(|%count.1.out| Int)") ) ;
+    assert!(
+      vmt.contains(
+        "(count (_ curr in) (_ curr |%count.1.out|) \
+        (_ curr |%count.1.%pre.1|) (_ curr |%count.1.%pre.2|) \
+        (_ curr |%count.1.%init|))"
+      )
+    ) ;
+    assert!( vmt.contains("(verify top (ok |cnt <= 10|))") )
+  }
+
+  #[test]
+  fn errors() {
+    let err = tr("
+      node top(in: bool) returns (out: int) ;
+      let
+        out = in + 1 ;
+        --%PROPERTY out >= 0 ;
+      tel
+    ").unwrap_err() ;
+    assert!( err.contains("illegal operand type bool for `+`") ) ;
+    let err = tr("
+      node top(in: bool) returns (out: int) ;
+      let
+        out = top(in) ;
+        --%PROPERTY out >= 0 ;
+      tel
+    ").unwrap_err() ;
+    assert!( err.contains("node `top` is recursive") ) ;
+    let err = tr("
+      node top(in: bool) returns (out: int) ;
+      let
+        --%PROPERTY in ;
+      tel
+    ").unwrap_err() ;
+    assert!( err.contains("stream `out` is never defined") )
+  }
+}
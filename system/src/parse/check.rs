@@ -300,7 +300,7 @@ pub fn check_fun_dec(
   ctxt: & Context, sym: Spnd<Sym>, sig: Sig, typ: Spnd<Type>
 ) -> Result<Callable, InternalParseError> {
   new_check_sym!(ctxt, sym) ;
-  match ctxt.factory().set_fun_type(sym.get().clone(), * typ.get()) {
+  match ctxt.factory().set_fun_type(sym.get().clone(), typ.get().clone()) {
     Ok(()) => (),
     Err(e) => return Err(
       InternalParseError::mk(
@@ -311,6 +311,27 @@ pub fn check_fun_dec(
   Ok( Callable::Dec( Uf::mk(sym, sig, typ) ) )
 }
 
+/// Checks that an enumerated sort declaration is legal and declares it in
+/// the factory.
+pub fn check_enum(
+  ctxt: & Context, sym: Spnd<Sym>, cons: Vec<Spnd<Sym>>
+) -> Result<(), InternalParseError> {
+  let mut names = Vec::with_capacity( cons.len() ) ;
+  for con in cons.into_iter() {
+    new_check_sym!(ctxt, con) ;
+    names.push( con.val.get().sym().to_string() )
+  }
+  let name = sym.val.get().sym().to_string() ;
+  match ctxt.factory().declare_enum(name, names) {
+    Ok(_) => Ok(()),
+    Err(e) => Err(
+      InternalParseError::mk(
+        sym.span, format!("{}", e), vec![]
+      )
+    ),
+  }
+}
+
 /// Checks that a function definition is legal.
 pub fn check_fun_def(
   ctxt: & Context, sym: Spnd<Sym>, args: Args,
@@ -925,6 +946,101 @@ pub fn check_sys(
   )
 }
 
+/// Checks that a term of a liveness property is legal: a `Bool` term over the
+/// current state of the system.
+fn check_live_term(
+  ctxt: & Context, sys: & ::Sys, span: & Spn, term: TermAndDep,
+  calls: & mut CallSet, desc: & 'static str
+) -> Result<Term, InternalParseError> {
+  use std::iter::FromIterator ;
+  use term::BindMaker ;
+
+  let maybe_ok = new_type_check!(
+    ctxt, term.term, Spnd::mk(
+      Type::Bool, span.clone()
+    ), state: sys.state().args(), span.clone(),
+    t => "{} of liveness property should have type Bool, got {}", desc, t
+  ) ;
+  let res = try!(
+    sys_try!(
+      check_term_and_dep(
+        ctxt, & term, sys.locals(), sys.state(), true, false, calls
+      ), ctxt, term.term, sys.sym().get().clone(), span.clone(),
+      format!("in {} of liveness property", desc)
+    )
+  ) ;
+  try!{ maybe_ok }
+  Ok(res)
+}
+
+/// Checks that a liveness property definition is legal, and reduces it to a
+/// safety property of an augmented system.
+///
+/// Returns the property, over the augmented system, the state variables of
+/// the monitor and the one holding in the first state of the loop of the
+/// counterexamples.
+pub fn check_live(
+  ctxt: & Context, sym: Spnd<Sym>, spnd_sys: Spnd<Sym>,
+  request: TermAndDep, response: TermAndDep, justice: Vec<TermAndDep>
+) -> Result<(Prop, Vec<Sym>, Sym), InternalParseError> {
+  use std::sync::Arc ;
+  use term::SymMaker ;
+  new_check_sym!(ctxt, sym) ;
+
+  let live_sym = Spnd::mk(
+    ctxt.factory().sym( format!("live[{}]", sym.get().sym()) ),
+    sym.span.clone()
+  ) ;
+  new_check_sym!(ctxt, live_sym) ;
+
+  let sys = match ctxt.get_sys( & spnd_sys ) {
+    Some(s) => s.clone(),
+    None => return Err(
+      InternalParseError::mk(
+        spnd_sys.span, "unknown system".into(), vec![]
+      )
+    ),
+  } ;
+
+  let mut calls = CallSet::empty() ;
+  let request = try!(
+    check_live_term(
+      ctxt, & sys, & sym.span, request, & mut calls, "request"
+    )
+  ) ;
+  let response = try!(
+    check_live_term(
+      ctxt, & sys, & sym.span, response, & mut calls, "response"
+    )
+  ) ;
+  let mut nu_justice = Vec::with_capacity( justice.len() ) ;
+  for term in justice.into_iter() {
+    nu_justice.push(
+      try!(
+        check_live_term(
+          ctxt, & sys, & sym.span, term, & mut calls, "justice condition"
+        )
+      )
+    )
+  }
+
+  match ::live::reduce(
+    ctxt.factory(), & sym, & sys, request, response, nu_justice, & calls
+  ) {
+    Ok(reduction) => Ok(
+      (
+        Prop::mk(
+          sym, Arc::new(reduction.sys), reduction.body, calls
+        ),
+        reduction.monitor, reduction.save
+      )
+    ),
+    Err(blah) => Err(
+      InternalParseError::mk(sym.span, blah, vec![])
+    ),
+  }
+}
+
 /// Checks that a check is legal.
 pub fn check_check(
   ctxt: & Context, sym: Spnd<Sym>,
@@ -949,7 +1065,9 @@ pub fn check_check(
       ),
       Some(prop) => (* prop).0.clone(),
     } ;
-    if sys.sym() != prop.sys().sym() {
+    // Liveness properties are over the augmented system built for them.
+    let live_sys = ctxt.live_sys( prop.sys().sym().get() ) ;
+    if sys.sym() != prop.sys().sym() && live_sys != Some( sys.sym().get() ) {
       return Err( IncProp(prop.clone(), sys.clone(), desc) )
     } else {
       real_props.push(prop)
@@ -963,6 +1081,27 @@ pub fn check_check(
     Some(atoms) => {
       let mut nu_atoms = Vec::with_capacity(atoms.len()) ;
       for atom in atoms.into_iter() {
+        // Atoms naming a state variable of the system are assumed in every
+        // state.
+        let svar_typ = sys.state().args().iter().find(
+          |& & (ref svar, _)| svar.get() == atom.sym().get()
+        ).map( |& (_, ref typ)| typ.get().clone() ) ;
+        match svar_typ {
+          Some(Type::Bool) => {
+            nu_atoms.push( atom.into_svar(ctxt.factory()) ) ;
+            continue
+          },
+          Some(typ) => return Err(
+            TypeCheck(
+              format!(
+                "state variable {} of system {} has type {} in {}, \
+                expected Bool",
+                atom.sym().get(), sym.get(), typ, desc
+              )
+            )
+          ),
+          None => (),
+        }
         match var_defined(ctxt, atom.sym()) {
           None => return Err(
             UkAtom(atom.sym().get().clone(), sym.get().clone(), desc)
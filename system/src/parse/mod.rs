@@ -40,6 +40,8 @@ use base::* ;
 mod parsers ;
 pub use self::parsers::InternalParseError ;
 pub mod check ;
+mod replay ;
+pub use self::replay::Inconsistency ;
 
 use self::parsers::* ;
 
@@ -119,6 +121,22 @@ impl Atom {
       },
     }
   }
+  /// Same as `into_var` for an atom naming a state variable, in the current
+  /// state.
+  #[inline]
+  pub fn into_svar(self, f: & Factory) -> Term {
+    use term::{ VarMaker, State } ;
+    match self {
+      Atom::Pos(sym) => {
+        let (sym, _) = sym.destroy() ;
+        f.svar(sym, State::Curr)
+      },
+      Atom::Neg(sym) => {
+        let (sym, _) = sym.destroy() ;
+        f.not( f.svar(sym, State::Curr) )
+      },
+    }
+  }
 }
 
 /// Normal result of a parsing attempt.
@@ -176,7 +194,12 @@ impl Res {
 pub struct Cex {
   sys: ::Sys,
   no_state: HashMap<Sym, Cst>,
-  trace: HashMap<Offset, HashMap<Sym, Cst>>
+  trace: HashMap<Offset, HashMap<Sym, Cst>>,
+  /// State variables not shown, *e.g.* the internal ones of a Lustre node.
+  hidden: HashSet<Sym>,
+  /// First state of the loop for the counterexamples of liveness properties.
+  /// The last state of the trace is equal to this one.
+  lasso: Option<usize>,
 }
 impl Cex {
   /// Length of a cex. Number of states minus one.
@@ -184,6 +207,21 @@ impl Cex {
     assert!(self.trace.len() > 0) ;
     self.trace.len() - 1
   }
+  /// First state of the loop of a lasso-shaped counterexample, for liveness
+  /// properties. Repeating the states from this one to the last one, which
+  /// is equal to it, gives an infinite path falsifying the property.
+  pub fn loop_start(& self) -> Option<usize> { self.lasso }
+  /// Values of the state variables in the `index`-th state of the trace,
+  /// hidden ones excluded. `None` if the trace is shorter.
+  pub fn state(& self, index: usize) -> Option<HashMap<Sym, Cst>> {
+    self.trace.get( & Offset::of_int(index) ).map(
+      |state| state.iter().filter(
+        |& (sym, _)| ! self.hidden.contains(sym)
+      ).map(
+        |(sym, cst)| ( sym.clone(), cst.clone() )
+      ).collect()
+    )
+  }
   /// Formats a counterexample vmt-style.
   pub fn write_vmt<W: io::Write>(
     & self, props: & [ Sym ], fmt: & mut W
@@ -214,14 +252,208 @@ impl Cex {
     while let Some( ref cex ) = self.trace.get(& off) {
       try!( write!(fmt, "  ; state {}:\n  (and\n", off) ) ;
       for (ref sym, ref cst) in cex.iter() {
+        if self.hidden.contains(* sym) { continue }
         try!( write!(fmt, "    (= {} {})\n", sym, cst) )
       }
       try!( write!(fmt, "  )\n") ) ;
       off = off.nxt()
     }
+    if let Some(start) = self.lasso {
+      try!( write!(fmt, "  ; loops back to state {}\n", start) )
+    }
 
     write!(fmt, ")\n")
   }
+  /// Formats a counterexample as a JSON object.
+  ///
+  /// The object has a `length` field, the values of the function symbols in
+  /// `functions`, and the states of the trace in `trace`. Values are
+  /// SMT-LIB constants written as strings. Symbols are sorted so that the
+  /// output is stable. Lasso-shaped counterexamples also have a `loop` field,
+  /// the index of the first state of the loop.
+  pub fn write_json<W: io::Write>(& self, fmt: & mut W) -> io::Result<()> {
+    use json_str ;
+    fn write_map<Writer: io::Write>(
+      map: & HashMap<Sym, Cst>, hidden: & HashSet<Sym>, fmt: & mut Writer
+    ) -> io::Result<()> {
+      let mut vals: Vec<(String, String)> = map.iter().filter(
+        |& (sym, _)| ! hidden.contains(sym)
+      ).map(
+        |(sym, cst)| ( format!("{}", sym), format!("{}", cst) )
+      ).collect() ;
+      vals.sort() ;
+      try!( write!(fmt, "{{") ) ;
+      let mut fst = true ;
+      for (sym, cst) in vals.into_iter() {
+        try!(
+          write!(
+            fmt, "{}{}: {}",
+            if fst { "" } else { ", " }, json_str(& sym), json_str(& cst)
+          )
+        ) ;
+        fst = false
+      }
+      write!(fmt, "}}")
+    }
+
+    try!( write!(fmt, "{{\"length\": {}, \"functions\": ", self.len()) ) ;
+    try!( write_map(& self.no_state, & HashSet::new(), fmt) ) ;
+    try!( write!(fmt, ", \"trace\": [") ) ;
+    let mut off = Offset::zero() ;
+    while let Some( ref state ) = self.trace.get(& off) {
+      if off != Offset::zero() {
+        try!( write!(fmt, ", ") )
+      }
+      try!( write_map(state, & self.hidden, fmt) ) ;
+      off = off.nxt()
+    }
+    try!( write!(fmt, "]") ) ;
+    if let Some(start) = self.lasso {
+      try!( write!(fmt, ", \"loop\": {}", start) )
+    }
+    write!(fmt, "}}")
+  }
+  /// Formats a counterexample as a VCD (Value Change Dump) waveform.
+  ///
+  /// State variables are declared in a scope named after the system, Bool
+  /// ones as 1-bit wires, bit-vectors as wires of the same width and
+  /// arithmetic ones as real signals. Enumerated ones are real signals giving
+  /// the index of the constructor in the declaration of the sort. Function
+  /// symbols are constants declared in scope `functions` and only dumped at
+  /// time `0`. State `k` of the trace is dumped at time `k`, with only the
+  /// values that changed. Arrays have no VCD representation and are skipped.
+  pub fn write_vcd<W: io::Write>(& self, fmt: & mut W) -> io::Result<()> {
+    use term::real_term ;
+
+    /// VCD identifier code of the `n`-th signal.
+    fn id_code(mut n: usize) -> String {
+      let mut s = String::new() ;
+      loop {
+        s.push( (33 + (n % 94)) as u8 as char ) ;
+        n = n / 94 ;
+        if n == 0 { return s } else { n = n - 1 }
+      }
+    }
+    /// VCD reference name of a symbol, no spaces allowed.
+    fn ref_name(sym: & Sym) -> String {
+      format!("{}", sym).chars().map(
+        |c| if c.is_whitespace() { '_' } else { c }
+      ).collect()
+    }
+    /// Declares a signal.
+    fn write_var<Writer: io::Write>(
+      fmt: & mut Writer, sym: & Sym, typ: & Type, id: & str
+    ) -> io::Result<()> {
+      match * typ {
+        Type::Bool => write!(
+          fmt, "    $var wire 1 {} {} $end\n", id, ref_name(sym)
+        ),
+        Type::BitVec(width) => write!(
+          fmt, "    $var wire {} {} {} $end\n", width, id, ref_name(sym)
+        ),
+        _ => write!(
+          fmt, "    $var real 64 {} {} $end\n", id, ref_name(sym)
+        ),
+      }
+    }
+    /// Dumps the value of a signal.
+    fn write_val<Writer: io::Write>(
+      fmt: & mut Writer, cst: & Cst, id: & str
+    ) -> io::Result<()> {
+      match * cst.get() {
+        real_term::Cst::Bool(b) => write!(
+          fmt, "{}{}\n", if b { 1 } else { 0 }, id
+        ),
+        real_term::Cst::BitVec(_, ref val) => write!(
+          fmt, "b{} {}\n", val.to_str_radix(2), id
+        ),
+        real_term::Cst::Enum(_, idx) => write!(fmt, "r{} {}\n", idx, id),
+        _ => match cst.to_f64() {
+          Some(f) => write!(fmt, "r{} {}\n", f, id),
+          None => write!(fmt, "rnan {}\n", id),
+        },
+      }
+    }
+
+    /// False for the values that cannot be dumped, *i.e.* arrays.
+    fn dumpable(cst: & Cst) -> bool {
+      match * cst.get() {
+        real_term::Cst::Array(_, _, _) => false,
+        _ => true,
+      }
+    }
+
+    // Sorting symbols so that the output is stable.
+    let mut functions: Vec<(String, & Sym, & Cst)> = self.no_state.iter(
+    ).filter( |& (_, cst)| dumpable(cst) ).map(
+      |(sym, cst)| ( format!("{}", sym), sym, cst )
+    ).collect() ;
+    functions.sort_by( |lft, rgt| lft.0.cmp(& rgt.0) ) ;
+    let mut state: Vec<(String, & Sym, Type)> = match self.trace.get(
+      & Offset::zero()
+    ) {
+      Some(state) => state.iter().filter(
+        |& (sym, cst)| dumpable(cst) && ! self.hidden.contains(sym)
+      ).map(
+        |(sym, cst)| ( format!("{}", sym), sym, cst.typ() )
+      ).collect(),
+      None => vec![],
+    } ;
+    state.sort_by( |lft, rgt| lft.0.cmp(& rgt.0) ) ;
+
+    // Header.
+    try!( write!(fmt, "$version kino $end\n$timescale 1s $end\n") ) ;
+    let mut ids = HashMap::with_capacity( state.len() ) ;
+    try!( write!(fmt, "$scope module {} $end\n", self.sys.sym()) ) ;
+    for & (_, sym, ref typ) in state.iter() {
+      let id = id_code( ids.len() ) ;
+      try!( write_var(fmt, sym, typ, & id) ) ;
+      ids.insert(sym, id) ; ()
+    }
+    let mut fun_ids = Vec::with_capacity( functions.len() ) ;
+    if ! functions.is_empty() {
+      try!( write!(fmt, "  $scope module functions $end\n") ) ;
+      for & (_, sym, cst) in functions.iter() {
+        let id = id_code( ids.len() + fun_ids.len() ) ;
+        try!( write_var(fmt, sym, & cst.typ(), & id) ) ;
+        fun_ids.push( (cst, id) )
+      }
+      try!( write!(fmt, "  $upscope $end\n") )
+    }
+    try!( write!(fmt, "$upscope $end\n$enddefinitions $end\n") ) ;
+
+    // Trace.
+    let mut prev: Option<& HashMap<Sym, Cst>> = None ;
+    let mut off = Offset::zero() ;
+    let mut time = 0 ;
+    while let Some( values ) = self.trace.get(& off) {
+      try!( write!(fmt, "#{}\n", time) ) ;
+      if prev.is_none() {
+        try!( write!(fmt, "$dumpvars\n") ) ;
+        for & (cst, ref id) in fun_ids.iter() {
+          try!( write_val(fmt, cst, id) )
+        }
+      }
+      for & (_, sym, _) in state.iter() {
+        if let Some(cst) = values.get(sym) {
+          let changed = match prev {
+            Some(prev) => prev.get(sym) != Some(cst),
+            None => true,
+          } ;
+          if changed {
+            try!( write_val(fmt, cst, & ids[sym]) )
+          }
+        }
+      }
+      if prev.is_none() {
+        try!( write!(fmt, "$end\n") )
+      }
+      prev = Some(values) ;
+      off = off.nxt() ;
+      time = time + 1
+    }
+    write!(fmt, "#{}\n", time)
+  }
   /// Prints a counterexample vmt-style.
   pub fn print_vmt(
     & self, props: & [ Sym ]
@@ -250,11 +482,15 @@ impl Cex {
     while let Some( ref cex ) = self.trace.get(& off) {
       print!("  ; state {}:\n  (and\n", off) ;
       for (ref sym, ref cst) in cex.iter() {
+        if self.hidden.contains(* sym) { continue }
         print!("    (= {} {})\n", sym, cst)
       }
       print!("  )\n") ;
       off = off.nxt()
     }
+    if let Some(start) = self.lasso {
+      print!("  ; loops back to state {}\n", start)
+    }
 
     print!(")\n")
   }
@@ -265,8 +501,10 @@ impl Cex {
     // necessary for each symbol. We also compute the maximum length of the
     // offsets as a string.
     let mut cst_lens = HashMap::new() ;
-    let args = self.sys.state().args() ;
-    for & (ref sym, _) in args {
+    let args: Vec<_> = self.sys.state().args().iter().filter(
+      |& & (ref sym, _)| ! self.hidden.contains( sym.get() )
+    ).collect() ;
+    for & & (ref sym, _) in args.iter() {
       cst_lens.insert(
         sym.get().clone(), format!("{}", sym).len()
       ) ;
@@ -279,6 +517,7 @@ impl Cex {
         format!("{}",off).len(), offset_len
       ) ;
       for (ref sym, ref cst) in map.iter() {
+        if self.hidden.contains(* sym) { continue }
         let len = format!("{}", cst).len() ;
         let len = match cst_lens.get(sym) {
           None => unreachable!(),
@@ -316,7 +555,7 @@ impl Cex {
     for _ in 0..offset_len {
       sep.push('-')
     } ;
-    for & (ref sym, _) in args {
+    for & & (ref sym, _) in args.iter() {
       s = format!("{} | ", s) ;
       sep = format!("{}-|-", sep) ;
       let fmt = format!("{}", sym) ;
@@ -343,7 +582,7 @@ impl Cex {
             } ;
           }
           s = format!("{}{}", s, fmt) ;
-          for & (ref sym, _) in args.iter() {
+          for & & (ref sym, _) in args.iter() {
             s = format!("{} | ", s) ;
             let width = cst_lens.get(sym).unwrap() ;
             let fmt = match map.get(sym) {
@@ -362,6 +601,9 @@ impl Cex {
       } ;
       offset = offset.nxt()
     } ;
+    if let Some(start) = self.lasso {
+      s = format!("{}\n  loops back to state {}", s, start)
+    }
     s
   }
 }
@@ -398,6 +640,13 @@ pub struct Context {
   syss: HashMap<Sym, ::Sys>,
   /// Maps system identifiers to their invariants.
   invs: HashMap<Sym, STermSet>,
+  /// Maps system identifiers to the state variables hidden in their
+  /// counterexamples.
+  hidden: HashMap<Sym, HashSet<Sym>>,
+  /// Maps the augmented systems of liveness properties to the system they
+  /// were built from, and to the state variable holding in the first state
+  /// of the loop of their counterexamples.
+  lives: HashMap<Sym, (Sym, Sym)>,
 }
 impl Context {
   /// Creates an empty context.
@@ -418,6 +667,8 @@ impl Context {
       // transs: HashMap::with_capacity(23),
       syss: HashMap::with_capacity(23),
       invs: HashMap::with_capacity(127),
+      hidden: HashMap::with_capacity(23),
+      lives: HashMap::with_capacity(7),
     }
   }
 
@@ -437,6 +688,14 @@ impl Context {
   pub fn get_prop(& self, sym: & Sym) -> Option<& (::Prop, PropStatus) > {
     self.props.get(sym)
   }
+  /// Properties declared over a system.
+  pub fn props_of(& self, sym: & Sym) -> Vec<::Prop> {
+    self.props.values().filter_map(
+      |& (ref prop, _)| if prop.sys().sym().get() == sym {
+        Some( prop.clone() )
+      } else { None }
+    ).collect()
+  }
   /// Updates the status of a property to invariant.
   pub fn set_prop_k_true(
     & mut self, sym: & Sym, k: usize
@@ -457,7 +716,8 @@ impl Context {
         PropStatus::KTrue(old_k) => if k <= old_k {
           return Ok(())
         },
-        PropStatus::Invariant(_) => return Ok(()),
+        PropStatus::Invariant(_) |
+        PropStatus::MinInvariant(_, _) => return Ok(()),
         _ => (),
       }
       * status = PropStatus::KTrue(k) ;
@@ -482,6 +742,7 @@ impl Context {
         PropStatus::Invariant(old_k) => if old_k <= k {
           return Ok(())
         },
+        PropStatus::MinInvariant(_, _) => return Ok(()),
         _ => (),
       }
       * status = PropStatus::Invariant(k) ;
@@ -492,13 +753,39 @@ impl Context {
       )
     }
   }
+  /// Updates the status of a proved property to minimized invariant, with
+  /// the lemmas it is `k`-inductive relative to.
+  pub fn set_prop_min_inv(
+    & mut self, sym: & Sym, k: usize, lemmas: STermSet
+  ) -> Result<(), String> {
+    if let Some( & mut (_, ref mut status) ) = self.props.get_mut(sym) {
+      match * status {
+        PropStatus::Invariant(_) |
+        PropStatus::MinInvariant(_, _) => (),
+        ref status => return Err(
+          format!(
+            "[Context::set_prop_min_inv] cannot set property minimized \
+            invariant at {}\nproperty's status is {}",
+            k, status
+          )
+        ),
+      }
+      * status = PropStatus::MinInvariant(k, lemmas) ;
+      Ok(())
+    } else {
+      Err(
+        format!("[Context::set_prop_min_inv] unknown property {}", sym)
+      )
+    }
+  }
   /// Updates the status of a property to falsified.
   pub fn set_prop_false(
     & mut self, sym: & Sym, cex: Cex
   ) -> Result<(), String> {
     if let Some( & mut (_, ref mut status) ) = self.props.get_mut(sym) {
       match * status {
-        PropStatus::Invariant(k) => return Err(
+        PropStatus::Invariant(k) |
+        PropStatus::MinInvariant(k, _) => return Err(
           format!(
             "[Context::set_prop_false] cannot set property false at {}\n\
             property's status is proved at {}",
@@ -586,6 +873,19 @@ impl Context {
     self.syss.get(sym)
   }
 
+  /// System an augmented system was built from, if it is the one of a
+  /// liveness property.
+  #[inline]
+  pub fn live_sys(& self, sym: & Sym) -> Option<& Sym> {
+    self.lives.get(sym).map( |& (ref sys, _)| sys )
+  }
+
+  /// Invariants known for a system.
+  #[inline]
+  pub fn get_invs(& self, sym: & Sym) -> Option<& STermSet> {
+    self.invs.get(sym)
+  }
+
   /// Add invariants for a system.
   #[inline]
   pub fn add_invs(
@@ -779,6 +1079,95 @@ impl Context {
     }
   }
 
+  /// Reads a script in the annotation-based VMT-LIB format, where plain
+  /// SMT-LIB definitions are annotated with `:next`, `:init`, `:trans` and
+  /// `:invar-property`. The script is translated to a system `vmt`, and the
+  /// result is the check of its properties `prop_<n>`.
+  pub fn read_vmtlib(
+    & mut self, reader: & mut io::Read
+  ) -> Result<Res, ExtError> {
+    let mut src = String::new() ;
+    try!( reader.read_to_string(& mut src).map_err(ExtError::Io) ) ;
+    let vmtlib = try!( ::vmtlib::translate(& src) ) ;
+    self.read_translation(vmtlib)
+  }
+
+  /// Reads a Lustre program. Each node is translated to a system, and the
+  /// result is the check of the properties of the main node: the one
+  /// annotated with `--%MAIN`, or the last one otherwise.
+  ///
+  /// The state variables introduced by the translation are hidden in the
+  /// counterexamples, which thus only mention Lustre streams.
+  pub fn read_lustre(
+    & mut self, reader: & mut io::Read
+  ) -> Result<Res, ExtError> {
+    let mut src = String::new() ;
+    try!( reader.read_to_string(& mut src).map_err(ExtError::Io) ) ;
+    let lustre = try!( ::lustre::translate(& src) ) ;
+    self.read_translation(lustre)
+  }
+
+  /// Reads a BTOR2 model. The model is built as a system `btor`, and the
+  /// result is the check of its `bad` properties.
+  ///
+  /// The operator nodes shared by several nodes are state variables of the
+  /// system, hidden in the counterexamples.
+  pub fn read_btor(
+    & mut self, reader: & mut io::Read
+  ) -> Result<Res, ExtError> {
+    let mut src = String::new() ;
+    try!( reader.read_to_string(& mut src).map_err(ExtError::Io) ) ;
+    let model = try!( ::btor::translate(self, & src) ) ;
+    let sym = model.sys.sym().get().clone() ;
+    self.internal_add_sys(model.sys) ;
+    let sys = self.syss[& sym].clone() ;
+    let mut props = Vec::with_capacity( model.props.len() ) ;
+    for (prop_sym, body) in model.props.into_iter() {
+      let prop = Prop::mk(
+        Spnd::mk( prop_sym.clone(), sys.sym().span.clone() ), sys.clone(),
+        body, CallSet::empty()
+      ) ;
+      self.internal_add_prop(prop, PropStatus::Unknown) ;
+      props.push( self.props[& prop_sym].0.clone() )
+    }
+    self.hidden.insert( sym, model.hidden.into_iter().collect() ) ;
+    Ok( Res::Check(sys, props) )
+  }
+
+  /// Reads an AIGER graph, ASCII or binary. The graph is translated to a
+  /// system `aiger` over Booleans, and the result is the check of its bad
+  /// states, or of its outputs if it has no bad state section.
+  ///
+  /// The and gates shared by several literals are state variables of the
+  /// system, hidden in the counterexamples.
+  pub fn read_aiger(
+    & mut self, reader: & mut io::Read
+  ) -> Result<Res, ExtError> {
+    let mut src = vec![] ;
+    try!( reader.read_to_end(& mut src).map_err(ExtError::Io) ) ;
+    let aiger = try!( ::aiger::translate(& src) ) ;
+    self.read_translation(aiger)
+  }
+
+  /// Reads the result of a front-end translation, and registers its hidden
+  /// state variables.
+  fn read_translation(
+    & mut self, translation: ::translation::Translation
+  ) -> Result<Res, ExtError> {
+    use term::SymMaker ;
+    let res = try!(
+      self.read( & mut translation.vmt.as_bytes() )
+    ) ;
+    for (sys, svars) in translation.hidden.into_iter() {
+      let svars = svars.into_iter().map(
+        |svar| self.factory.sym(svar)
+      ).collect() ;
+      let sys = self.factory.sym(sys) ;
+      self.hidden.insert(sys, svars) ; ()
+    }
+    Ok(res)
+  }
+
   /// Returns a counterexample for a system from a model.
   ///
   /// Assumes the offset **does not have reverse semantics**. That is, the
@@ -823,7 +1212,32 @@ impl Context {
       }
     }
 
-    Cex { sys: sys.clone(), no_state: no_state, trace: trace }
+    let hidden = self.hidden.get( sys.sym().get() ).cloned().unwrap_or_else(
+      HashSet::new
+    ) ;
+
+    // The loop of a liveness counterexample starts at the first state where
+    // the monitor saves the state.
+    let lasso = self.lives.get( sys.sym().get() ).and_then(
+      |& (_, ref save)| {
+        use term::real_term ;
+        let mut off = Offset::zero() ;
+        while let Some(state) = trace.get(& off) {
+          if let Some(cst) = state.get(save) {
+            if let real_term::Cst::Bool(true) = * cst.get() {
+              return Some( off.to_usize() )
+            }
+          }
+          off = off.nxt()
+        }
+        None
+      }
+    ) ;
+
+    Cex {
+      sys: sys.clone(), no_state: no_state, trace: trace, hidden: hidden,
+      lasso: lasso,
+    }
   }
 
 
@@ -893,6 +1307,13 @@ impl Context {
     // }
   }
 
+  /// Adds an enumerated sort declaration to the context.
+  pub fn add_enum(
+    & mut self, sym: Spnd<Sym>, cons: Vec<Spnd<Sym>>
+  ) -> Result<(), InternalParseError> {
+    check::check_enum(self, sym, cons)
+  }
+
   /// Adds a function definition to the context.
   pub fn add_fun_def(
     & mut self, sym: Spnd<Sym>, args: Args, typ: Spnd<Type>, body: TermAndDep
@@ -920,6 +1341,26 @@ impl Context {
     )
   }
 
+  /// Adds a liveness property definition to the context. The augmented
+  /// system the property is reduced to is added too.
+  pub fn add_live(
+    & mut self, sym: Spnd<Sym>, sys: Spnd<Sym>,
+    request: TermAndDep, response: TermAndDep, justice: Vec<TermAndDep>
+  ) -> Result<(), InternalParseError> {
+    let sys_sym = sys.get().clone() ;
+    check::check_live(self, sym, sys, request, response, justice).map(
+      |(prop, monitor, save)| {
+        let live_sym = prop.sys().sym().get().clone() ;
+        self.internal_add_sys( (* * prop.sys()).clone() ) ;
+        self.hidden.insert(
+          live_sym.clone(), monitor.into_iter().collect()
+        ) ;
+        self.lives.insert( live_sym, (sys_sym, save) ) ;
+        self.internal_add_prop(prop, PropStatus::Unknown)
+      }
+    )
+  }
+
   /// Adds a system definition to the context.
   pub fn add_sys(
     & mut self, sym: Spnd<Sym>, state: Args,
@@ -238,12 +238,12 @@ macro_rules! parse_or_fail {
     )
   ) ;
   (
-    $bytes:expr, $submac:ident!($len:ident < type ($t_offset:expr))
+    $bytes:expr, $submac:ident!($len:ident < type ($t_offset:expr, $ctx:expr))
     ! at $offset:expr, $blah:expr
   ) => (
     parse_or_fail!(
       $bytes, $submac!(
-        $len < spn thru apply!(type_parser, $t_offset)
+        $len < spn thru apply!(type_parser, $t_offset, $ctx.factory())
       )! at $offset, with (span, desc) => (
         span, format!(
           "expected type {}, found {}", $blah, desc
@@ -302,7 +302,9 @@ macro_rules! parse_or_fail {
 }
 
 /// Parses a signature, does **not** parse leading/trailing spaces/comments.
-fn sig_parser(bytes: Bytes, offset: usize) -> IRes< Spnd<Sig> > {
+fn sig_parser<'a>(
+  bytes: Bytes<'a>, offset: usize, c: & mut Context
+) -> IRes<'a, Spnd<Sig>> {
   let mut len = 0 ;
   do_parse!(
     bytes,
@@ -314,7 +316,7 @@ fn sig_parser(bytes: Bytes, offset: usize) -> IRes< Spnd<Sig> > {
     args: many0!(
       terminated!(
         len_add!(
-          len < spn thru apply!(type_parser, offset + len)
+          len < spn thru apply!(type_parser, offset + len, c.factory())
         ),
         len_add!(len < opt spc cmt)
       )
@@ -352,7 +354,7 @@ fn args_parser<'a>(
           ) >>
           len_add!(len < opt spc cmt) >>
           typ: parse_or_fail!(
-            len_add!(len < type (offset + len))
+            len_add!(len < type (offset + len, c))
             ! at (offset + len), "in argument declaration"
           ) >>
           len_add!(len < opt spc cmt) >> 
@@ -397,13 +399,13 @@ fn fun_dec_parser<'a>(
         (s, d, vec)
       },
       len_add!(
-        len < spn apply!(sig_parser, offset + len)
+        len < spn apply!(sig_parser, offset + len, c)
       )
     ) >>
     len_add!(len < opt spc cmt) >>
     typ: parse_or_fail!(
       len_add!(
-        len < type (offset + len)
+        len < type (offset + len, c)
       )
       ! at (offset + len), "in `declare-fun`"
     ) >> (
@@ -416,6 +418,45 @@ fn fun_dec_parser<'a>(
   )
 }
 
+/// Parses an enumerated sort declaration.
+fn enum_parser<'a>(
+  bytes: & 'a [u8], offset: usize, c: & mut Context
+) -> IRes<'a, Spnd<Res>> {
+  let mut len = 0 ;
+  do_parse!(
+    bytes,
+    sym: parse_or_fail!(
+      len_set!( len < sym (offset + len, c) )
+      ! at (offset + len), "in `declare-enum`"
+    ) >>
+    len_add!(len < opt spc cmt) >>
+    parse_or_fail!(
+      len_add!(len < char '(')
+      ! at (offset + len), "opening constructor list"
+    ) >>
+    len_add!(len < opt spc cmt) >>
+    cons: many1!(
+      terminated!(
+        len_add!(
+          len < spn thru apply!(sym_parser, offset + len, c.factory())
+        ),
+        len_add!(len < opt spc cmt)
+      )
+    ) >>
+    parse_or_fail!(
+      len_add!(len < char ')')
+      ! at (offset + len), "closing constructor list, or a constructor"
+    ) >> ({
+      let sym_span = sym.span.clone() ;
+      try_parserr!(
+        _ = c.add_enum(sym, cons) => Spnd::len_mk(
+          Res::Success, offset, len
+        ), (sym_span, "in this `declare-enum`".into())
+      )
+    })
+  )
+}
+
 /// Parses a function definition.
 fn fun_def_parser<'a>(
   bytes: & 'a [u8], offset: usize, c: & mut Context
@@ -442,7 +483,7 @@ fn fun_def_parser<'a>(
     len_add!(len < opt spc cmt) >>
     typ: parse_or_fail!(
       len_add!(
-        len < type (offset + len)
+        len < type (offset + len, c)
       )
       ! at (offset + len), "in `define-fun`"
     ) >>
@@ -523,6 +564,67 @@ fn rel_parser<'a>(
   )
 }
 
+/// Parses a liveness property definition.
+fn live_parser<'a>(
+  bytes: & 'a [u8], offset: usize, c: & mut Context
+) -> IRes<'a, Spnd<Res>> {
+  let mut len = 0 ;
+  do_parse!(
+    bytes,
+    sym: parse_or_fail!(
+      len_add!( len < sym (offset + len, c) )
+      ! at (offset + len), "in `define-live`"
+    ) >>
+    len_add!(len < opt spc cmt) >>
+    sys: parse_or_fail!(
+      len_add!( len < sym (offset + len, c) )
+      ! at (offset + len), "for system name in `define-live`"
+    ) >>
+    len_add!(len < opt spc cmt) >>
+    request: parse_or_fail!(
+      len_add!( len < trm (offset + len, c) )
+      ! at sym.span.clone(), "parse error in request of `define-live`"
+    ) >>
+    len_add!(len < opt spc cmt) >>
+    response: parse_or_fail!(
+      len_add!( len < trm (offset + len, c) )
+      ! at sym.span.clone(), "parse error in response of `define-live`"
+    ) >>
+    len_add!(len < opt spc cmt) >>
+    justice: delimited!(
+      parse_or_fail!(
+        len_add!(len < char '(')
+        ! at (offset + len), "starting justice condition list"
+      ),
+      preceded!(
+        len_add!(len < opt spc cmt),
+        many0!(
+          terminated!(
+            len_add!(
+              len < trm apply!(term_parser, offset + len, c.factory())
+            ),
+            len_add!(len < opt spc cmt)
+          )
+        )
+      ),
+      parse_or_fail!(
+        len_add!(len < char ')')
+        ! at (offset + len),
+        "closing justice condition list, or another justice condition"
+      )
+    ) >> ({
+      let sym_span = sym.span.clone() ;
+      try_parserr!(
+        _ = c.add_live(
+          sym, sys, request, response, justice
+        ) => Spnd::len_mk(
+          Res::Success, offset, len
+        ), (sym_span, "in this `define-live`".into())
+      )
+    })
+  )
+}
+
 
 
 fn sys_call_parser<'a>(
@@ -761,7 +863,7 @@ fn verify_assuming_parser<'a>(
         )
       ),
       parse_or_fail!(
-        len_add!(len < char '(')
+        len_add!(len < char ')')
         ! at (offset + len), "closing property/relation list"
       )
     ) >>
@@ -783,7 +885,7 @@ fn verify_assuming_parser<'a>(
         )
       ),
       parse_or_fail!(
-        len_add!(len < char '(')
+        len_add!(len < char ')')
         ! at (offset + len), "closing atom list"
       )
     ) >> ({
@@ -854,6 +956,11 @@ pub fn item_parser<'a>(
               len_add!(len < opt spc cmt)
             ) >> apply!(fun_dec_parser, offset + len, ctx) |
 
+            terminated!(
+              len_add!(len < tag "declare-enum"),
+              len_add!(len < opt spc cmt)
+            ) >> apply!(enum_parser, offset + len, ctx) |
+
             terminated!(
               len_add!(len < tag "define-fun"),
               len_add!(len < opt spc cmt)
@@ -869,6 +976,11 @@ pub fn item_parser<'a>(
               len_add!(len < opt spc cmt)
             ) >> apply!(rel_parser, offset + len, ctx) |
 
+            terminated!(
+              len_add!(len < tag "define-live"),
+              len_add!(len < opt spc cmt)
+            ) >> apply!(live_parser, offset + len, ctx) |
+
             terminated!(
               len_add!(len < tag "define-sys"),
               len_add!(len < opt spc cmt)
@@ -963,8 +1075,9 @@ mod test {
   #[test]
   fn sig_parser() {
     use super::sig_parser ;
+    let mut ctx = get_context() ;
     let txt = "(Int Bool)" ;
-    let res = try_parse_command!(sig_parser, 7, txt).unwrap().1 ;
+    let res = try_parse_command!(sig_parser, 7, ctx, txt).unwrap().1 ;
     let (sig, spn) = res.destroy() ;
     assert_eq!{ spn, Spn::len_mk(7, 10) }
     let mut iter = sig.types().iter() ;
@@ -980,7 +1093,7 @@ mod test {
     }
 
     let txt = "(  Int   Bool )" ;
-    let res = try_parse_command!(sig_parser, 7, txt).unwrap().1 ;
+    let res = try_parse_command!(sig_parser, 7, ctx, txt).unwrap().1 ;
     let (sig, spn) = res.destroy() ;
     assert_eq!{ spn, Spn::len_mk(7, 15) }
     let mut iter = sig.types().iter() ;
@@ -996,7 +1109,7 @@ mod test {
     }
 
     let txt = "( Int Blah )" ;
-    match try_parse_command!(sig_parser, 7, txt) {
+    match try_parse_command!(sig_parser, 7, ctx, txt) {
       Err(e) => {
         assert!(e.notes.is_empty()) ;
         assert_eq!( e.span, Spn::len_mk(13, 4) ) ;
@@ -1009,7 +1122,7 @@ mod test {
     }
 
     let txt = "Blah )" ;
-    match try_parse_command!(sig_parser, 7, txt) {
+    match try_parse_command!(sig_parser, 7, ctx, txt) {
       Err(e) => {
         assert!(e.notes.is_empty()) ;
         assert_eq!( e.span, Spn::len_mk(7, 4) ) ;
@@ -1394,4 +1507,54 @@ mod test {
       Ok(res) => panic!("unexpected result: {:?}", res),
     }
   }
+
+  #[test]
+  fn live_parser() {
+    use super::item_parser ;
+
+    let mut ctx = get_context() ;
+
+    let txt = "\
+(define-sys prout
+  ;; State.
+  ( (x Int) )
+  ;; Init.
+  (>= (_curr x) 0)
+  ;; Trans.
+  (> (_ next x) (_ curr x))
+  ;; No calls.
+  ()
+)\
+    " ;
+    match try_parse_command!(item_parser, 7, ctx, txt) {
+      Err(e) => {
+        e.print() ;
+        panic!("unexpected result")
+      },
+      Ok(res) => assert_eq!( res.1.to_span(), Spn::len_mk(7, 135) ),
+    }
+
+    let txt = "(define-live blah prout (> (_ curr x) 0) (= (_ curr x) 0) ())" ;
+    match try_parse_command!(item_parser, 7, ctx, txt) {
+      Err(e) => {
+        e.print() ;
+        panic!("unexpected result")
+      },
+      Ok(res) => assert_eq!( res.1.to_span(), Spn::len_mk(7, 61) ),
+    }
+
+    let txt = "(define-live blah prout (> (_ curr x) 0))" ;
+    match try_parse_command!(item_parser, 7, ctx, txt) {
+      Err(e) => {
+        e.print() ;
+        assert_eq!( e.span, Spn::len_mk(20, 4) ) ;
+        assert_eq!(
+          e.blah,
+          "parse error in response of `define-live`"
+        ) ;
+        assert!(e.notes.is_empty())
+      },
+      Ok(res) => panic!("unexpected result: {:?}", res),
+    }
+  }
 }
\ No newline at end of file
@@ -0,0 +1,339 @@
+// Copyright 2015 Adrien Champion. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Replays counterexamples by concrete evaluation.
+//!
+//! Does not rely on the solver: the init and transition predicates of the
+//! system (sub-systems included) and the bodies of the properties are
+//! evaluated on the values of the trace. Counterexamples of sliced systems
+//! are lifted the same way.
+
+use std::fmt ;
+use std::collections::HashMap ;
+
+use term::{
+  Offset, Cst, Sym, Var, Term, Factory, VarMaker, State
+} ;
+use term::zip::{ Step, fold_info, extract } ;
+
+use base::Callable ;
+use super::Cex ;
+
+/// First inconsistency found when replaying a counterexample.
+#[derive(Debug, Clone)]
+pub enum Inconsistency {
+  /// The first state of the trace is not initial.
+  Init,
+  /// A state of the trace is not a successor of the previous one. The
+  /// offset is the one of the second state.
+  Trans(Offset),
+  /// A property holds in all the states of the trace.
+  Prop(Sym),
+  /// Evaluation failed at some state of the trace. The trace might still be
+  /// legal.
+  Eval(Offset, String),
+}
+impl fmt::Display for Inconsistency {
+  fn fmt(& self, fmt: & mut fmt::Formatter) -> fmt::Result {
+    match * self {
+      Inconsistency::Init => write!(fmt, "first state is not initial"),
+      Inconsistency::Trans(ref off) => write!(
+        fmt, "state {} is not a successor of the previous one", off
+      ),
+      Inconsistency::Prop(ref sym) => write!(
+        fmt, "property {} holds in all states of the trace", sym
+      ),
+      Inconsistency::Eval(ref off, ref blah) => write!(
+        fmt, "evaluation failed at state {}: {}", off, blah
+      ),
+    }
+  }
+}
+
+/// Evaluates terms in the context of a counterexample.
+struct Evaluator<'a> {
+  /// Factory, used to evaluate operators.
+  factory: & 'a Factory,
+  /// Functions that can appear in the terms.
+  funs: HashMap<Sym, ::Callable>,
+  /// Init and trans predicates of the system and its sub-systems.
+  preds: HashMap<Sym, (Vec<Var>, Term)>,
+  /// Values of the function symbols.
+  no_state: & 'a HashMap<Sym, Cst>,
+}
+impl<'a> Evaluator<'a> {
+  /// Creates an evaluator for a system, the values of the function symbols
+  /// of a counterexample, and some properties.
+  fn mk(
+    factory: & 'a Factory, sys: & ::Sys, no_state: & 'a HashMap<Sym, Cst>,
+    props: & [::Prop]
+  ) -> Self {
+    let mut funs = HashMap::new() ;
+    for fun in sys.calls().get() {
+      funs.insert( fun.sym().clone(), fun.clone() ) ; ()
+    }
+    for prop in props.iter() {
+      for fun in prop.calls().get() {
+        funs.insert( fun.sym().clone(), fun.clone() ) ; ()
+      }
+    }
+    let mut preds = HashMap::new() ;
+    let mut syss = vec![ sys.clone() ] ;
+    while let Some(sys) = syss.pop() {
+      for & (ref sub, _) in sys.subsys() {
+        syss.push( sub.clone() )
+      }
+      for & (ref sym, ref params, ref body, _) in vec![
+        sys.init(), sys.trans()
+      ].into_iter() {
+        preds.insert(
+          sym.clone(), (
+            params.iter().map( |& (ref var, _)| var.clone() ).collect(),
+            body.clone()
+          )
+        ) ; ()
+      }
+    }
+    Evaluator {
+      factory: factory, funs: funs, preds: preds, no_state: no_state
+    }
+  }
+
+  /// Evaluates a term given some values for its variables.
+  fn eval(
+    & self, term: & Term, env: & HashMap<Var, Cst>
+  ) -> Result<Cst, String> {
+    fold_info(
+      |step, bindings, _| self.eval_step(step, bindings, env), term
+    )
+  }
+
+  /// Evaluates an application.
+  fn eval_app(& self, sym: & Sym, args: Vec<Cst>) -> Result<Cst, String> {
+    if let Some( & (ref params, ref body) ) = self.preds.get(sym) {
+      let env = params.iter().cloned().zip( args.into_iter() ).collect() ;
+      return self.eval(body, & env)
+    }
+    let fun = match self.funs.get(sym) {
+      Some(fun) => fun,
+      None => return Err( format!("unknown function symbol {}", sym) ),
+    } ;
+    match * * fun {
+      Callable::Def(ref fun) => {
+        let mut env = HashMap::with_capacity( args.len() ) ;
+        let params = fun.args().iter().zip( args.into_iter() ) ;
+        for (& (ref sym, _), cst) in params {
+          let var: Var = self.factory.var( sym.get().clone() ) ;
+          env.insert(var, cst) ; ()
+        }
+        self.eval(fun.body(), & env)
+      },
+      Callable::Dec(_) => if args.is_empty() {
+        match self.no_state.get(sym) {
+          Some(cst) => Ok( cst.clone() ),
+          None => Err(
+            format!("no value for function symbol {}", sym)
+          ),
+        }
+      } else {
+        Err(
+          format!("cannot evaluate application of uninterpreted {}", sym)
+        )
+      },
+    }
+  }
+
+  /// Evaluates a step of the zipper.
+  fn eval_step(
+    & self, step: Step<Cst>, bindings: & [ HashMap<Sym, Cst> ],
+    env: & HashMap<Var, Cst>
+  ) -> Result<Cst, String> {
+    use term::zip::Step::* ;
+    match step {
+      App(sym, args) => self.eval_app(& sym, args),
+      Op(op, args) => op.eval(self.factory, args).map_err(
+        |e| format!("{}", e)
+      ),
+      Let(_, cst) => Ok(cst),
+      C(cst) => Ok(cst),
+      V(var) => {
+        if let Some(cst) = env.get(& var) {
+          return Ok( cst.clone() )
+        }
+        let sym = var.sym().clone() ;
+        if let Some(cst) = extract(& sym, bindings) {
+          return Ok( cst.clone() )
+        }
+        match * var.get() {
+          ::term::real_term::Var::Var(_) => self.eval_app(& sym, vec![]),
+          _ => Err( format!("no value for state variable {}", var) ),
+        }
+      },
+      _ => Err("cannot evaluate quantifiers".into()),
+    }
+  }
+}
+
+/// Adds the values of a state to an environment, as current or next state
+/// variables.
+fn env_of(
+  factory: & Factory, state: & HashMap<Sym, Cst>, st: State,
+  env: & mut HashMap<Var, Cst>
+) {
+  for (sym, cst) in state.iter() {
+    let var: Var = factory.svar(sym.clone(), st) ;
+    env.insert( var, cst.clone() ) ; ()
+  }
+}
+
+impl Cex {
+  /// Lifts a counterexample of a system sliced by
+  /// [`coi::slice`](../coi/fn.slice.html) to the original system `sys`.
+  ///
+  /// The state variables sliced away get the value given by their
+  /// definition, see [`coi::definitions`](../coi/fn.definitions.html), when
+  /// it can be evaluated, and the default value of their type otherwise.
+  /// The result might not be a trace of `sys`, it must be replayed.
+  pub fn lift(& self, factory: & Factory, sys: & ::Sys) -> Cex {
+    let (init_defs, trans_defs) = ::coi::definitions(sys) ;
+    let evaluator = Evaluator::mk(factory, sys, & self.no_state, & []) ;
+    let mut trace = self.trace.clone() ;
+
+    let zero = Offset::zero() ;
+    let mut off = zero ;
+    while trace.contains_key(& off) {
+      let defs = if off == zero { & init_defs } else { & trans_defs } ;
+      // Evaluating the definitions until none can be, they can depend on
+      // each other.
+      let mut progress = true ;
+      while progress {
+        progress = false ;
+        let mut env = HashMap::new() ;
+        if off == zero {
+          env_of(factory, & trace[& off], State::Curr, & mut env)
+        } else {
+          env_of(factory, & trace[& off.pre()], State::Curr, & mut env) ;
+          env_of(factory, & trace[& off], State::Next, & mut env)
+        }
+        for & (ref sym, ref term) in defs.iter() {
+          if trace[& off].contains_key(sym) { continue }
+          if let Ok(cst) = evaluator.eval(term, & env) {
+            if let Some(state) = trace.get_mut(& off) {
+              state.insert( sym.clone(), cst ) ; ()
+            }
+            progress = true
+          }
+        }
+      }
+      off = off.nxt()
+    }
+
+    for (_, state) in trace.iter_mut() {
+      for & (ref sym, ref typ) in sys.state().args() {
+        if ! state.contains_key( sym.get() ) {
+          state.insert(
+            sym.get().clone(), factory.mk_rcst( typ.get().default() )
+          ) ; ()
+        }
+      }
+    }
+    Cex {
+      sys: sys.clone(), no_state: self.no_state.clone(), trace: trace,
+      hidden: self.hidden.clone(), lasso: self.lasso,
+    }
+  }
+
+  /// Values of the state variables at some offset, as current or next state
+  /// variables. Missing values get the default value of their type.
+  fn env_at(
+    & self, factory: & Factory, off: & Offset, st: State,
+    env: & mut HashMap<Var, Cst>
+  ) {
+    let state = self.trace.get(off) ;
+    for & (ref sym, ref typ) in self.sys.state().args() {
+      let cst = match state.and_then( |state| state.get( sym.get() ) ) {
+        Some(cst) => cst.clone(),
+        None => factory.mk_rcst( typ.get().default() ),
+      } ;
+      let var: Var = factory.svar(sym.get().clone(), st) ;
+      env.insert(var, cst) ; ()
+    }
+  }
+
+  /// Replays a counterexample by concrete evaluation, without any solver.
+  ///
+  /// Checks that the first state is initial, that each state is a successor
+  /// of the previous one, and that each property is false in at least one
+  /// state. Returns the first inconsistency found.
+  pub fn replay(
+    & self, factory: & Factory, props: & [::Prop]
+  ) -> Result<(), Inconsistency> {
+    let evaluator = Evaluator::mk(factory, & self.sys, & self.no_state, props) ;
+
+    macro_rules! eval_bool {
+      ($term:expr, $env:expr, $off:expr) => (
+        match evaluator.eval($term, $env) {
+          Ok(cst) => match * cst.get() {
+            ::term::real_term::Cst::Bool(b) => b,
+            _ => return Err(
+              Inconsistency::Eval(
+                $off.clone(), format!("expected Bool, got {}", cst)
+              )
+            ),
+          },
+          Err(blah) => return Err( Inconsistency::Eval($off.clone(), blah) ),
+        }
+      ) ;
+    }
+
+    // Properties that still hold in all states so far.
+    let mut holding: Vec<& ::Prop> = props.iter().collect() ;
+
+    let zero = Offset::zero() ;
+    let mut env = HashMap::new() ;
+    self.env_at(factory, & zero, State::Curr, & mut env) ;
+    if ! eval_bool!( self.sys.init_term(), & env, zero ) {
+      return Err( Inconsistency::Init )
+    }
+    let mut still_holding = Vec::with_capacity( holding.len() ) ;
+    for prop in holding.into_iter() {
+      let holds = match prop.body().state() {
+        Some(term) => eval_bool!(term, & env, zero),
+        // Two-state properties hold in the initial state.
+        None => true,
+      } ;
+      if holds { still_holding.push(prop) }
+    }
+    holding = still_holding ;
+
+    let mut off = zero ;
+    while self.trace.contains_key( & off.nxt() ) {
+      let nxt = off.nxt() ;
+      env.clear() ;
+      self.env_at(factory, & off, State::Curr, & mut env) ;
+      self.env_at(factory, & nxt, State::Next, & mut env) ;
+      if ! eval_bool!( self.sys.trans_term(), & env, nxt ) {
+        return Err( Inconsistency::Trans(nxt) )
+      }
+      let mut still_holding = Vec::with_capacity( holding.len() ) ;
+      for prop in holding.into_iter() {
+        if eval_bool!( prop.body().next(), & env, nxt ) {
+          still_holding.push(prop)
+        }
+      }
+      holding = still_holding ;
+      off = nxt
+    }
+
+    match holding.first() {
+      Some(prop) => Err( Inconsistency::Prop( prop.sym().get().clone() ) ),
+      None => Ok(()),
+    }
+  }
+}
@@ -0,0 +1,61 @@
+// Copyright 2016 Adrien Champion. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Helpers shared by the front-ends translating their input to kinō's
+//! VMT-LIB: Lustre, AIGER and annotation-based VMT-LIB. The BTOR2 front-end
+//! builds its system directly but names its state variables the same way.
+
+use std::collections::HashMap ;
+
+/// Translation of an input to kinō's VMT-LIB.
+pub struct Translation {
+  /// VMT-LIB script defining the systems and checking some properties.
+  pub vmt: String,
+  /// State variables introduced by the translation, by system. Hidden in
+  /// counterexamples.
+  pub hidden: HashMap<String, Vec<String>>,
+}
+
+/// True if a symbol can be used as a state variable or property name.
+pub fn legal_sym(sym: & str) -> bool {
+  let bytes = sym.as_bytes() ;
+  let simple = |c: u8| {
+    (b'a' <= c && c <= b'z') || (b'A' <= c && c <= b'Z') || c == b'_'
+  } ;
+  if bytes.len() == 1 {
+    simple(bytes[0])
+  } else {
+    ! bytes.is_empty() && bytes[0] != b'@' && ! sym.contains('|') &&
+    ! sym.contains('\\')
+  }
+}
+
+/// Quotes a symbol if needed.
+pub fn quote(sym: & str) -> String {
+  let simple = sym.bytes().enumerate().all(
+    |(idx, c)| (b'a' <= c && c <= b'z') || (b'A' <= c && c <= b'Z') ||
+    c == b'_' || (idx > 0 && b'0' <= c && c <= b'9')
+  ) ;
+  if simple { sym.to_string() } else { format!("|{}|", sym) }
+}
+
+/// Conjunction of some terms.
+pub fn conj(terms: Vec<String>) -> String {
+  match terms.len() {
+    0 => "  true".to_string(),
+    1 => format!("  {}", terms[0]),
+    _ => {
+      let mut s = "  (and".to_string() ;
+      for term in terms.iter() {
+        s = format!("{}\n    {}", s, term)
+      }
+      format!("{}\n  )", s)
+    },
+  }
+}
@@ -167,7 +167,7 @@ fn checker(
                   Err( (_, bla) ) => return Err( format!("{}", bla) ),
                 },
                 None => {
-                  if let Some( & (_, typ) ) = extract(sym, bindings) {
+                  if let Some( & (_, ref typ) ) = extract(sym, bindings) {
                     typ.clone()
                   } else {
                     if let Some(typ) = extract(sym, quantified) {
@@ -370,7 +370,7 @@ pub mod test {
                 for t in vec![ Type::Bool, Type::Int, Type::Rat ] {
                   println!("| generating {} terms of type {}", term_cnt, t) ;
                   println!("| max depth is {}", depth) ;
-                  let terms = gen.generate(t, term_cnt, Some(depth)) ;
+                  let terms = gen.generate(t.clone(), term_cnt, Some(depth)) ;
                   println!("| > done ({})", terms.len()) ;
                   for term in terms {
                     println!("") ;
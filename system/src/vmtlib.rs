@@ -0,0 +1,575 @@
+// Copyright 2016 Adrien Champion. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*! Annotation-based VMT-LIB front-end.
+
+The VMT-LIB format used by other tools is plain SMT-LIB where some
+definitions are annotated:
+
+```smt
+(declare-fun x () Int)
+(declare-fun x.next () Int)
+(define-fun .sv0 () Int (! x :next x.next))
+(define-fun .init () Bool (! (= x 0) :init true))
+(define-fun .trans () Bool (! (= x.next (+ x 1)) :trans true))
+(define-fun .prop () Bool (! (>= x 0) :invar-property 0))
+```
+
+Such a script is translated to a single system `vmt`:
+
+* constants paired by a `:next` annotation are a state variable, the other
+  constants are unconstrained state variables (inputs),
+* `:init` and `:trans` definitions are conjoined to the init and trans
+  predicates,
+* `:invar-property <n>` definitions are properties named `prop_<n>`,
+* declared functions with arguments are uninterpreted functions,
+* other definitions are macros, and are expanded as are `let`s.
+
+`:live-property` annotations, sort declarations and datatypes are not
+supported. */
+
+use std::collections::HashMap ;
+
+use { Error, Line } ;
+use translation::{ Translation, legal_sym, quote, conj } ;
+
+/// Result of a translation step, errors are a position in the script and a
+/// message.
+type VmtRes<T> = Result<T, (usize, String)> ;
+
+/// Name of the system of a script.
+static sys_name: & 'static str = "vmt" ;
+
+/// S-expressions.
+#[derive(Debug, Clone)]
+enum Sexp {
+  /// Atom: its text, unquoted, whether it was quoted and its position.
+  Atom(String, bool, usize),
+  /// List and its position.
+  List(Vec<Sexp>, usize),
+}
+impl Sexp {
+  /// Position of an s-expression.
+  fn pos(& self) -> usize {
+    match * self {
+      Sexp::Atom(_, _, pos) | Sexp::List(_, pos) => pos,
+    }
+  }
+  /// The text of an atom, `None` for lists.
+  fn atom(& self) -> Option<& str> {
+    match * self {
+      Sexp::Atom(ref txt, _, _) => Some(txt),
+      Sexp::List(_, _) => None,
+    }
+  }
+  /// The text of an atom, error for lists.
+  fn sym(& self, what: & str) -> VmtRes<& str> {
+    match self.atom() {
+      Some(txt) => Ok(txt),
+      None => Err( (self.pos(), format!("expected {}", what)) ),
+    }
+  }
+  /// The elements of a list, error for atoms.
+  fn list(& self, what: & str) -> VmtRes<& [Sexp]> {
+    match * self {
+      Sexp::List(ref kids, _) => Ok(kids),
+      Sexp::Atom(_, _, pos) => Err( (pos, format!("expected {}", what)) ),
+    }
+  }
+  /// Writes an s-expression as is.
+  fn raw(& self, s: & mut String) {
+    match * self {
+      Sexp::Atom(ref txt, quoted, _) => if quoted {
+        s.push('|') ;
+        s.push_str(txt) ;
+        s.push('|')
+      } else {
+        s.push_str(txt)
+      },
+      Sexp::List(ref kids, _) => {
+        s.push('(') ;
+        for (idx, kid) in kids.iter().enumerate() {
+          if idx > 0 { s.push(' ') }
+          kid.raw(s)
+        }
+        s.push(')')
+      },
+    }
+  }
+  /// String of an s-expression as is.
+  fn raw_str(& self) -> String {
+    let mut s = String::new() ;
+    self.raw(& mut s) ;
+    s
+  }
+}
+
+/// True for the whitespace characters of SMT-LIB.
+fn is_space(c: u8) -> bool {
+  c == b' ' || c == b'\t' || c == b'\n' || c == b'\r'
+}
+
+/// Parses a script as a list of s-expressions.
+fn parse(src: & str) -> VmtRes< Vec<Sexp> > {
+  let bytes = src.as_bytes() ;
+  // Stack of the lists being parsed, with their position.
+  let mut stack: Vec< (Vec<Sexp>, usize) > = vec![ (vec![], 0) ] ;
+  let mut pos = 0 ;
+  while pos < bytes.len() {
+    let bgn = pos ;
+    match bytes[pos] {
+      b';' => while pos < bytes.len() && bytes[pos] != b'\n' { pos += 1 },
+      b'(' => {
+        stack.push( (vec![], pos) ) ;
+        pos += 1
+      },
+      b')' => {
+        if stack.len() < 2 {
+          return Err( (pos, "unexpected closing parenthesis".into()) )
+        }
+        let (kids, lpos) = stack.pop().unwrap() ;
+        stack.last_mut().unwrap().0.push( Sexp::List(kids, lpos) ) ;
+        pos += 1
+      },
+      b'|' | b'"' => {
+        let delim = bytes[pos] ;
+        pos += 1 ;
+        while pos < bytes.len() && bytes[pos] != delim { pos += 1 }
+        if pos >= bytes.len() {
+          return Err( (bgn, "unterminated quoted symbol or string".into()) )
+        }
+        pos += 1 ;
+        let atom = if delim == b'|' {
+          Sexp::Atom( src[bgn + 1 .. pos - 1].to_string(), true, bgn )
+        } else {
+          Sexp::Atom( src[bgn .. pos].to_string(), false, bgn )
+        } ;
+        stack.last_mut().unwrap().0.push(atom)
+      },
+      c if is_space(c) => pos += 1,
+      _ => {
+        while pos < bytes.len() && ! is_space(bytes[pos])
+        && bytes[pos] != b'(' && bytes[pos] != b')' && bytes[pos] != b';' {
+          pos += 1
+        }
+        stack.last_mut().unwrap().0.push(
+          Sexp::Atom( src[bgn .. pos].to_string(), false, bgn )
+        )
+      },
+    }
+  }
+  if stack.len() > 1 {
+    return Err( (stack.last().unwrap().1, "unclosed parenthesis".into()) )
+  }
+  Ok( stack.pop().unwrap().0 )
+}
+
+/// True if a script uses VMT-LIB annotations: some definition is of the
+/// form `(define-fun <id> <args> <sort> (! ...))`.
+pub fn is_annotated(src: & str) -> bool {
+  match parse(src) {
+    Ok(cmds) => cmds.iter().any(
+      |cmd| match * cmd {
+        Sexp::List(ref kids, _) => kids.len() == 5 &&
+        kids[0].atom() == Some("define-fun") && match kids[4] {
+          Sexp::List(ref body, _) => ! body.is_empty() &&
+          body[0].atom() == Some("!"),
+          _ => false,
+        },
+        _ => false,
+      }
+    ),
+    Err(_) => false,
+  }
+}
+
+/// A macro: formal parameters and body.
+type Macro = (Vec<String>, Sexp) ;
+
+/// Translation context of a script.
+struct Ctx {
+  /// Declared constants and their sort, in declaration order.
+  consts: Vec<(String, String)>,
+  /// Maps next state constants to their current state constant.
+  nexts: HashMap<String, String>,
+  /// Current state constants having a next state one.
+  currs: HashMap<String, String>,
+  /// Uninterpreted functions, as VMT-LIB declarations.
+  funs: Vec<String>,
+  /// Names of the uninterpreted functions.
+  fun_names: HashMap<String, ()>,
+  /// Macros.
+  macros: HashMap<String, Macro>,
+  /// Conjuncts of the init predicate.
+  init: Vec<Sexp>,
+  /// Conjuncts of the trans predicate.
+  trans: Vec<Sexp>,
+  /// Properties, by index.
+  props: Vec<(usize, Sexp)>,
+}
+impl Ctx {
+  /// Legal VMT-LIB symbol for a declared symbol.
+  fn sym(& self, name: & str, pos: usize) -> VmtRes<String> {
+    if legal_sym(name) {
+      Ok( quote(name) )
+    } else {
+      Err( (pos, format!("unsupported symbol `{}`", name)) )
+    }
+  }
+
+  /// Handles a command.
+  fn cmd(& mut self, cmd: & Sexp) -> VmtRes<()> {
+    let kids = try!( cmd.list("command") ) ;
+    let pos = cmd.pos() ;
+    let head = match kids.first() {
+      Some(head) => try!( head.sym("command") ),
+      None => return Err( (pos, "expected command".into()) ),
+    } ;
+    match head {
+      "set-logic" | "set-info" | "set-option" | "check-sat" | "exit" => (),
+      "declare-fun" if kids.len() == 4 => {
+        let name = try!( kids[1].sym("function symbol") ).to_string() ;
+        let args = try!( kids[2].list("signature") ) ;
+        if self.fun_names.contains_key(& name) || self.consts.iter().any(
+          |& (ref n, _)| n == & name
+        ) {
+          return Err( (kids[1].pos(), format!("`{}` is declared twice", name)) )
+        }
+        if args.is_empty() {
+          self.consts.push( (name, kids[3].raw_str()) )
+        } else {
+          let mut dec = format!(
+            "(declare-fun {} (", try!( self.sym(& name, kids[1].pos()) )
+          ) ;
+          for arg in args.iter() {
+            dec.push(' ') ;
+            arg.raw(& mut dec)
+          }
+          dec.push_str( & format!(" ) {})", kids[3].raw_str()) ) ;
+          self.funs.push(dec) ;
+          self.fun_names.insert(name, ()) ; ()
+        }
+      },
+      "define-fun" if kids.len() == 5 => {
+        let name = try!( kids[1].sym("function symbol") ).to_string() ;
+        let annotated = match kids[4] {
+          Sexp::List(ref body, _) if ! body.is_empty() &&
+          body[0].atom() == Some("!") => Some(body),
+          _ => None,
+        } ;
+        if let Some(body) = annotated {
+          if body.len() < 2 || body.len() % 2 != 0 {
+            return Err( (kids[4].pos(), "illegal annotated term".into()) )
+          }
+          let term = & body[1] ;
+          for pair in body[2..].chunks(2) {
+            let key = try!( pair[0].sym("keyword") ) ;
+            match key {
+              ":next" => {
+                let curr = try!( term.sym("constant") ).to_string() ;
+                let next = try!( pair[1].sym("constant") ).to_string() ;
+                if self.currs.contains_key(& curr) ||
+                self.nexts.contains_key(& next) {
+                  return Err(
+                    (
+                      pair[1].pos(),
+                      format!("second `:next` for `{}` or `{}`", curr, next)
+                    )
+                  )
+                }
+                self.currs.insert( curr.clone(), next.clone() ) ;
+                self.nexts.insert(next, curr) ; ()
+              },
+              ":init" => self.init.push( term.clone() ),
+              ":trans" => self.trans.push( term.clone() ),
+              ":invar-property" => {
+                let idx = match pair[1].atom().map(
+                  |txt| txt.parse::<usize>()
+                ) {
+                  Some( Ok(idx) ) => idx,
+                  _ => return Err(
+                    (pair[1].pos(), "expected property index".into())
+                  ),
+                } ;
+                if self.props.iter().any( |& (i, _)| i == idx ) {
+                  return Err(
+                    (
+                      pair[1].pos(),
+                      format!("property {} is defined twice", idx)
+                    )
+                  )
+                }
+                self.props.push( (idx, term.clone()) )
+              },
+              ":live-property" => return Err(
+                (pair[0].pos(), "liveness properties are not supported".into())
+              ),
+              // Other annotations such as `:named` are ignored.
+              _ => (),
+            }
+          }
+        } else {
+          let mut params = vec![] ;
+          for param in try!( kids[2].list("formal parameters") ) {
+            let param = try!( param.list("formal parameter") ) ;
+            match param.first() {
+              Some(sym) => params.push(
+                try!( sym.sym("formal parameter") ).to_string()
+              ),
+              None => return Err( (pos, "illegal formal parameter".into()) ),
+            }
+          }
+          self.macros.insert( name, (params, kids[4].clone()) ) ; ()
+        }
+      },
+      _ => return Err( (pos, format!("unsupported command `{}`", head)) ),
+    }
+    Ok(())
+  }
+
+  /// Translates a term. Local symbols are replaced by their value in `env`.
+  fn term(
+    & self, term: & Sexp, env: & HashMap<String, String>, s: & mut String
+  ) -> VmtRes<()> {
+    match * term {
+      Sexp::Atom(ref name, _, pos) => if let Some(val) = env.get(name) {
+        s.push_str(val)
+      } else if let Some(curr) = self.nexts.get(name) {
+        s.push_str( & format!("(_ next {})", try!( self.sym(curr, pos) )) )
+      } else if self.consts.iter().any( |& (ref n, _)| n == name ) {
+        s.push_str( & format!("(_ curr {})", try!( self.sym(name, pos) )) )
+      } else if self.fun_names.contains_key(name) {
+        s.push_str( & try!( self.sym(name, pos) ) )
+      } else if let Some( & (ref params, ref body) ) = self.macros.get(name) {
+        if ! params.is_empty() {
+          return Err( (pos, format!("macro `{}` expects arguments", name)) )
+        }
+        try!( self.term(body, & HashMap::new(), s) )
+      } else {
+        term.raw(s)
+      },
+
+      Sexp::List(ref kids, pos) => {
+        let head = kids.first().and_then( |head| head.atom() ) ;
+        match head {
+          // Indexed identifiers and qualified constants.
+          Some("_") | Some("as") => term.raw(s),
+          Some("!") if kids.len() >= 2 => try!( self.term(& kids[1], env, s) ),
+          Some("let") if kids.len() == 3 => {
+            let mut local = env.clone() ;
+            for binding in try!( kids[1].list("bindings") ) {
+              let binding = try!( binding.list("binding") ) ;
+              if binding.len() != 2 {
+                return Err( (pos, "illegal binding".into()) )
+              }
+              let mut val = String::new() ;
+              try!( self.term(& binding[1], env, & mut val) ) ;
+              local.insert(
+                try!( binding[0].sym("symbol") ).to_string(), val
+              ) ; ()
+            }
+            try!( self.term(& kids[2], & local, s) )
+          },
+          Some(q) if (q == "forall" || q == "exists") && kids.len() == 3 => {
+            let mut local = env.clone() ;
+            s.push_str( & format!("({} (", q) ) ;
+            for var in try!( kids[1].list("sorted variables") ) {
+              let var = try!( var.list("sorted variable") ) ;
+              if var.len() != 2 {
+                return Err( (pos, "illegal sorted variable".into()) )
+              }
+              let name = try!( var[0].sym("symbol") ) ;
+              let sym = try!( self.sym(name, var[0].pos()) ) ;
+              s.push_str( & format!(" ({} {})", sym, var[1].raw_str()) ) ;
+              local.insert( name.to_string(), sym ) ; ()
+            }
+            s.push_str(" ) ") ;
+            try!( self.term(& kids[2], & local, s) ) ;
+            s.push(')')
+          },
+          Some(name) if self.macros.contains_key(name) &&
+          ! env.contains_key(name) => {
+            let & (ref params, ref body) = self.macros.get(name).unwrap() ;
+            if params.len() + 1 != kids.len() {
+              return Err(
+                (
+                  pos, format!(
+                    "macro `{}` expects {} arguments", name, params.len()
+                  )
+                )
+              )
+            }
+            let mut local = HashMap::with_capacity( params.len() ) ;
+            for (param, arg) in params.iter().zip( kids[1..].iter() ) {
+              let mut val = String::new() ;
+              try!( self.term(arg, env, & mut val) ) ;
+              local.insert( param.clone(), val ) ; ()
+            }
+            try!( self.term(body, & local, s) )
+          },
+          _ => {
+            s.push('(') ;
+            for (idx, kid) in kids.iter().enumerate() {
+              if idx > 0 { s.push(' ') }
+              try!( self.term(kid, env, s) )
+            }
+            s.push(')')
+          },
+        }
+      },
+    }
+    Ok(())
+  }
+
+  /// String of a term.
+  fn term_str(& self, term: & Sexp) -> VmtRes<String> {
+    let mut s = String::new() ;
+    try!( self.term(term, & HashMap::new(), & mut s) ) ;
+    Ok(s)
+  }
+}
+
+/// Translates a script, errors are positions in the script.
+fn translate_internal(src: & str) -> VmtRes<Translation> {
+  let cmds = try!( parse(src) ) ;
+  let mut ctx = Ctx {
+    consts: vec![], nexts: HashMap::new(), currs: HashMap::new(),
+    funs: vec![], fun_names: HashMap::new(), macros: HashMap::new(),
+    init: vec![], trans: vec![], props: vec![],
+  } ;
+  for cmd in cmds.iter() {
+    try!( ctx.cmd(cmd) )
+  }
+
+  // Checking the `:next` pairs.
+  for (curr, next) in ctx.currs.iter() {
+    for name in [ curr, next ].iter() {
+      if ! ctx.consts.iter().any( |& (ref n, _)| n == * name ) {
+        return Err(
+          (0, format!("`:next` pair over undeclared constant `{}`", name))
+        )
+      }
+    }
+  }
+  if ctx.props.is_empty() {
+    return Err( (src.len(), "no `:invar-property` to check".into()) )
+  }
+
+  let mut vmt = String::new() ;
+  for fun in ctx.funs.iter() {
+    vmt.push_str(fun) ;
+    vmt.push('\n')
+  }
+  if ! ctx.funs.is_empty() { vmt.push('\n') }
+
+  vmt.push_str( & format!("(define-sys {}\n  (\n", sys_name) ) ;
+  for & (ref name, ref sort) in ctx.consts.iter() {
+    if ! ctx.nexts.contains_key(name) {
+      vmt.push_str(
+        & format!("    ({} {})\n", try!( ctx.sym(name, 0) ), sort)
+      )
+    }
+  }
+  let mut init = Vec::with_capacity( ctx.init.len() ) ;
+  for term in ctx.init.iter() {
+    init.push( try!( ctx.term_str(term) ) )
+  }
+  let mut trans = Vec::with_capacity( ctx.trans.len() ) ;
+  for term in ctx.trans.iter() {
+    trans.push( try!( ctx.term_str(term) ) )
+  }
+  vmt.push_str(
+    & format!("  )\n{}\n{}\n  ()\n)\n\n", conj(init), conj(trans))
+  ) ;
+
+  ctx.props.sort_by( |& (lhs, _), & (rhs, _)| lhs.cmp(& rhs) ) ;
+  let mut names = Vec::with_capacity( ctx.props.len() ) ;
+  for & (idx, ref term) in ctx.props.iter() {
+    let name = format!("prop_{}", idx) ;
+    vmt.push_str(
+      & format!(
+        "(define-prop {} {}\n  {}\n)\n", name, sys_name,
+        try!( ctx.term_str(term) )
+      )
+    ) ;
+    names.push(name)
+  }
+  vmt.push_str( & format!("\n(verify {} ({}))\n", sys_name, names.join(" ")) ) ;
+
+  let mut hidden = HashMap::new() ;
+  hidden.insert( sys_name.to_string(), vec![] ) ;
+  Ok( Translation { vmt: vmt, hidden: hidden } )
+}
+
+/// Translates an annotation-based VMT-LIB script to kinō's VMT-LIB.
+pub fn translate(src: & str) -> Result<Translation, Error> {
+  translate_internal(src).map_err(
+    |(pos, blah)| Error::parse_mk( Line::of_pos(src, pos), blah, vec![] )
+  )
+}
+
+
+#[cfg(test)]
+mod test {
+  use super::{ translate, is_annotated } ;
+
+  /// Translates a script, prints the error if any.
+  fn tr(src: & str) -> Result<String, String> {
+    translate(src).map( |t| t.vmt ).map_err(
+      |e| { e.print() ; format!("{}", e) }
+    )
+  }
+
+  #[test]
+  fn annotated() {
+    let src = "\
+      (set-logic QF_LIA)\n\
+      (declare-fun x () Int)\n\
+      (declare-fun x.next () Int)\n\
+      (declare-fun |in| () Bool)\n\
+      (define-fun .sv0 () Int (! x :next x.next))\n\
+      (define-fun inc ((n Int)) Int (+ n 1))\n\
+      (define-fun .init () Bool (! (= x 0) :init true))\n\
+      (define-fun .trans () Bool (!\n\
+        (let ((y (ite in (inc x) x))) (= x.next y))\n\
+      :trans true))\n\
+      (define-fun .p1 () Bool (! (<= x 10) :invar-property 1))\n\
+      (define-fun .p0 () Bool (! (>= x 0) :invar-property 0))\n\
+    " ;
+    assert!( is_annotated(src) ) ;
+    let vmt = tr(src).unwrap() ;
+    assert!( vmt.contains("(x Int)") ) ;
+    assert!( vmt.contains("(in Bool)") ) ;
+    assert!( ! vmt.contains("x.next") ) ;
+    assert!( vmt.contains("(= (_ curr x) 0)") ) ;
+    assert!(
+      vmt.contains(
+        "(= (_ next x) (ite (_ curr in) (+ (_ curr x) 1) (_ curr x)))"
+      )
+    ) ;
+    assert!( vmt.contains("(define-prop prop_0 vmt\n  (>= (_ curr x) 0)") ) ;
+    assert!( vmt.contains("(verify vmt (prop_0 prop_1))") ) ;
+
+    assert!( ! is_annotated("(define-sys sys ( (x Int) ) true true ())") )
+  }
+
+  #[test]
+  fn errors() {
+    let err = tr("\
+      (declare-fun x () Bool)\n\
+      (define-fun .p () Bool (! x :live-property 0))\n\
+    ").unwrap_err() ;
+    assert!( err.contains("liveness properties are not supported") ) ;
+    let err = tr("\
+      (declare-fun x () Bool)\n\
+      (define-fun .i () Bool (! x :init true))\n\
+    ").unwrap_err() ;
+    assert!( err.contains("no `:invar-property`") )
+  }
+}
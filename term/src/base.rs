@@ -143,10 +143,10 @@ pub struct Offset2 {
 }
 
 impl Offset2 {
-  // /// Creates an `Offset2`. Sometimes necessary, but prefer `init`.
-  // pub fn mk(curr: Offset, next: Offset) -> Self {
-  //   Offset2 { curr: curr, next: next }
-  // }
+  /// Creates an `Offset2`. Sometimes necessary, but prefer `init`.
+  pub fn mk(curr: Offset, next: Offset) -> Self {
+    Offset2 { curr: curr, next: next }
+  }
 
   /// Initial two-state offset.
   pub fn init() -> Self {
@@ -11,6 +11,7 @@
 
 use std::io ;
 use std::fmt ;
+use std::sync::Arc ;
 
 use base::{ Writable, HConsed, HConsign, HConser } ;
 use typ ;
@@ -28,6 +29,14 @@ pub enum RealCst {
   Int(typ::Int),
   /// Rational constant.
   Rat(typ::Rat),
+  /// Array constant: index type, default value, and the values stored at
+  /// some indices. The latter are sorted by index and never store the
+  /// default value, so that two equal arrays have the same representation.
+  Array(typ::Type, Box<RealCst>, Vec<(RealCst, RealCst)>),
+  /// Bit-vector constant: width and unsigned value, in `[0, 2^width)`.
+  BitVec(usize, typ::Int),
+  /// Enumerated constant: sort and index of the constructor in the sort.
+  Enum(Arc<typ::Enum>, usize),
 }
 
 impl RealCst {
@@ -37,13 +46,83 @@ impl RealCst {
       Bool(_) => typ::Type::Bool,
       Int(_) => typ::Type::Int,
       Rat(_) => typ::Type::Rat,
+      Array(ref idx, ref default, _) => typ::Type::array(
+        idx.clone(), default.typ()
+      ),
+      BitVec(width, _) => typ::Type::BitVec(width),
+      Enum(ref e, _) => typ::Type::Enum( e.clone() ),
+    }
+  }
+
+  /// Bit-vector constant of some width. The value is taken modulo
+  /// `2^width`, so negative values are understood in two's complement.
+  pub fn bv(width: usize, val: typ::Int) -> Self {
+    use num::Integer ;
+    use num::traits::One ;
+    BitVec( width, val.mod_floor( & (typ::Int::one() << width) ) )
+  }
+
+  /// Value stored at some index of an array constant.
+  pub fn select(& self, idx: & Self) -> Result<Self, Self> {
+    match * self {
+      Array(ref idx_typ, ref default, ref vals) => if idx.typ() != * idx_typ {
+        Err(idx.clone())
+      } else {
+        match vals.binary_search_by(|& (ref i, _)| i.cmp(idx)) {
+          Ok(pos) => Ok( vals[pos].1.clone() ),
+          Err(_) => Ok( (* * default).clone() ),
+        }
+      },
+      _ => Err(self.clone()),
+    }
+  }
+
+  /// Stores a value at some index of an array constant.
+  pub fn store(& self, idx: & Self, val: & Self) -> Result<Self, Self> {
+    match * self {
+      Array(ref idx_typ, ref default, ref vals) => if idx.typ() != * idx_typ {
+        Err(idx.clone())
+      } else if val.typ() != default.typ() {
+        Err(val.clone())
+      } else {
+        let mut vals = vals.clone() ;
+        match vals.binary_search_by(|& (ref i, _)| i.cmp(idx)) {
+          Ok(pos) => if * val == * * default {
+            vals.remove(pos) ; ()
+          } else {
+            vals[pos].1 = val.clone()
+          },
+          Err(pos) => if * val != * * default {
+            vals.insert( pos, (idx.clone(), val.clone()) )
+          },
+        }
+        Ok( Array(idx_typ.clone(), default.clone(), vals) )
+      },
+      _ => Err(self.clone()),
+    }
+  }
+
+  /// Floating-point approximation of an arithmetic constant, or of the
+  /// unsigned value of a bit-vector. `None` for booleans, arrays and
+  /// enumerated constants, or if the value does not fit in an `f64`.
+  pub fn to_f64(& self) -> Option<f64> {
+    use num::traits::ToPrimitive ;
+    match * self {
+      Bool(_) | Array(_, _, _) | Enum(_, _) => None,
+      Int(ref i) | BitVec(_, ref i) => i.to_f64(),
+      Rat(ref r) => match ( r.numer().to_f64(), r.denom().to_f64() ) {
+        ( Some(num), Some(den) ) => Some(num / den),
+        _ => None,
+      },
     }
   }
 
   /// Adds two constants if possible.
   pub fn add(& self, rhs: & Self) -> Result<Self, Self> {
     match * self {
-      Bool(_) => Err(self.clone()),
+      Bool(_) | Array(_, _, _) | BitVec(_, _) | Enum(_, _) => Err(
+        self.clone()
+      ),
       Int(ref lhs) => match * rhs {
         Int(ref rhs) => Ok( Int(lhs + rhs) ),
         _ => Err(rhs.clone()),
@@ -58,7 +137,9 @@ impl RealCst {
   /// Substracts two constants if possible.
   pub fn sub(& self, rhs: & Self) -> Result<Self, Self> {
     match * self {
-      Bool(_) => Err(self.clone()),
+      Bool(_) | Array(_, _, _) | BitVec(_, _) | Enum(_, _) => Err(
+        self.clone()
+      ),
       Int(ref lhs) => match * rhs {
         Int(ref rhs) => Ok( Int(lhs - rhs) ),
         _ => Err(rhs.clone()),
@@ -73,7 +154,9 @@ impl RealCst {
   /// Multiplies two constants if possible.
   pub fn mul(& self, rhs: & Self) -> Result<Self, Self> {
     match * self {
-      Bool(_) => Err(self.clone()),
+      Bool(_) | Array(_, _, _) | BitVec(_, _) | Enum(_, _) => Err(
+        self.clone()
+      ),
       Int(ref lhs) => match * rhs {
         Int(ref rhs) => Ok( Int(lhs * rhs) ),
         _ => Err(rhs.clone()),
@@ -109,10 +192,82 @@ impl RealCst {
     }
   }
 
+  /// Integer division of two constants if possible, `div` in SMT-LIB. The
+  /// remainder is always positive, see `int_mod`.
+  pub fn int_div(& self, in_rhs: & Self) -> Result<Self, Self> {
+    use num::Integer ;
+    use num::traits::{ Zero, Signed } ;
+    match * self {
+      Int(ref lhs) => match * in_rhs {
+        Int(ref rhs) => if rhs.is_zero() {
+          Err(in_rhs.clone())
+        } else {
+          let rem = lhs.mod_floor( & rhs.abs() ) ;
+          Ok( Int( (lhs - rem) / rhs ) )
+        },
+        _ => Err(in_rhs.clone()),
+      },
+      _ => Err(self.clone()),
+    }
+  }
+
+  /// Integer remainder of two constants if possible, `mod` in SMT-LIB. Always
+  /// positive and smaller than the absolute value of the divisor.
+  pub fn int_mod(& self, in_rhs: & Self) -> Result<Self, Self> {
+    use num::Integer ;
+    use num::traits::{ Zero, Signed } ;
+    match * self {
+      Int(ref lhs) => match * in_rhs {
+        Int(ref rhs) => if rhs.is_zero() {
+          Err(in_rhs.clone())
+        } else {
+          Ok( Int( lhs.mod_floor( & rhs.abs() ) ) )
+        },
+        _ => Err(in_rhs.clone()),
+      },
+      _ => Err(self.clone()),
+    }
+  }
+
+  /// Absolute value of an integer constant.
+  pub fn abs(& self) -> Result<Self, Self> {
+    use num::traits::Signed ;
+    match * self {
+      Int(ref v) => Ok( Int( v.abs() ) ),
+      _ => Err(self.clone()),
+    }
+  }
+
+  /// Converts an integer constant to a rational one.
+  pub fn to_real(& self) -> Result<Self, Self> {
+    match * self {
+      Int(ref v) => Ok( Rat( typ::Rat::from_integer( v.clone() ) ) ),
+      _ => Err(self.clone()),
+    }
+  }
+
+  /// Converts a rational constant to an integer one, rounding down.
+  pub fn to_int(& self) -> Result<Self, Self> {
+    match * self {
+      Rat(ref v) => Ok( Int( v.floor().to_integer() ) ),
+      _ => Err(self.clone()),
+    }
+  }
+
+  /// True if a rational constant is an integer.
+  pub fn is_int(& self) -> Result<Self, Self> {
+    match * self {
+      Rat(ref v) => Ok( Bool( v.is_integer() ) ),
+      _ => Err(self.clone()),
+    }
+  }
+
   /// Negates a constant if possible.
   pub fn neg(& self) -> Result<Self, Self> {
     match * self {
-      Bool(_) => Err(self.clone()),
+      Bool(_) | Array(_, _, _) | BitVec(_, _) | Enum(_, _) => Err(
+        self.clone()
+      ),
       Int(ref v) => Ok( Int(- v) ),
       Rat(ref v) => Ok( Rat(- v) ),
     }
@@ -125,6 +280,54 @@ impl fmt::Display for RealCst {
       Bool(ref b) => write!(fmt, "{}", b),
       Int(ref i) => write!(fmt, "{}", i),
       Rat(ref r) => write!(fmt, "{}", r),
+      Enum(ref e, idx) => write!(fmt, "{}", e.cons()[idx]),
+      Array(_, _, _) | BitVec(_, _) => {
+        use std::str::from_utf8 ;
+        let mut s: Vec<u8> = vec![] ;
+        self.write(& mut s).unwrap() ;
+        write!(fmt, "{}", from_utf8(& s).unwrap())
+      },
+    }
+  }
+}
+
+impl Writable for RealCst {
+  fn write(& self, writer: & mut io::Write) -> io::Result<()> {
+    match * self {
+      Bool(ref b) => write!( writer, "{}", b ),
+      Int(ref i) => write!( writer, "{}", i ),
+      Rat(ref r) => write!( writer, "(/ {} {})", r.numer(), r.denom() ),
+      Array(ref idx, ref default, ref vals) => {
+        for _ in vals.iter() {
+          try!( write!(writer, "(store ") )
+        }
+        try!(
+          write!(
+            writer, "((as const {}) ", typ::Type::array(
+              idx.clone(), default.typ()
+            )
+          )
+        ) ;
+        try!( default.write(writer) ) ;
+        try!( write!(writer, ")") ) ;
+        for & (ref idx, ref val) in vals.iter() {
+          try!( write!(writer, " ") ) ;
+          try!( idx.write(writer) ) ;
+          try!( write!(writer, " ") ) ;
+          try!( val.write(writer) ) ;
+          try!( write!(writer, ")") )
+        }
+        Ok(())
+      },
+      BitVec(width, ref val) => {
+        let bits = val.to_str_radix(2) ;
+        try!( write!(writer, "#b") ) ;
+        for _ in bits.len()..width {
+          try!( write!(writer, "0") )
+        }
+        write!(writer, "{}", bits)
+      },
+      Enum(ref e, idx) => write!( writer, "{}", e.cons()[idx] ),
     }
   }
 }
@@ -135,11 +338,7 @@ pub type Cst = HConsed<RealCst> ;
 impl Writable for Cst {
   #[inline(always)]
   fn write(& self, writer: & mut io::Write) -> io::Result<()> {
-    match * self.get() {
-      Bool(ref b) => write!( writer, "{}", b ),
-      Int(ref i) => write!( writer, "{}", i ),
-      Rat(ref r) => write!( writer, "(/ {} {})", r.numer(), r.denom() ),
-    }
+    self.get().write(writer)
   }
 }
 
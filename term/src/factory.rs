@@ -17,14 +17,14 @@ use nom::IResult ;
 use rsmt2::ParseSmt2 ;
 
 use base::{ Mkable, State, Offset, Offset2, Smt2Offset } ;
-use typ::{ Type, Bool, Int, Rat } ;
+use typ::{ Type, Enum, SortResolver, Bool, Int, Rat } ;
 use sym::{ SymConsign, Sym, SymMaker } ;
 use cst::{ RealCst, Cst, CstConsign } ;
 use var::{ Var, VarConsign, VarMaker } ;
 use term::{
   TermConsign, Operator, Term, RealTerm,
   CstMaker, VariableMaker, OpMaker, AppMaker, BindMaker,
-  bump, debump
+  bump, debump, subst
 } ;
 use parser ;
 use parser::Spnd ;
@@ -66,6 +66,10 @@ pub struct Factory {
   unscoped_types: Arc< RwLock< HashMap<Term, Type> > >,
   /// Maps function symbols to their type.
   fun_types: Arc< RwLock< HashMap<Sym, Type> > >,
+  /// Maps names to the enumerated sorts declared so far.
+  enums: Arc< RwLock< HashMap<String, Type> > >,
+  /// Maps the constructors of the enumerated sorts to their constant.
+  enum_csts: Arc< RwLock< HashMap<String, RealCst> > >,
 }
 
 // /** Helper macro to create operators. */
@@ -114,6 +118,12 @@ impl Factory {
       fun_types: Arc::new(
         RwLock::new( HashMap::with_capacity(107) )
       ),
+      enums: Arc::new(
+        RwLock::new( HashMap::with_capacity(7) )
+      ),
+      enum_csts: Arc::new(
+        RwLock::new( HashMap::with_capacity(17) )
+      ),
     }
   }
 
@@ -130,7 +140,7 @@ impl Factory {
     & self, sym: Sym, typ: Type
   ) -> Res<()> {
     let sym_bak = sym.clone() ;
-    match self.fun_types.write().unwrap().insert( sym, typ ) {
+    match self.fun_types.write().unwrap().insert( sym, typ.clone() ) {
       Some(t) if t != typ => Err(
         format!(
           "trying to redefine type of function {} from {} to {}",
@@ -141,6 +151,52 @@ impl Factory {
     }
   }
 
+  /// Declares an enumerated sort, returns the corresponding type.
+  ///
+  /// Fails if there is no constructor, if the name is a builtin sort, or if
+  /// the sort or one of its constructors is already declared.
+  pub fn declare_enum(
+    & self, name: String, cons: Vec<String>
+  ) -> Res<Type> {
+    if cons.is_empty() {
+      return Err(
+        format!("enumerated sort {} has no constructor", name).into()
+      )
+    }
+    match & name as & str {
+      "Int" | "Bool" | "Real" | "Array" => return Err(
+        format!("cannot redeclare builtin sort {}", name).into()
+      ),
+      _ => (),
+    }
+    let mut enums = self.enums.write().unwrap() ;
+    let mut csts = self.enum_csts.write().unwrap() ;
+    if enums.contains_key(& name) {
+      return Err(
+        format!("trying to redeclare enumerated sort {}", name).into()
+      )
+    }
+    for (idx, c) in cons.iter().enumerate() {
+      let known = c == "true" || c == "false" || csts.contains_key(c) ||
+        cons[0..idx].contains(c) ;
+      if known {
+        return Err(
+          format!(
+            "constructor {} of enumerated sort {} is already declared",
+            c, name
+          ).into()
+        )
+      }
+    }
+    let sort = Arc::new( Enum::mk(name.clone(), cons) ) ;
+    for (idx, c) in sort.cons().iter().enumerate() {
+      csts.insert( c.clone(), RealCst::Enum(sort.clone(), idx) ) ; ()
+    }
+    let typ = Type::Enum(sort) ;
+    enums.insert( name, typ.clone() ) ;
+    Ok(typ)
+  }
+
   /// Inserts a type for a variable without doing anything else.
   fn set_type_unsafe(
     & self, sym: Option<Sym>, term: Term, typ: Type
@@ -149,7 +205,9 @@ impl Factory {
     match sym {
       Some(sym) => {
         let sym_bak = sym.clone() ;
-        match self.scoped_types.write().unwrap().insert( (sym, term), typ ) {
+        match self.scoped_types.write().unwrap().insert(
+          (sym, term), typ.clone()
+        ) {
           Some(t) if t != typ => Err(
             format!(
               "trying to redefine type of {}::{} from {} to {}",
@@ -159,7 +217,9 @@ impl Factory {
           _ => Ok(())
         }
       },
-      None => match self.unscoped_types.write().unwrap().insert( term, typ ) {
+      None => match self.unscoped_types.write().unwrap().insert(
+        term, typ.clone()
+      ) {
         Some(t) if t != typ => Err(
           format!(
             "trying to redefine type of {} from {} to {}",
@@ -187,7 +247,9 @@ impl Factory {
       },
       Some(state) => {
         let var: Term = self.mk_var(v) ;
-        try!( self.set_type_unsafe(sym.clone(), var.clone(), typ) ) ;
+        try!(
+          self.set_type_unsafe(sym.clone(), var.clone(), typ.clone())
+        ) ;
         let var: Term = try!(
           match state {
             // Neither of these two can be an error.
@@ -218,7 +280,7 @@ impl Factory {
         match self.scoped_types.read().unwrap().get(
           & (scope.clone(), term.clone())
         ) {
-          Some(typ) => Ok(* typ),
+          Some(typ) => Ok( typ.clone() ),
           None => Err(
             format!(
               "can't type unknown term {} under scope {}", term, scope
@@ -228,7 +290,7 @@ impl Factory {
       },
       None => {
         match self.unscoped_types.read().unwrap().get( term ) {
-          Some(typ) => Ok(* typ),
+          Some(typ) => Ok( typ.clone() ),
           None => Err(
             format!("can't type unknown term {}", term).into()
           ),
@@ -237,11 +299,11 @@ impl Factory {
     }
   }
 
-  mk_parser!{
-    #[doc = "Parses a type."]
-    pub fn parse_type(bytes, offset: usize) -> Spnd<Type> {
-      parser::type_parser(bytes, offset)
-    }
+  /// Parses a type, resolving the enumerated sorts declared so far.
+  pub fn parse_type<'a>(
+    & self, bytes: & 'a [u8], offset: usize
+  ) -> IResult<& 'a [u8], Spnd<Type>> {
+    parser::type_parser(bytes, offset, self)
   }
 
   /// Creates a variable from a `Var`.
@@ -417,6 +479,26 @@ impl Factory {
     self.op(Operator::Gt, vec![ lhs, rhs])
   }
 
+  /// Creates an array selection.
+  pub fn select(& self, array: Term, idx: Term) -> Term {
+    self.op(Operator::Select, vec![ array, idx ])
+  }
+
+  /// Creates an array store.
+  pub fn store(& self, array: Term, idx: Term, val: Term) -> Term {
+    self.op(Operator::Store, vec![ array, idx, val ])
+  }
+
+  /// Creates a constant array, mapping all indices of type `idx` to
+  /// `default`.
+  pub fn const_array(& self, idx: Type, default: Cst) -> Term {
+    self.cst(
+      self.mk_rcst(
+        RealCst::Array( idx, Box::new( default.get().clone() ), vec![] )
+      )
+    )
+  }
+
   /// Evaluates a term.
   pub fn eval(
     & self, term: & Term, off: & Offset2, model: & ::Model, scope: Sym
@@ -438,6 +520,15 @@ impl Factory {
         Rat(ref r) => Err(
           format!("[eval_bool] got rational value `{}`", r).into()
         ),
+        Array(_, _, _) => Err(
+          format!("[eval_bool] got array value `{}`", val).into()
+        ),
+        BitVec(_, _) => Err(
+          format!("[eval_bool] got bit-vector value `{}`", val).into()
+        ),
+        Enum(_, _) => Err(
+          format!("[eval_bool] got enumerated value `{}`", val).into()
+        ),
       },
       Err(e) => Err(e),
     }
@@ -457,6 +548,15 @@ impl Factory {
         Rat(ref r) => Err(
           format!("[eval_int] got rational value `{}`", r).into()
         ),
+        Array(_, _, _) => Err(
+          format!("[eval_int] got array value `{}`", val).into()
+        ),
+        BitVec(_, _) => Err(
+          format!("[eval_int] got bit-vector value `{}`", val).into()
+        ),
+        Enum(_, _) => Err(
+          format!("[eval_int] got enumerated value `{}`", val).into()
+        ),
       },
       Err(e) => Err(e),
     }
@@ -471,10 +571,19 @@ impl Factory {
       Ok(val) => match * val.get() {
         Rat(ref r) => Ok(r.clone()),
         Bool(ref b) => Err(
-          format!("[eval_int] got bool value `{}`", b).into()
+          format!("[eval_rat] got bool value `{}`", b).into()
         ),
         Int(ref i) => Err(
-          format!("[eval_int] got integer value `{}`", i).into()
+          format!("[eval_rat] got integer value `{}`", i).into()
+        ),
+        Array(_, _, _) => Err(
+          format!("[eval_rat] got array value `{}`", val).into()
+        ),
+        BitVec(_, _) => Err(
+          format!("[eval_rat] got bit-vector value `{}`", val).into()
+        ),
+        Enum(_, _) => Err(
+          format!("[eval_rat] got enumerated value `{}`", val).into()
         ),
       },
       Err(e) => Err(e),
@@ -540,6 +649,12 @@ impl CstMaker<Rat, Cst> for Factory {
     self.cst.constant(cst)
   }
 }
+impl CstMaker<RealCst, Cst> for Factory {
+  fn cst(& self, cst: RealCst) -> Cst {
+    use cst::ConstMaker ;
+    self.cst.constant(cst)
+  }
+}
 
 
 /* |===| Factory can create variables. */
@@ -646,6 +761,11 @@ pub trait UnTermOps<Trm> {
   /// * changes all `SVar(sym, State::next)` to `SVar(sym, State::curr)`,
   /// * returns `Err(())` if input term contains a `SVar(_, State::curr)`.
   fn debump(& self, Trm) -> Res<Term> ;
+  /// Substitutes the variables of a term.
+  ///
+  /// * replaces each variable appearing as a key in `map` by its value,
+  /// * the values are not substituted themselves.
+  fn subst(& self, Trm, & HashMap<Term, Term>) -> Res<Term> ;
 }
 // impl<
 //   'a, Trm: Clone, T: UnTermOps<Trm> + Sized
@@ -660,6 +780,15 @@ pub trait UnTermOps<Trm> {
 //   }
 // }
 
+impl SortResolver for Factory {
+  fn resolve_sort(& self, name: & str) -> Option<Type> {
+    self.enums.read().unwrap().get(name).cloned()
+  }
+  fn resolve_cons(& self, name: & str) -> Option<RealCst> {
+    self.enum_csts.read().unwrap().get(name).cloned()
+  }
+}
+
 impl UnTermOps<Term> for Factory {
   fn bump(& self, term: Term) -> Res<Term> {
     bump(self, term)
@@ -667,6 +796,9 @@ impl UnTermOps<Term> for Factory {
   fn debump(& self, term: Term) -> Res<Term> {
     debump(self, term)
   }
+  fn subst(& self, term: Term, map: & HashMap<Term, Term>) -> Res<Term> {
+    subst(self, term, map)
+  }
 }
 
 impl<'a> UnTermOps<& 'a Term> for Factory {
@@ -676,6 +808,11 @@ impl<'a> UnTermOps<& 'a Term> for Factory {
   fn debump(& self, term: & 'a Term) -> Res<Term> {
     self.debump( term.clone() )
   }
+  fn subst(
+    & self, term: & 'a Term, map: & HashMap<Term, Term>
+  ) -> Res<Term> {
+    self.subst( term.clone(), map )
+  }
 }
 
 
@@ -770,6 +907,6 @@ impl ParseVmt2 for Factory {
   fn parse_type<'a>(
     & self, bytes: & 'a [u8], offset: usize
   ) -> IResult<& 'a [u8], Spnd<Type>> {
-    parser::type_parser(bytes, offset)
+    parser::type_parser(bytes, offset, self)
   }
 }
@@ -379,7 +379,7 @@ impl<'a, Rand: 'a + Rng + Sized> Zip<'a, Rand> {
   /// Adds a term to the map from types to terms if we're not under a let
   /// binding.
   fn remember(& mut self, term: Term) -> Option<bool> {
-    let typ = self.typ ;
+    let typ = self.typ.clone() ;
     // Remember only if we're not under a let binding.
     for & (ref map, _) in self.bindings.iter() {
       for (_, ref vec) in map.iter() {
@@ -429,7 +429,7 @@ impl<'a, Rand: 'a + Rng + Sized> Zip<'a, Rand> {
   /// Goes down, arith version.
   fn arith_down(& mut self) {
     let op = rand_arith_to_arith(& mut self.rng) ;
-    let typ = self.typ ;
+    let typ = self.typ.clone() ;
     self.push( Step::Op(op, typ, 0, Vec::with_capacity(1)) )
   }
 
@@ -460,7 +460,7 @@ impl<'a, Rand: 'a + Rng + Sized> Zip<'a, Rand> {
 
         // Generate an if then else with 5% chance.
         if down_allowed && rand_bool(& mut self.rng, 5) {
-          let typ = self.typ ;
+          let typ = self.typ.clone() ;
           self.push( Ite0(typ) ) ;
           self.typ = Type::Bool
         } else {
@@ -469,7 +469,7 @@ impl<'a, Rand: 'a + Rng + Sized> Zip<'a, Rand> {
           if ! down_allowed || (
             ! self.at_top() && rand_bool(& mut self.rng, 70)
           ) {
-            let typ = self.typ ;
+            let typ = self.typ.clone() ;
             let (term, depth) = self.get_term(typ) ;
             match self.up(term, depth) {
               Some(term) => return term,
@@ -481,6 +481,16 @@ impl<'a, Rand: 'a + Rng + Sized> Zip<'a, Rand> {
             match self.typ {
               Type::Bool => self.bool_down(),
               Type::Int | Type::Rat => self.arith_down(),
+              // Arrays, bit-vectors and enumerated values are not
+              // generated, reusing an existing term.
+              Type::Array(_, _) | Type::BitVec(_) | Type::Enum(_) => {
+                let typ = self.typ.clone() ;
+                let (term, depth) = self.get_term(typ) ;
+                match self.up(term, depth) {
+                  Some(term) => return term,
+                  None => (),
+                }
+              },
             }
           }
         }
@@ -167,7 +167,7 @@ pub use base::{
   State, PrintSmt2, PrintVmt, Offset, Offset2, Smt2Offset
 } ;
 mod typ ;
-pub use typ::{ Type, Bool, Int, Rat } ;
+pub use typ::{ Type, Enum, SortResolver, Bool, Int, Rat } ;
 mod sym ;
 pub use sym::{ Sym, SymMaker } ;
 mod cst ;
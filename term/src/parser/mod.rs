@@ -15,8 +15,8 @@ use std::io::Write ;
 
 use nom::{ digit, multispace, IResult, not_line_ending } ;
 
-use typ::{ Type, Bool, Int, Rat } ;
-use cst::Cst ;
+use typ::{ Type, Bool, Int, Rat, SortResolver } ;
+use cst::{ Cst, RealCst } ;
 use term::{ CstMaker, Operator } ;
 use rsmt2::{ Sort2Smt, Sym2Smt, Expr2Smt } ;
 use rsmt2::errors::Res ;
@@ -424,17 +424,77 @@ macro_rules! len_set {
 pub mod vmt ;
 pub mod smt2 ;
 
+/// Spanned type parser.
+///
+/// Simple symbols other than `Int`, `Bool` and `Real` are resolved as
+/// user-declared sorts by `r`.
+pub fn type_parser<'a, R: SortResolver>(
+  bytes: & 'a [u8], offset: usize, r: & R
+) -> IResult<& 'a [u8], Spnd<Type>> {
+  let mut len = 0 ;
+  alt!(
+    bytes,
+    map_opt!(
+      simple_symbol, |sym: String| {
+        let typ = match & sym as & str {
+          "Int" => Some(Type::Int),
+          "Bool" => Some(Type::Bool),
+          "Real" => Some(Type::Rat),
+          _ => r.resolve_sort(& sym),
+        } ;
+        typ.map( |typ| Spnd::len_mk(typ, offset, sym.len()) )
+      }
+    ) | do_parse!(
+      len_set!(len < char '(') >>
+      opt!( len_add!(len < int space_comment) ) >>
+      len_add!(len < tag "Array") >>
+      len_add!(len < int space_comment) >>
+      idx: len_add!(
+        len < spn apply!(type_parser, offset + len, r)
+      ) >>
+      len_add!(len < int space_comment) >>
+      elm: len_add!(
+        len < spn apply!(type_parser, offset + len, r)
+      ) >>
+      opt!( len_add!(len < int space_comment) ) >>
+      len_add!(len < char ')') >> (
+        Spnd::len_mk( Type::array(idx, elm), offset, len )
+      )
+    ) | map_opt!(
+      do_parse!(
+        len_set!(len < char '(') >>
+        opt!( len_add!(len < int space_comment) ) >>
+        len_add!(len < char '_') >>
+        len_add!(len < int space_comment) >>
+        len_add!(len < tag "BitVec") >>
+        len_add!(len < int space_comment) >>
+        width: len_add!(
+          len < spn apply!(numeral_parser, offset + len)
+        ) >>
+        opt!( len_add!(len < int space_comment) ) >>
+        len_add!(len < char ')') >> (width)
+      ), |width| if width > 0 {
+        Some( Spnd::len_mk( Type::BitVec(width), offset, len ) )
+      } else {
+        None
+      }
+    )
+  )
+}
+
 mk_parser!{
-  #[doc = "Spanned type parser."]
-  pub fn type_parser(bytes, offset: usize) -> Spnd<Type> {
-    alt!(
+  #[doc = "Parses a spanned numeral, a bit-vector width or index."]
+  pub fn numeral_parser(bytes, offset: usize) -> Spnd<usize> {
+    map_opt!(
       bytes,
-      map!(
-        tag!("Int"),  |bytes: Bytes| Spnd::bytes_mk(Type::Int,  offset, bytes)
-      ) | map!(
-        tag!("Bool"), |bytes: Bytes| Spnd::bytes_mk(Type::Bool, offset, bytes)
-      ) | map!(
-        tag!("Real"), |bytes: Bytes| Spnd::bytes_mk(Type::Rat,  offset, bytes)
+      do_parse!(
+        peek!( one_of!("0123456789") ) >>
+        bytes: digit >>
+        (bytes)
+      ), |bytes: Bytes| ::std::str::from_utf8(bytes).ok().and_then(
+        |s| s.parse::<usize>().ok()
+      ).map(
+        |n| Spnd::bytes_mk(n, offset, bytes)
       )
     )
   }
@@ -612,10 +672,70 @@ mk_parser!{
   }
 }
 
+mk_parser!{
+  #[doc = "Parses a spanned bit-vector constant, its width and its value."]
+  pub fn bv_parser(bytes, offset: usize) -> Spnd<(usize, Int)> {
+    let mut len = 0 ;
+    alt!(
+      bytes,
+      map!(
+        preceded!( tag!("#b"), is_a!("01") ),
+        |bits: Bytes| Spnd::len_mk(
+          ( bits.len(), Int::parse_bytes(bits, 2).unwrap() ),
+          offset, bits.len() + 2
+        )
+      ) |
+      map!(
+        preceded!( tag!("#x"), is_a!("0123456789abcdefABCDEF") ),
+        |hex: Bytes| Spnd::len_mk(
+          ( 4 * hex.len(), Int::parse_bytes(hex, 16).unwrap() ),
+          offset, hex.len() + 2
+        )
+      ) |
+      map_opt!(
+        do_parse!(
+          len_set!(len < char '(') >>
+          opt!( len_add!(len < int space_comment) ) >>
+          len_add!(len < char '_') >>
+          len_add!(len < int space_comment) >>
+          len_add!(len < tag "bv") >>
+          val: map_opt!(
+            digit, |bytes: Bytes| {
+              len += bytes.len() ;
+              Int::parse_bytes(bytes, 10)
+            }
+          ) >>
+          len_add!(len < int space_comment) >>
+          width: len_add!(
+            len < spn apply!(numeral_parser, offset + len)
+          ) >>
+          opt!( len_add!(len < int space_comment) ) >>
+          len_add!(len < char ')') >> ( (width, val) )
+        ), |(width, val)| if width > 0 {
+          Some( Spnd::len_mk( (width, val), offset, len ) )
+        } else {
+          None
+        }
+      )
+    )
+  }
+}
+
+/// Parses a spanned constant.
+///
+/// Bit-vector constants are either `#b<bits>`, `#x<hexa digits>` or
+/// `(_ bv<value> <width>)`.
+///
+/// Simple symbols are parsed as the constructors of the enumerated sorts `f`
+/// knows about.
+///
+/// Array constants are either a constant array `((as const <type>) <cst>)`
+/// or a `store` in an array constant.
 pub fn cst_parser<'a, F>(
   bytes: & 'a [u8], offset: usize, f: & F
 ) -> IResult<& 'a [u8], Spnd<Cst>>
-where F: CstMaker<Bool, Cst> + CstMaker<Int, Cst> + CstMaker<Rat, Cst> {
+where F: CstMaker<Bool, Cst> + CstMaker<Int, Cst> + CstMaker<Rat, Cst> +
+CstMaker<RealCst, Cst> + SortResolver {
   let mut len = 0 ;
   preceded!(
     bytes,
@@ -629,7 +749,87 @@ where F: CstMaker<Bool, Cst> + CstMaker<Int, Cst> + CstMaker<Rat, Cst> {
       ) |
       map!(
         apply!(bool_parser, offset), |b:Spnd<Bool>| b.map(|b| f.cst(b))
-      )
+      ) |
+      map!(
+        apply!(bv_parser, offset), |bv:Spnd<(usize, Int)>| bv.map(
+          |(width, val)| f.cst( RealCst::bv(width, val) )
+        )
+      ) |
+      map_opt!(
+        simple_symbol, |sym: String| f.resolve_cons(& sym).map(
+          |cst| Spnd::len_mk( f.cst(cst), offset, sym.len() )
+        )
+      ) |
+      apply!(array_cst_parser, offset, f)
+    )
+  )
+}
+
+/// Parses a spanned array constant.
+fn array_cst_parser<'a, F>(
+  bytes: & 'a [u8], offset: usize, f: & F
+) -> IResult<& 'a [u8], Spnd<Cst>>
+where F: CstMaker<Bool, Cst> + CstMaker<Int, Cst> + CstMaker<Rat, Cst> +
+CstMaker<RealCst, Cst> + SortResolver {
+  let mut len = 0 ;
+  alt_complete!(
+    bytes,
+    map_opt!(
+      do_parse!(
+        len_set!(len < char '(') >>
+        opt!( len_add!(len < int space_comment) ) >>
+        len_add!(len < char '(') >>
+        opt!( len_add!(len < int space_comment) ) >>
+        len_add!(len < tag "as") >>
+        len_add!(len < int space_comment) >>
+        len_add!(len < tag "const") >>
+        len_add!(len < int space_comment) >>
+        typ: len_add!(
+          len < spn apply!(type_parser, offset + len, f)
+        ) >>
+        opt!( len_add!(len < int space_comment) ) >>
+        len_add!(len < char ')') >>
+        opt!( len_add!(len < int space_comment) ) >>
+        default: len_add!(
+          len < spn apply!(cst_parser, offset + len, f)
+        ) >>
+        opt!( len_add!(len < int space_comment) ) >>
+        len_add!(len < char ')') >> ( (typ, default) )
+      ), |(typ, default): (Type, Cst)| match typ {
+        Type::Array(idx, elm) => if * elm == default.typ() {
+          let array = RealCst::Array(
+            * idx, Box::new( default.get().clone() ), vec![]
+          ) ;
+          Some( Spnd::len_mk( f.cst(array), offset, len ) )
+        } else {
+          None
+        },
+        _ => None,
+      }
+    ) |
+    map_opt!(
+      do_parse!(
+        len_set!(len < char '(') >>
+        opt!( len_add!(len < int space_comment) ) >>
+        len_add!(len < tag "store") >>
+        len_add!(len < int space_comment) >>
+        array: len_add!(
+          len < spn apply!(array_cst_parser, offset + len, f)
+        ) >>
+        len_add!(len < int space_comment) >>
+        idx: len_add!(
+          len < spn apply!(cst_parser, offset + len, f)
+        ) >>
+        len_add!(len < int space_comment) >>
+        val: len_add!(
+          len < spn apply!(cst_parser, offset + len, f)
+        ) >>
+        opt!( len_add!(len < int space_comment) ) >>
+        len_add!(len < char ')') >> ( (array, idx, val) )
+      ), |(array, idx, val): (Cst, Cst, Cst)| match array.store(& idx, & val) {
+        Ok(array) => Some( Spnd::len_mk( f.cst(array), offset, len ) ),
+        Err(_) => None,
+      }
     )
   )
 }
@@ -657,6 +857,19 @@ named_attr!{
   ")
 }
 
+named_attr!{
+  #[doc = "Parses a simple symbol."],
+  pub simple_symbol<String>,
+  do_parse!(
+    head: simple_symbol_head >>
+    tail: opt!(
+      map_res!( simple_symbol_tail, ::std::str::from_utf8 )
+    ) >> (
+      format!("{}{}", head, tail.unwrap_or(""))
+    )
+  )
+}
+
 
 mk_parser!{
   #[doc = "Parses an operator."]
@@ -711,6 +924,30 @@ mk_parser!{
         tag!("/"),
         |b: Bytes| Spnd::len_mk(Operator::Div, offset, b.len())
       ) |
+      map!(
+        tag!("div"),
+        |b: Bytes| Spnd::len_mk(Operator::IntDiv, offset, b.len())
+      ) |
+      map!(
+        tag!("mod"),
+        |b: Bytes| Spnd::len_mk(Operator::Mod, offset, b.len())
+      ) |
+      map!(
+        tag!("abs"),
+        |b: Bytes| Spnd::len_mk(Operator::Abs, offset, b.len())
+      ) |
+      map!(
+        tag!("to_real"),
+        |b: Bytes| Spnd::len_mk(Operator::ToReal, offset, b.len())
+      ) |
+      map!(
+        tag!("to_int"),
+        |b: Bytes| Spnd::len_mk(Operator::ToInt, offset, b.len())
+      ) |
+      map!(
+        tag!("is_int"),
+        |b: Bytes| Spnd::len_mk(Operator::IsInt, offset, b.len())
+      ) |
       map!(
         tag!("<="),
         |b: Bytes| Spnd::len_mk(Operator::Le, offset, b.len())
@@ -726,6 +963,96 @@ mk_parser!{
       map!(
         tag!(">"),
         |b: Bytes| Spnd::len_mk(Operator::Gt, offset, b.len())
+      ) |
+      map!(
+        tag!("select"),
+        |b: Bytes| Spnd::len_mk(Operator::Select, offset, b.len())
+      ) |
+      map!(
+        tag!("store"),
+        |b: Bytes| Spnd::len_mk(Operator::Store, offset, b.len())
+      ) |
+      apply!(bv_operator_parser, offset)
+    )
+  }
+}
+
+mk_parser!{
+  #[doc = "Parses a bit-vector operator."]
+  pub fn bv_operator_parser(bytes, offset: usize) -> Spnd<Operator> {
+    let mut len = 0 ;
+    alt!(
+      bytes,
+      map_opt!(
+        is_a!("abcdefghijklmnopqrstuvwxyz"),
+        |b: Bytes| {
+          use term::Operator::* ;
+          let op = match ::std::str::from_utf8(b) {
+            Ok("concat") => Some(Concat),
+            Ok("bvnot") => Some(BvNot),
+            Ok("bvand") => Some(BvAnd),
+            Ok("bvor") => Some(BvOr),
+            Ok("bvxor") => Some(BvXor),
+            Ok("bvneg") => Some(BvNeg),
+            Ok("bvadd") => Some(BvAdd),
+            Ok("bvsub") => Some(BvSub),
+            Ok("bvmul") => Some(BvMul),
+            Ok("bvudiv") => Some(BvUDiv),
+            Ok("bvurem") => Some(BvURem),
+            Ok("bvsdiv") => Some(BvSDiv),
+            Ok("bvsrem") => Some(BvSRem),
+            Ok("bvshl") => Some(BvShl),
+            Ok("bvlshr") => Some(BvLShr),
+            Ok("bvashr") => Some(BvAShr),
+            Ok("bvult") => Some(BvULt),
+            Ok("bvule") => Some(BvULe),
+            Ok("bvugt") => Some(BvUGt),
+            Ok("bvuge") => Some(BvUGe),
+            Ok("bvslt") => Some(BvSLt),
+            Ok("bvsle") => Some(BvSLe),
+            Ok("bvsgt") => Some(BvSGt),
+            Ok("bvsge") => Some(BvSGe),
+            _ => None,
+          } ;
+          op.map( |op| Spnd::len_mk(op, offset, b.len()) )
+        }
+      ) |
+      do_parse!(
+        len_set!(len < char '(') >>
+        opt!( len_add!(len < int space_comment) ) >>
+        len_add!(len < char '_') >>
+        len_add!(len < int space_comment) >>
+        op: alt!(
+          do_parse!(
+            len_add!(len < tag "extract") >>
+            len_add!(len < int space_comment) >>
+            high: len_add!(
+              len < spn apply!(numeral_parser, offset + len)
+            ) >>
+            len_add!(len < int space_comment) >>
+            low: len_add!(
+              len < spn apply!(numeral_parser, offset + len)
+            ) >> ( Operator::Extract(high, low) )
+          ) |
+          do_parse!(
+            len_add!(len < tag "zero_extend") >>
+            len_add!(len < int space_comment) >>
+            n: len_add!(
+              len < spn apply!(numeral_parser, offset + len)
+            ) >> ( Operator::ZeroExtend(n) )
+          ) |
+          do_parse!(
+            len_add!(len < tag "sign_extend") >>
+            len_add!(len < int space_comment) >>
+            n: len_add!(
+              len < spn apply!(numeral_parser, offset + len)
+            ) >> ( Operator::SignExtend(n) )
+          )
+        ) >>
+        opt!( len_add!(len < int space_comment) ) >>
+        len_add!(len < char ')') >> (
+          Spnd::len_mk(op, offset, len)
+        )
       )
     )
   }
@@ -778,7 +1105,7 @@ mod typ3 {
   fn boo1() {
     use super::* ;
     try_parse_val!(
-      |bytes| type_parser(bytes, 0), b"Bool",
+      |bytes| type_parser(bytes, 0, & ()), b"Bool",
       Spnd::len_mk(::typ::Type::Bool, 0, 4)
     )
   }
@@ -786,7 +1113,7 @@ mod typ3 {
   fn int() {
     use super::* ;
     try_parse_val!(
-      |bytes| type_parser(bytes, 0), b"Int",
+      |bytes| type_parser(bytes, 0, & ()), b"Int",
       Spnd::len_mk(::typ::Type::Int, 0, 3)
     )
   }
@@ -794,10 +1121,38 @@ mod typ3 {
   fn rat() {
     use super::* ;
     try_parse_val!(
-      |bytes| type_parser(bytes, 0), b"Real",
+      |bytes| type_parser(bytes, 0, & ()), b"Real",
       Spnd::len_mk(::typ::Type::Rat, 0, 4)
     )
   }
+  #[test]
+  fn array() {
+    use super::* ;
+    use typ::Type ;
+    try_parse_val!(
+      |bytes| type_parser(bytes, 0, & ()), b"(Array Int Bool)",
+      Spnd::len_mk(Type::array(Type::Int, Type::Bool), 0, 16)
+    ) ;
+    try_parse_val!(
+      |bytes| type_parser(bytes, 0, & ()), b"( Array Int (Array Real Int) )",
+      Spnd::len_mk(
+        Type::array( Type::Int, Type::array(Type::Rat, Type::Int) ), 0, 30
+      )
+    )
+  }
+  #[test]
+  fn bitvec() {
+    use super::* ;
+    use typ::Type ;
+    try_parse_val!(
+      |bytes| type_parser(bytes, 0, & ()), b"(_ BitVec 32)",
+      Spnd::len_mk(Type::BitVec(32), 0, 13)
+    ) ;
+    match type_parser(& b"(_ BitVec 0)"[..], 0, & ()) {
+      ::nom::IResult::Error(_) => (),
+      other => panic!("unexpected result on parsing width 0: {:?}", other)
+    }
+  }
 }
 
 
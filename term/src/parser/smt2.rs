@@ -333,8 +333,8 @@ fn quantified_parser<'a>(
             }
           ) >>
           multispace >>
-          ty: apply!(type_parser, 0) >>
-          opt!(multispace) >> (sym, * ty)
+          ty: apply!(type_parser, 0, f) >>
+          opt!(multispace) >> (sym, ty.get().clone())
         ),
         char!(')')
       )
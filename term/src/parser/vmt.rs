@@ -196,7 +196,7 @@ impl TermAndDep {
       let var = factory.var(sym.clone()) ;
       let was_there = vars.remove(& var) ;
       if was_there.is_some() {
-        binds.push( (sym, * typ) ) ;
+        binds.push( (sym, typ.val.clone()) ) ;
         types.entry(typ.val).or_insert(vec![]).push(typ.span) ;
         ()
       } ;
@@ -458,7 +458,7 @@ pub fn quantified_parser<'a>(
           ) >>
           len_add!(len < spc cmt) >>
           ty: map!(
-            apply!(type_parser, offset + len),
+            apply!(type_parser, offset + len, f),
             |ty: Spnd<Type>| { len += ty.len() ; ty }
           ) >>
           len_add!(len < opt spc cmt) >> (sym, ty)
@@ -613,7 +613,7 @@ mk_parser!{
         )
       ) |
       map!(
-        apply!(type_parser, offset), |res| (
+        apply!(type_parser, offset, & ()), |res| (
           Spnd::to_span(res), "a type".into()
         )
       ) |
@@ -904,6 +904,247 @@ mod terms {
     ) ;
   }
 
+  #[test]
+  fn array() {
+    use super::* ;
+    let factory = Factory::mk() ;
+
+    let zero: Cst = factory.cst( Int::from_str("0").unwrap() ) ;
+    let mem: Term = factory.const_array(Type::Int, zero.clone()) ;
+    let mut s: Vec<u8> = vec![] ;
+    mem.to_vmt(& mut s).unwrap() ;
+    try_parse_term!(
+      |bytes, factory| term_parser(bytes, 9, factory), & factory, & s,
+      Spnd::len_mk(mem.clone(), 9, s.len())
+    ) ;
+
+    let nested: Term = factory.select(
+      factory.store(
+        mem,
+        factory.var( factory.sym("idx") ),
+        factory.cst( Int::from_str("7").unwrap() )
+      ),
+      factory.cst( Int::from_str("3").unwrap() )
+    ) ;
+    let mut s: Vec<u8> = vec![] ;
+    nested.to_vmt(& mut s).unwrap() ;
+    try_parse_term!(
+      |bytes, factory| term_parser(bytes, 9, factory), & factory, & s,
+      Spnd::len_mk(nested.clone(), 9, s.len())
+    ) ;
+
+    // Stores in a constant array are normalized.
+    let mem: Term = factory.const_array(Type::Int, zero) ;
+    try_parse!(
+      |bytes, factory| term_parser(bytes, 0, factory), & factory,
+      b"(store (store ((as const (Array Int Int)) 0) 1 1) 1 0)",
+      (s, res) -> { assert_eq!(res.term, mem) }
+    )
+  }
+
+  #[test]
+  fn bitvec() {
+    use super::* ;
+    use cst::RealCst ;
+    let factory = Factory::mk() ;
+
+    let five: Term = factory.cst(
+      factory.mk_rcst( RealCst::bv(8, Int::from_str("5").unwrap()) )
+    ) ;
+    try_parse_term!(
+      |bytes, factory| term_parser(bytes, 0, factory), & factory,
+      b"#b00000101", Spnd::len_mk(five.clone(), 0, 10)
+    ) ;
+    try_parse_term!(
+      |bytes, factory| term_parser(bytes, 0, factory), & factory,
+      b"#x05", Spnd::len_mk(five.clone(), 0, 4)
+    ) ;
+    try_parse_term!(
+      |bytes, factory| term_parser(bytes, 0, factory), & factory,
+      b"(_ bv5 8)", Spnd::len_mk(five.clone(), 0, 9)
+    ) ;
+
+    let term: Term = factory.op(
+      Operator::BvULe, vec![
+        factory.op(
+          Operator::Extract(3, 0), vec![ factory.var( factory.sym("bla") ) ]
+        ),
+        factory.op(
+          Operator::BvAdd, vec![
+            factory.op( Operator::Extract(7, 4), vec![ five.clone() ] ),
+            factory.op( Operator::ZeroExtend(2), vec![
+              factory.cst(
+                factory.mk_rcst( RealCst::bv(2, Int::from_str("3").unwrap()) )
+              )
+            ] ),
+          ]
+        ),
+      ]
+    ) ;
+    let mut s: Vec<u8> = vec![] ;
+    term.to_vmt(& mut s).unwrap() ;
+    try_parse_term!(
+      |bytes, factory| term_parser(bytes, 9, factory), & factory, & s,
+      Spnd::len_mk(term.clone(), 9, s.len())
+    )
+  }
+
+  #[test]
+  fn bitvec_eval() {
+    use super::* ;
+    use cst::RealCst ;
+    let factory = Factory::mk() ;
+    let bv = |val: & str| factory.mk_rcst(
+      RealCst::bv( 4, Int::from_str(val).unwrap() )
+    ) ;
+    let eval = |op: Operator, args: Vec<Cst>| op.eval(
+      & factory, args
+    ).unwrap() ;
+    let (tru, fls): (Cst, Cst) = ( factory.cst(true), factory.cst(false) ) ;
+
+    // Overflows wrap around.
+    assert_eq!( eval( Operator::BvAdd, vec![ bv("15"), bv("1") ] ), bv("0") ) ;
+    assert_eq!( eval( Operator::BvNeg, vec![ bv("1") ] ), bv("15") ) ;
+    // Unsigned and signed comparisons differ.
+    assert_eq!(
+      eval( Operator::BvULt, vec![ bv("1"), bv("15") ] ), tru
+    ) ;
+    assert_eq!(
+      eval( Operator::BvSLt, vec![ bv("1"), bv("15") ] ), fls
+    ) ;
+    // Division by zero.
+    assert_eq!( eval( Operator::BvUDiv, vec![ bv("7"), bv("0") ] ), bv("15") ) ;
+    assert_eq!( eval( Operator::BvURem, vec![ bv("7"), bv("0") ] ), bv("7") ) ;
+    // Signed division rounds towards zero.
+    assert_eq!( eval( Operator::BvSDiv, vec![ bv("9"), bv("2") ] ), bv("13") ) ;
+    assert_eq!( eval( Operator::BvAShr, vec![ bv("8"), bv("1") ] ), bv("12") ) ;
+    assert_eq!(
+      eval( Operator::Concat, vec![ bv("1"), bv("2") ] ),
+      factory.mk_rcst( RealCst::bv( 8, Int::from_str("18").unwrap() ) )
+    )
+  }
+
+  #[test]
+  fn int_ops() {
+    use super::* ;
+    let factory = Factory::mk() ;
+    let int = |val: & str| -> Cst {
+      factory.cst( Int::from_str(val).unwrap() )
+    } ;
+    let rat = |num: & str, den: & str| -> Cst {
+      factory.cst(
+        Rat::new( Int::from_str(num).unwrap(), Int::from_str(den).unwrap() )
+      )
+    } ;
+    let eval = |op: Operator, args: Vec<Cst>| op.eval(
+      & factory, args
+    ).unwrap() ;
+    let fls: Cst = factory.cst(false) ;
+
+    // The remainder is always positive.
+    assert_eq!(
+      eval( Operator::IntDiv, vec![ int("7"), int("2") ] ), int("3")
+    ) ;
+    assert_eq!( eval( Operator::Mod, vec![ int("7"), int("2") ] ), int("1") ) ;
+    assert_eq!(
+      eval( Operator::IntDiv, vec![ int("-7"), int("2") ] ), int("-4")
+    ) ;
+    assert_eq!(
+      eval( Operator::Mod, vec![ int("-7"), int("2") ] ), int("1")
+    ) ;
+    assert_eq!(
+      eval( Operator::IntDiv, vec![ int("7"), int("-2") ] ), int("-3")
+    ) ;
+    assert_eq!( eval( Operator::Mod, vec![ int("7"), int("-2") ] ), int("1") ) ;
+    assert!(
+      Operator::Mod.eval(& factory, vec![ int("7"), int("0") ]).is_err()
+    ) ;
+    assert_eq!( eval( Operator::Abs, vec![ int("-3") ] ), int("3") ) ;
+    assert_eq!( eval( Operator::ToReal, vec![ int("3") ] ), rat("3", "1") ) ;
+    assert_eq!( eval( Operator::ToInt, vec![ rat("-3", "2") ] ), int("-2") ) ;
+    assert_eq!( eval( Operator::IsInt, vec![ rat("3", "2") ] ), fls ) ;
+
+    let x: Term = factory.var( factory.sym("bla") ) ;
+    let term: Term = factory.op(
+      Operator::IsInt, vec![
+        factory.op(
+          Operator::ToReal, vec![
+            factory.op(
+              Operator::Mod, vec![
+                factory.op(
+                  Operator::IntDiv, vec![
+                    factory.op( Operator::Abs, vec![ x.clone() ] ),
+                    factory.cst( Int::from_str("3").unwrap() ),
+                  ]
+                ),
+                factory.op(
+                  Operator::ToInt, vec![
+                    factory.op( Operator::ToReal, vec![ x ] )
+                  ]
+                ),
+              ]
+            )
+          ]
+        )
+      ]
+    ) ;
+    let mut s: Vec<u8> = vec![] ;
+    term.to_vmt(& mut s).unwrap() ;
+    try_parse_term!(
+      |bytes, factory| term_parser(bytes, 9, factory), & factory, & s,
+      Spnd::len_mk(term.clone(), 9, s.len())
+    )
+  }
+
+  #[test]
+  fn enumerated() {
+    use super::* ;
+    use cst::RealCst ;
+    let factory = Factory::mk() ;
+    let typ = factory.declare_enum(
+      "Mode".to_string(), vec![
+        "Off".to_string(), "Idle".to_string(), "Run".to_string()
+      ]
+    ).unwrap() ;
+    let idle = match typ {
+      Type::Enum(ref e) => factory.mk_rcst( RealCst::Enum(e.clone(), 1) ),
+      _ => panic!("expected enumerated type, got {}", typ),
+    } ;
+    assert_eq!( format!("{}", idle), "Idle" ) ;
+
+    try_parse_term!(
+      |bytes, factory| term_parser(bytes, 0, factory), & factory,
+      b"Idle", Spnd::len_mk(factory.cst(idle.clone()), 0, 4)
+    ) ;
+    let term: Term = factory.eq(
+      vec![
+        factory.svar( factory.sym("mode"), State::Curr ),
+        factory.cst(idle.clone())
+      ]
+    ) ;
+    try_parse_term!(
+      |bytes, factory| term_parser(bytes, 0, factory), & factory,
+      b"(= (_ curr mode) Idle)", Spnd::len_mk(term.clone(), 0, 22)
+    ) ;
+
+    // Redeclarations and constructor clashes are rejected.
+    assert!(
+      factory.declare_enum(
+        "Mode".to_string(), vec![ "Stop".to_string() ]
+      ).is_err()
+    ) ;
+    assert!(
+      factory.declare_enum(
+        "Other".to_string(), vec![ "Run".to_string() ]
+      ).is_err()
+    ) ;
+    assert!(
+      factory.declare_enum(
+        "Dup".to_string(), vec![ "A".to_string(), "A".to_string() ]
+      ).is_err()
+    )
+  }
+
   #[test]
   fn empty() {
     let factory = Factory::mk() ;
@@ -11,6 +11,7 @@
 
 use std::io ;
 use std::fmt ;
+use std::collections::HashMap ;
 
 use errors::* ;
 
@@ -51,6 +52,18 @@ pub enum Operator {
   Mul,
   /// Division operator.
   Div,
+  /// Integer division operator.
+  IntDiv,
+  /// Integer modulo operator.
+  Mod,
+  /// Integer absolute value operator.
+  Abs,
+  /// Int to Real conversion.
+  ToReal,
+  /// Real to Int conversion, rounding down.
+  ToInt,
+  /// True if a real is an integer.
+  IsInt,
   /// Less or equal operator.
   Le,
   /// Greater or equal operator.
@@ -59,6 +72,65 @@ pub enum Operator {
   Lt,
   /// Greater than operator.
   Gt,
+  /// Array selection operator.
+  Select,
+  /// Array store operator.
+  Store,
+  /// Bit-vector concatenation.
+  Concat,
+  /// Bit-vector extraction of the bits from the first index down to the
+  /// second one, inclusive.
+  Extract(usize, usize),
+  /// Bit-vector extension with some zeros.
+  ZeroExtend(usize),
+  /// Bit-vector extension with some copies of the sign bit.
+  SignExtend(usize),
+  /// Bit-vector bitwise negation.
+  BvNot,
+  /// Bit-vector bitwise conjunction.
+  BvAnd,
+  /// Bit-vector bitwise disjunction.
+  BvOr,
+  /// Bit-vector bitwise exclusive disjunction.
+  BvXor,
+  /// Bit-vector two's complement negation.
+  BvNeg,
+  /// Bit-vector addition.
+  BvAdd,
+  /// Bit-vector subtraction.
+  BvSub,
+  /// Bit-vector multiplication.
+  BvMul,
+  /// Bit-vector unsigned division.
+  BvUDiv,
+  /// Bit-vector unsigned remainder.
+  BvURem,
+  /// Bit-vector signed division.
+  BvSDiv,
+  /// Bit-vector signed remainder, the sign follows the dividend.
+  BvSRem,
+  /// Bit-vector shift left.
+  BvShl,
+  /// Bit-vector logical shift right.
+  BvLShr,
+  /// Bit-vector arithmetic shift right.
+  BvAShr,
+  /// Bit-vector unsigned less than.
+  BvULt,
+  /// Bit-vector unsigned less or equal.
+  BvULe,
+  /// Bit-vector unsigned greater than.
+  BvUGt,
+  /// Bit-vector unsigned greater or equal.
+  BvUGe,
+  /// Bit-vector signed less than.
+  BvSLt,
+  /// Bit-vector signed less or equal.
+  BvSLe,
+  /// Bit-vector signed greater than.
+  BvSGt,
+  /// Bit-vector signed greater or equal.
+  BvSGe,
 }
 
 impl Operator {
@@ -68,15 +140,21 @@ impl Operator {
     use self::Operator::* ;
     match * self {
       // Unary.
-      Not => Some(1u8),
+      Not | Abs | ToReal | ToInt | IsInt |
+      Extract(_, _) | ZeroExtend(_) | SignExtend(_) |
+      BvNot | BvNeg => Some(1u8),
       // Binary.
-      Div | Le | Ge | Lt | Gt => Some(2),
+      Div | IntDiv | Mod | Le | Ge | Lt | Gt | Select |
+      Concat | BvSub | BvUDiv | BvURem | BvSDiv | BvSRem |
+      BvShl | BvLShr | BvAShr |
+      BvULt | BvULe | BvUGt | BvUGe | BvSLt | BvSLe | BvSGt | BvSGe => Some(2),
       // Ternary.
-      Ite => Some(3),
+      Ite | Store => Some(3),
       // N-ary.
       Eq | And | Or | Impl | Xor |
       Distinct |
-      Add | Sub | Mul => None,
+      Add | Sub | Mul |
+      BvAnd | BvOr | BvXor | BvAdd | BvMul => None,
     }
   }
 
@@ -234,6 +312,54 @@ impl Operator {
         }
       },
 
+      IntDiv | Mod => {
+        if sig.len() != 2 {
+          return Err( (
+            None,
+            format!("operator {} expects 2 arguments, got {}", self, sig.len())
+          ) )
+        } ;
+        let mut cpt = 0 ;
+        for typ in sig.iter() {
+          if * typ != Type::Int {
+            return Err( (
+              Some( vec![cpt] ),
+              format!(
+                "argument {} of operator {}: expected Int, got {}",
+                cpt + 1, self, typ
+              )
+            ) )
+          } ;
+          cpt = cpt + 1 ;
+        } ;
+        Ok( Type::Int )
+      },
+
+      Abs | ToReal | ToInt | IsInt => {
+        if sig.len() != 1 {
+          return Err( (
+            None,
+            format!("operator {} expects 1 argument, got {}", self, sig.len())
+          ) )
+        } ;
+        let (expected, res) = match * self {
+          Abs => (Type::Int, Type::Int),
+          ToReal => (Type::Int, Type::Rat),
+          ToInt => (Type::Rat, Type::Int),
+          _ => (Type::Rat, Type::Bool),
+        } ;
+        if sig[0] != expected {
+          return Err( (
+            Some(vec![0]),
+            format!(
+              "first argument of {} should have sort {}, got {}",
+              self, expected, sig[0]
+            )
+          ) )
+        } ;
+        Ok(res)
+      },
+
       Le | Ge | Lt | Gt => {
         let mut sig = sig.iter() ;
         if let Some(first) = sig.next() {
@@ -271,6 +397,152 @@ impl Operator {
         }
       },
 
+      Select => {
+        if sig.len() != 2 {
+          return Err( (
+            None,
+            format!("operator select expects 2 arguments, got {}", sig.len())
+          ) )
+        } ;
+        match sig[0] {
+          Type::Array(ref idx, ref elm) => if * * idx != sig[1] {
+            Err( (
+              Some(vec![1]),
+              format!(
+                "second argument of select should have sort {}, got {}",
+                idx, sig[1]
+              )
+            ) )
+          } else {
+            Ok( (* * elm).clone() )
+          },
+          _ => Err( (
+            Some(vec![0]),
+            format!(
+              "first argument of select should be an array, got {}", sig[0]
+            )
+          ) ),
+        }
+      },
+
+      Store => {
+        if sig.len() != 3 {
+          return Err( (
+            None,
+            format!("operator store expects 3 arguments, got {}", sig.len())
+          ) )
+        } ;
+        match sig[0] {
+          Type::Array(ref idx, ref elm) => if * * idx != sig[1] {
+            Err( (
+              Some(vec![1]),
+              format!(
+                "second argument of store should have sort {}, got {}",
+                idx, sig[1]
+              )
+            ) )
+          } else if * * elm != sig[2] {
+            Err( (
+              Some(vec![2]),
+              format!(
+                "third argument of store should have sort {}, got {}",
+                elm, sig[2]
+              )
+            ) )
+          } else {
+            Ok( sig[0].clone() )
+          },
+          _ => Err( (
+            Some(vec![0]),
+            format!(
+              "first argument of store should be an array, got {}", sig[0]
+            )
+          ) ),
+        }
+      },
+
+      Concat | Extract(_, _) | ZeroExtend(_) | SignExtend(_) |
+      BvNot | BvAnd | BvOr | BvXor |
+      BvNeg | BvAdd | BvSub | BvMul |
+      BvUDiv | BvURem | BvSDiv | BvSRem |
+      BvShl | BvLShr | BvAShr |
+      BvULt | BvULe | BvUGt | BvUGe |
+      BvSLt | BvSLe | BvSGt | BvSGe => self.bv_type_check(sig),
+
+    }
+  }
+
+  /// Type checking for bit-vector operators.
+  fn bv_type_check(& self, sig: & [Type]) -> Result<
+    Type, (Option<Vec<usize>>, String)
+  > {
+    use Operator::* ;
+    let mut widths = Vec::with_capacity( sig.len() ) ;
+    let mut cpt = 0 ;
+    for typ in sig.iter() {
+      match * typ {
+        Type::BitVec(width) => widths.push(width),
+        _ => return Err( (
+          Some( vec![cpt] ),
+          format!(
+            "argument {} of operator {}: expected a bit-vector, got {}",
+            cpt + 1, self, typ
+          )
+        ) ),
+      }
+      cpt = cpt + 1
+    }
+    if let Some(arity) = self.arity() {
+      if widths.len() != arity as usize {
+        return Err( (
+          None,
+          format!(
+            "operator {} expects {} argument(s), got {}",
+            self, arity, widths.len()
+          )
+        ) )
+      }
+    } else if widths.is_empty() {
+      return Err( (
+        None,
+        format!("operator {} is applied to nothing", self)
+      ) )
+    }
+    match * self {
+      Concat => Ok( Type::BitVec(widths[0] + widths[1]) ),
+      Extract(high, low) => if low <= high && high < widths[0] {
+        Ok( Type::BitVec(high - low + 1) )
+      } else {
+        Err( (
+          Some( vec![0] ),
+          format!(
+            "cannot extract bits {} to {} of a bit-vector of width {}",
+            high, low, widths[0]
+          )
+        ) )
+      },
+      ZeroExtend(n) | SignExtend(n) => Ok( Type::BitVec(widths[0] + n) ),
+      _ => {
+        let mut cpt = 1 ;
+        for width in widths[1..].iter() {
+          if * width != widths[0] {
+            return Err( (
+              Some( vec![cpt] ),
+              format!(
+                "argument {} as incompatible type \
+                  expected {}, got {}",
+                cpt + 1, sig[0], sig[cpt]
+              )
+            ) )
+          }
+          cpt = cpt + 1
+        }
+        match * self {
+          BvULt | BvULe | BvUGt | BvUGe |
+          BvSLt | BvSLe | BvSGt | BvSGe => Ok( Type::Bool ),
+          _ => Ok( Type::BitVec(widths[0]) ),
+        }
+      },
     }
   }
 
@@ -509,6 +781,52 @@ impl Operator {
         }
       },
 
+      IntDiv | Mod => if args.len() == 2 {
+        let res = match * self {
+          IntDiv => args[0].int_div(& args[1]),
+          _ => args[0].int_mod(& args[1]),
+        } ;
+        match res {
+          Ok(cst) => Ok( factory.mk_rcst(cst) ),
+          // Only a zero divisor can be an illegal integer.
+          Err(ref cst) if cst.typ() == Type::Int => Err(
+            format!(
+              "division by zero in `({} {} {})`", self, args[0], args[1]
+            ).into()
+          ),
+          Err(cst) => Err(
+            ErrorKind::OpTypeError(
+              * self, cst.typ(), Type::Int, Some(
+                format!("(found `{}`)", cst)
+              )
+            ).into()
+          ),
+        }
+      } else {
+          Err( ErrorKind::OpArityError(* self, args.len(), "2").into() )
+      },
+
+      Abs | ToReal | ToInt | IsInt => if args.len() == 1 {
+        let (res, expected) = match * self {
+          Abs => ( args[0].abs(), Type::Int ),
+          ToReal => ( args[0].to_real(), Type::Int ),
+          ToInt => ( args[0].to_int(), Type::Rat ),
+          _ => ( args[0].is_int(), Type::Rat ),
+        } ;
+        match res {
+          Ok(cst) => Ok( factory.mk_rcst(cst) ),
+          Err(cst) => Err(
+            ErrorKind::OpTypeError(
+              * self, cst.typ(), expected, Some(
+                format!("(found `{}`)", cst)
+              )
+            ).into()
+          ),
+        }
+      } else {
+          Err( ErrorKind::OpArityError(* self, args.len(), "1").into() )
+      },
+
       Le => if args.len() == 2 {
         match * args[0].get() {
           RCst::Int(ref lhs) => match * args[1].get() {
@@ -645,7 +963,198 @@ impl Operator {
           Err( ErrorKind::OpArityError(Gt, args.len(), "2").into() )
       },
 
+      Select => if args.len() == 2 {
+        match args[0].select(& args[1]) {
+          Ok(cst) => Ok( factory.mk_rcst(cst) ),
+          Err(_) => Err(
+            format!(
+              "illegal selection of `{}` in `{}`", args[1], args[0]
+            ).into()
+          ),
+        }
+      } else {
+          Err( ErrorKind::OpArityError(Select, args.len(), "2").into() )
+      },
+
+      Store => if args.len() == 3 {
+        match args[0].store(& args[1], & args[2]) {
+          Ok(cst) => Ok( factory.mk_rcst(cst) ),
+          Err(_) => Err(
+            format!(
+              "illegal store of `{}` at `{}` in `{}`", args[2], args[1], args[0]
+            ).into()
+          ),
+        }
+      } else {
+          Err( ErrorKind::OpArityError(Store, args.len(), "3").into() )
+      },
+
+      Concat | Extract(_, _) | ZeroExtend(_) | SignExtend(_) |
+      BvNot | BvAnd | BvOr | BvXor |
+      BvNeg | BvAdd | BvSub | BvMul |
+      BvUDiv | BvURem | BvSDiv | BvSRem |
+      BvShl | BvLShr | BvAShr |
+      BvULt | BvULe | BvUGt | BvUGe |
+      BvSLt | BvSLe | BvSGt | BvSGe => self.bv_eval(factory, args),
+
+    }
+  }
+
+  /// Evaluates a bit-vector operator given some arguments.
+  ///
+  /// Division by zero follows the SMT-LIB semantics: `bvudiv` yields all
+  /// ones and `bvurem` yields the dividend.
+  fn bv_eval(& self, factory: & ::Factory, args: Vec<Cst>) -> Res<Cst> {
+    use Operator::* ;
+    use ::real_term::Cst as RCst ;
+    use typ::Int ;
+    use num::Integer ;
+    use num::bigint::Sign ;
+    use num::traits::{ Zero, One, ToPrimitive } ;
+
+    let mut vals = Vec::with_capacity( args.len() ) ;
+    let mut cpt = 0 ;
+    for arg in args.iter() {
+      match * arg.get() {
+        RCst::BitVec(width, ref val) => vals.push( (width, val.clone()) ),
+        ref arg => return Err(
+          format!(
+            "argument {} of operator `{}` is not a bit-vector (found `{}`)",
+            cpt + 1, self, arg
+          ).into()
+        ),
+      }
+      cpt = cpt + 1
+    }
+
+    match self.arity() {
+      Some(arity) => if vals.len() != arity as usize {
+        return Err(
+          ErrorKind::OpArityError(
+            * self, vals.len(), if arity == 1 { "1" } else { "2" }
+          ).into()
+        )
+      },
+      None => if vals.is_empty() {
+        return Err( ErrorKind::OpArityError(* self, 0, "> 0").into() )
+      },
+    }
+
+    let width = vals[0].0 ;
+    match * self {
+      Concat | Extract(_, _) | ZeroExtend(_) | SignExtend(_) => (),
+      _ => for & (w, _) in vals[1..].iter() {
+        if w != width {
+          return Err(
+            format!(
+              "width mismatch on operator `{}`, {} and {}", self, width, w
+            ).into()
+          )
+        }
+      },
     }
+
+    let bound = Int::one() << width ;
+    let signed = |val: & Int| if val.clone() << 1 < bound {
+      val.clone()
+    } else {
+      val - & bound
+    } ;
+
+    let res = match * self {
+      Concat => RCst::BitVec(
+        width + vals[1].0, (vals[0].1.clone() << vals[1].0) + & vals[1].1
+      ),
+      Extract(high, low) => if low <= high && high < width {
+        RCst::bv( high - low + 1, vals[0].1.clone() >> low )
+      } else {
+        return Err(
+          format!(
+            "cannot extract bits {} to {} of a bit-vector of width {}",
+            high, low, width
+          ).into()
+        )
+      },
+      ZeroExtend(n) => RCst::BitVec( width + n, vals[0].1.clone() ),
+      SignExtend(n) => RCst::bv( width + n, signed(& vals[0].1) ),
+      BvNot => RCst::bv( width, - Int::one() - & vals[0].1 ),
+      BvNeg => RCst::bv( width, - & vals[0].1 ),
+      BvAnd | BvOr | BvXor => {
+        let mut vals = vals.into_iter().map(
+          |(_, val)| val.to_biguint().unwrap()
+        ) ;
+        let mut res = vals.next().unwrap() ;
+        for val in vals {
+          res = match * self {
+            BvAnd => res & val,
+            BvOr => res | val,
+            _ => res ^ val,
+          }
+        }
+        RCst::BitVec( width, Int::from_biguint(Sign::Plus, res) )
+      },
+      BvAdd | BvMul => {
+        let mut res = vals[0].1.clone() ;
+        for & (_, ref val) in vals[1..].iter() {
+          res = if * self == BvAdd { res + val } else { res * val }
+        }
+        RCst::bv(width, res)
+      },
+      BvSub => RCst::bv( width, & vals[0].1 - & vals[1].1 ),
+      BvUDiv => if vals[1].1.is_zero() {
+        RCst::bv( width, - Int::one() )
+      } else {
+        RCst::BitVec( width, & vals[0].1 / & vals[1].1 )
+      },
+      BvURem => if vals[1].1.is_zero() {
+        RCst::BitVec( width, vals[0].1.clone() )
+      } else {
+        RCst::BitVec( width, & vals[0].1 % & vals[1].1 )
+      },
+      BvSDiv => {
+        let (lhs, rhs) = ( signed(& vals[0].1), signed(& vals[1].1) ) ;
+        if rhs.is_zero() {
+          RCst::bv(
+            width, if lhs < Int::zero() { Int::one() } else { - Int::one() }
+          )
+        } else {
+          RCst::bv(width, lhs / rhs)
+        }
+      },
+      BvSRem => {
+        let (lhs, rhs) = ( signed(& vals[0].1), signed(& vals[1].1) ) ;
+        if rhs.is_zero() {
+          RCst::BitVec( width, vals[0].1.clone() )
+        } else {
+          RCst::bv(width, lhs % rhs)
+        }
+      },
+      BvShl | BvLShr | BvAShr => {
+        // Shifting by `width` or more is the same as shifting by `width`.
+        let shift = match vals[1].1.to_usize() {
+          Some(shift) if shift < width => shift,
+          _ => width,
+        } ;
+        match * self {
+          BvShl => RCst::bv( width, vals[0].1.clone() << shift ),
+          BvLShr => RCst::BitVec( width, vals[0].1.clone() >> shift ),
+          _ => RCst::bv(
+            width, signed(& vals[0].1).div_floor( & (Int::one() << shift) )
+          ),
+        }
+      },
+      BvULt => RCst::Bool( vals[0].1 < vals[1].1 ),
+      BvULe => RCst::Bool( vals[0].1 <= vals[1].1 ),
+      BvUGt => RCst::Bool( vals[0].1 > vals[1].1 ),
+      BvUGe => RCst::Bool( vals[0].1 >= vals[1].1 ),
+      BvSLt => RCst::Bool( signed(& vals[0].1) < signed(& vals[1].1) ),
+      BvSLe => RCst::Bool( signed(& vals[0].1) <= signed(& vals[1].1) ),
+      BvSGt => RCst::Bool( signed(& vals[0].1) > signed(& vals[1].1) ),
+      BvSGe => RCst::Bool( signed(& vals[0].1) >= signed(& vals[1].1) ),
+      _ => unreachable!(),
+    } ;
+
+    Ok( factory.mk_rcst(res) )
   }
 }
 
@@ -662,6 +1171,19 @@ impl Writable for Operator {
   fn write(
     & self, writer: & mut io::Write
   ) -> io::Result<()> {
+    // Indexed operators.
+    match * self {
+      Operator::Extract(high, low) => return write!(
+        writer, "(_ extract {} {})", high, low
+      ),
+      Operator::ZeroExtend(n) => return write!(
+        writer, "(_ zero_extend {})", n
+      ),
+      Operator::SignExtend(n) => return write!(
+        writer, "(_ sign_extend {})", n
+      ),
+      _ => (),
+    }
     write!(
       writer,
       "{}",
@@ -678,10 +1200,45 @@ impl Writable for Operator {
         Operator::Sub => "-",
         Operator::Mul => "*",
         Operator::Div => "/",
+        Operator::IntDiv => "div",
+        Operator::Mod => "mod",
+        Operator::Abs => "abs",
+        Operator::ToReal => "to_real",
+        Operator::ToInt => "to_int",
+        Operator::IsInt => "is_int",
         Operator::Le => "<=",
         Operator::Ge => ">=",
         Operator::Lt => "<",
         Operator::Gt => ">",
+        Operator::Select => "select",
+        Operator::Store => "store",
+        Operator::Concat => "concat",
+        Operator::BvNot => "bvnot",
+        Operator::BvAnd => "bvand",
+        Operator::BvOr => "bvor",
+        Operator::BvXor => "bvxor",
+        Operator::BvNeg => "bvneg",
+        Operator::BvAdd => "bvadd",
+        Operator::BvSub => "bvsub",
+        Operator::BvMul => "bvmul",
+        Operator::BvUDiv => "bvudiv",
+        Operator::BvURem => "bvurem",
+        Operator::BvSDiv => "bvsdiv",
+        Operator::BvSRem => "bvsrem",
+        Operator::BvShl => "bvshl",
+        Operator::BvLShr => "bvlshr",
+        Operator::BvAShr => "bvashr",
+        Operator::BvULt => "bvult",
+        Operator::BvULe => "bvule",
+        Operator::BvUGt => "bvugt",
+        Operator::BvUGe => "bvuge",
+        Operator::BvSLt => "bvslt",
+        Operator::BvSLe => "bvsle",
+        Operator::BvSGt => "bvsgt",
+        Operator::BvSGe => "bvsge",
+        Operator::Extract(_, _) |
+        Operator::ZeroExtend(_) |
+        Operator::SignExtend(_) => unreachable!(),
       }
     )
   }
@@ -708,6 +1265,11 @@ pub enum RealTerm {
   App(Sym, Vec<Term>),
 }
 impl RealTerm {
+  /// Returns true iff the term is a constant.
+  #[inline]
+  pub fn is_cst(& self) -> bool {
+    if let RealTerm::C(_) = * self { true } else { false }
+  }
   /// Returns true iff the term is the constant `true`.
   #[inline]
   pub fn is_true(& self) -> bool {
@@ -1069,6 +1631,19 @@ pub fn debump<F: Factory>(f: & F, term: Term) -> Res<Term> {
   )
 }
 
+pub fn subst<F: Factory>(
+  f: & F, term: Term, map: & HashMap<Term, Term>
+) -> Res<Term> {
+  zip::var_map(
+    f,
+    |_, t| match * t.get() {
+      V(_) => Ok( map.get(t).cloned() ),
+      _ => Ok(None),
+    },
+    term
+  )
+}
+
 
 
 
@@ -42,7 +42,7 @@ impl TmpTerm {
     while let Some(term) = stack.pop() {
       match * term {
         Sym(ref id, ref ty) => {
-          let _ = res.insert( (id.clone(), * ty) ) ;
+          let _ = res.insert( (id.clone(), ty.clone()) ) ;
         },
         Trm(_) => (),
         Nod(_, ref kids) => for kid in kids.iter() {
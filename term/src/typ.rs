@@ -11,42 +11,67 @@
 
 use std::io ;
 use std::fmt ;
+use std::sync::Arc ;
 
 use base::{ Writable } ;
 use real_term::Cst ;
 
-/// A primitive type.
-#[derive(Clone,Copy,Debug,PartialEq,Eq,PartialOrd,Ord,Hash)]
+/// A type.
+#[derive(Clone,Debug,PartialEq,Eq,PartialOrd,Ord,Hash)]
 pub enum Type {
   /// Bool type.
   Bool,
   /// Int type.
   Int,
   /// Rat type.
-  Rat
+  Rat,
+  /// Array type, from an index type to an element type.
+  Array(Box<Type>, Box<Type>),
+  /// Bit-vector type of some width, always positive.
+  BitVec(usize),
+  /// Enumerated sort, declared with `declare-enum`.
+  Enum(Arc<Enum>),
 }
 
 impl fmt::Display for Type {
   fn fmt(& self, fmt: & mut fmt::Formatter) -> fmt::Result {
-    write!(
-      fmt, "{}",
-      match * self {
-        Type::Bool => "Bool",
-        Type::Int => "Int",
-        Type::Rat => "Real",
-      }
-    )
+    match * self {
+      Type::Array(ref idx, ref elm) => write!(
+        fmt, "(Array {} {})", idx, elm
+      ),
+      Type::BitVec(width) => write!(fmt, "(_ BitVec {})", width),
+      Type::Enum(ref e) => write!(fmt, "{}", e.name()),
+      _ => write!(fmt, "{}", self.to_str()),
+    }
   }
 }
 
 impl Type {
-  /// String representation of a type.
+  /// Creates an array type.
+  #[inline]
+  pub fn array(idx: Type, elm: Type) -> Self {
+    Type::Array( Box::new(idx), Box::new(elm) )
+  }
+  /// String representation of a type. Only the head of the type for arrays
+  /// and bit-vectors, and `Enum` for enumerated sorts. Use `Display` for the
+  /// complete representation.
   #[inline]
   pub fn to_str(& self) -> & 'static str {
     match * self {
       Type::Bool => "Bool",
       Type::Int => "Int",
       Type::Rat => "Real",
+      Type::Array(_, _) => "Array",
+      Type::BitVec(_) => "BitVec",
+      Type::Enum(_) => "Enum",
+    }
+  }
+  /// True if the type is `Int` or `Real`.
+  #[inline]
+  pub fn is_arith(& self) -> bool {
+    match * self {
+      Type::Int | Type::Rat => true,
+      _ => false,
     }
   }
   /// Default value of a type.
@@ -63,14 +88,64 @@ impl Type {
           Int::parse_bytes(b"1", 10).unwrap(),
         )
       ),
+      Type::Array(ref idx, ref elm) => Cst::Array(
+        (* * idx).clone(), Box::new( elm.default() ), vec![]
+      ),
+      Type::BitVec(width) => Cst::BitVec(
+        width, Int::parse_bytes(b"0", 10).unwrap()
+      ),
+      Type::Enum(ref e) => Cst::Enum(e.clone(), 0),
     }
   }
 }
 
+/// An enumerated sort: a name and its constructors, in declaration order.
+#[derive(Clone,Debug,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct Enum {
+  /// Name of the sort.
+  name: String,
+  /// Constructors of the sort, never empty.
+  cons: Vec<String>,
+}
+
+impl Enum {
+  /// Creates an enumerated sort. There should be at least one constructor
+  /// and no constructor should appear twice.
+  #[inline]
+  pub fn mk(name: String, cons: Vec<String>) -> Self {
+    debug_assert!( ! cons.is_empty() ) ;
+    Enum { name: name, cons: cons }
+  }
+  /// Name of the sort.
+  #[inline]
+  pub fn name(& self) -> & str { & self.name }
+  /// Constructors of the sort.
+  #[inline]
+  pub fn cons(& self) -> & [String] { & self.cons }
+  /// Index of a constructor.
+  #[inline]
+  pub fn index_of(& self, cons: & str) -> Option<usize> {
+    self.cons.iter().position(|c| c == cons)
+  }
+}
+
+/// Resolves the names of user-declared sorts and of their constructors.
+pub trait SortResolver {
+  /// The sort corresponding to a name, if any.
+  fn resolve_sort(& self, name: & str) -> Option<Type> ;
+  /// The constant corresponding to a constructor, if any.
+  fn resolve_cons(& self, name: & str) -> Option<Cst> ;
+}
+/// No user-declared sorts.
+impl SortResolver for () {
+  fn resolve_sort(& self, _: & str) -> Option<Type> { None }
+  fn resolve_cons(& self, _: & str) -> Option<Cst> { None }
+}
+
 impl Writable for Type {
   #[inline]
   fn write(& self, writer: & mut io::Write) -> io::Result<()> {
-    write!(writer, "{}", self.to_str())
+    write!(writer, "{}", self)
   }
 }
 
@@ -0,0 +1,87 @@
+// Copyright 2016 Adrien Champion. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Tests running the `kino` binary: exit codes and JSON output.
+
+use std::process::{ Command, Output } ;
+
+/// Runs kino on a file of `rsc/simple` (given *without* the `.vmt`
+/// extension) with some options.
+fn run(file: & str, options: & [& str]) -> Output {
+  Command::new( env!("CARGO_BIN_EXE_kino") ).args(options).arg(
+    format!("rsc/simple/{}.vmt", file)
+  ).output().expect("could not run kino")
+}
+
+/// JSON records of an output, one per line.
+fn records(output: & Output) -> Vec<String> {
+  String::from_utf8( output.stdout.clone() ).expect(
+    "output is not utf8"
+  ).lines().map( |line| line.to_string() ).collect()
+}
+
+#[test]
+fn exit_safe() {
+  assert_eq!( run("simple", & []).status.code(), Some(0) )
+}
+
+#[test]
+fn exit_unsafe() {
+  assert_eq!( run("simple_false", & []).status.code(), Some(10) )
+}
+
+#[test]
+fn exit_unknown() {
+  assert_eq!(
+    run("hard", & ["-o", "timeout 1"]).status.code(), Some(20)
+  )
+}
+
+#[test]
+fn exit_error() {
+  assert_eq!( run("parse_error", & []).status.code(), Some(2) ) ;
+  assert_eq!( run("no_such_file", & []).status.code(), Some(2) )
+}
+
+#[test]
+fn json_safe() {
+  let output = run("simple", & ["--format", "json"]) ;
+  assert_eq!( output.status.code(), Some(0) ) ;
+  let records = records(& output) ;
+  assert_eq!( records.len(), 1 ) ;
+  assert!( records[0].starts_with("{\"property\": \"out_positive\", ") ) ;
+  assert!( records[0].contains("\"status\": \"invariant\", \"k\": 1, ") ) ;
+  assert!( records[0].contains("\"cex\": null") )
+}
+
+#[test]
+fn json_unsafe() {
+  let output = run("simple_false", & ["--format", "json"]) ;
+  assert_eq!( output.status.code(), Some(10) ) ;
+  let records = records(& output) ;
+  assert_eq!( records.len(), 2 ) ;
+  for (record, (prop, len)) in records.iter().zip(
+    vec![ ("out_positive", 10), ("out_positive1", 1) ]
+  ) {
+    assert!(
+      record.starts_with( & format!("{{\"property\": \"{}\", ", prop) )
+    ) ;
+    assert!( record.contains("\"status\": \"falsified\", \"k\": null, ") ) ;
+    assert!( record.contains( & format!("\"cex\": {{\"length\": {}, ", len) ) )
+  }
+}
+
+#[test]
+fn json_error() {
+  let output = run("parse_error", & ["--format", "json"]) ;
+  assert_eq!( output.status.code(), Some(2) ) ;
+  let records = records(& output) ;
+  assert_eq!( records.len(), 1 ) ;
+  assert!( records[0].starts_with("{\"error\": ") )
+}
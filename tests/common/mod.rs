@@ -13,6 +13,10 @@ pub enum ExpStatus {
   False(usize),
   /// Invariant.
   Inv(usize),
+  /// Falsified, whatever the length of the counterexample.
+  AnyFalse,
+  /// Invariant, whatever the `k`.
+  AnyInv,
 }
 impl ExpStatus {
   /// Compares an `ExpStatus` to a `PropStatus`.
@@ -33,6 +37,12 @@ impl ExpStatus {
       (& KTru(n), & KTrue(exp_n))
       if n == exp_n => true,
 
+      (& AnyFalse, & Falsified(_)) => true,
+
+      (& AnyInv, & Invariant(_)) => true,
+
+      (& AnyInv, & MinInvariant(_, _)) => true,
+
       _ => false,
 
     }
@@ -45,6 +55,8 @@ impl fmt::Display for ExpStatus {
       KTru(n) => write!(fmt, "<{}-true>", n),
       False(n) => write!(fmt, "<{}-false>", n),
       Inv(n) => write!(fmt, "<{}-invariant>", n),
+      AnyFalse => write!(fmt, "<false>"),
+      AnyInv => write!(fmt, "<invariant>"),
     }
   }
 }
@@ -55,30 +67,24 @@ macro_rules! exp {
   (true $n:expr) => ( common::ExpStatus::KTru($n) ) ;
   (false $n:expr) => ( common::ExpStatus::False($n) ) ;
   (inv $n:expr) => ( common::ExpStatus::Inv($n) ) ;
+  (false) => ( common::ExpStatus::AnyFalse ) ;
+  (inv) => ( common::ExpStatus::AnyInv ) ;
 }
 
 /// Test macro, creates a test function for a file expecting some results.
+///
+/// With `with <conf>` after the path, the file is analyzed with
+/// configuration `<conf>` instead of the default one.
 #[macro_export]
 macro_rules! mk_test {
   (
-    $name:ident, $path:expr $(
-      , $prop_sym:expr => $prop_status:expr
-    )* ,
-  ) => (
-    mk_test!{
-      $name, $path $(, $prop_sym => $prop_status )*
-    }
-  ) ;
-  (
-    $name:ident, $path:expr $(
+    @check $file:expr, $res:expr $(
       , $prop_sym:expr => $prop_status:expr
     )*
   ) => (
-    #[test]
-    fn $name() {
-      #![allow(unused_mut)]
-      let file = & $path ;
-      match kino::analyze(file) {
+    {
+      let file = $file ;
+      match $res {
         Ok( (context, props) ) => {
           use std::collections::HashMap ;
           use common::ExpStatus ;
@@ -140,6 +146,61 @@ macro_rules! mk_test {
       }
     }
   ) ;
+  (
+    $name:ident, $path:expr, with $conf:expr $(
+      , $prop_sym:expr => $prop_status:expr
+    )* ,
+  ) => (
+    mk_test!{
+      $name, $path, with $conf $(, $prop_sym => $prop_status )*
+    }
+  ) ;
+  (
+    $name:ident, $path:expr, with $conf:expr $(
+      , $prop_sym:expr => $prop_status:expr
+    )*
+  ) => (
+    #[test]
+    fn $name() {
+      #![allow(unused_mut)]
+      let file = & $path ;
+      let res = kino::analyze_file(file, $conf).map(
+        |(context, report)| {
+          let props: Vec<_> = report.props.into_iter().map(
+            |prop| prop.prop
+          ).collect() ;
+          (context, props)
+        }
+      ) ;
+      mk_test!{
+        @check file, res $(, $prop_sym => $prop_status )*
+      }
+    }
+  ) ;
+  (
+    $name:ident, $path:expr $(
+      , $prop_sym:expr => $prop_status:expr
+    )* ,
+  ) => (
+    mk_test!{
+      $name, $path $(, $prop_sym => $prop_status )*
+    }
+  ) ;
+  (
+    $name:ident, $path:expr $(
+      , $prop_sym:expr => $prop_status:expr
+    )*
+  ) => (
+    #[test]
+    fn $name() {
+      #![allow(unused_mut)]
+      let file = & $path ;
+      let res = kino::analyze(file) ;
+      mk_test!{
+        @check file, res $(, $prop_sym => $prop_status )*
+      }
+    }
+  ) ;
 }
 
 /// Prints a `PropStatus` as test information.
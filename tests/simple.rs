@@ -18,6 +18,60 @@ fn path_to(file: & str) -> String {
   format!("rsc/simple/{}.vmt", file)
 }
 
+/// Configuration running PDR only.
+fn pdr_only() -> kino::conf::Master {
+  use kino::conf::HasSet ;
+  let mut conf = kino::conf::Master::default() ;
+  let mut pdr = kino::conf::Pdr::default() ;
+  pdr.set("turn", "on").expect("could not set pdr option") ;
+  conf.pdr = Some(pdr) ;
+  conf.bmc = None ;
+  conf.kind = None ;
+  conf.twind = None ;
+  conf.tig = None ;
+  conf.pruner = None ;
+  conf
+}
+
+/// Configuration where only invariant generation can find the lemmas
+/// 2-induction needs: k-induction and PDR are off. TIG mines two-state
+/// and/or arithmetic candidates depending on the flags.
+fn tig_twind(two_state: bool, arith: bool) -> kino::conf::Master {
+  use kino::conf::HasSet ;
+  let on_off = |flag: bool| if flag { "on" } else { "off" } ;
+  let mut conf = kino::conf::Master::default() ;
+  let mut tig = kino::conf::Tig::default() ;
+  tig.set("two_state", on_off(two_state)).expect(
+    "could not set tig option"
+  ) ;
+  tig.set("arith", on_off(arith)).expect("could not set tig option") ;
+  conf.tig = Some(tig) ;
+  conf.kind = None ;
+  conf.pruner = None ;
+  conf.pdr = None ;
+  conf
+}
+
+/// Configuration where only BMC and k-induction prove properties: TIG,
+/// Twind and PDR are off.
+fn kind_only(compositional: bool) -> kino::conf::Master {
+  let mut conf = kino::conf::Master::default() ;
+  conf.compositional = compositional ;
+  conf.tig = None ;
+  conf.twind = None ;
+  conf.pdr = None ;
+  conf
+}
+
+/// Configuration analyzing the groups of properties with disjoint cones of
+/// influence one at a time.
+fn one_job() -> kino::conf::Master {
+  let mut conf = kino::conf::Master::default() ;
+  conf.schedule = true ;
+  conf.jobs = Some(1) ;
+  conf
+}
+
 #[test]
 mk_test!{
   simple, path_to("simple"),
@@ -78,4 +132,416 @@ mk_test!{
   "prop(b,2)" => exp!(inv 1),
   "prop(b,3)" => exp!(inv 1),
   "prop(b,4)" => exp!(false 9),
-}
\ No newline at end of file
+}
+
+#[test]
+mk_test!{
+  simple_assuming, path_to("simple_assuming"),
+  "out_le_10" => exp!(inv 1),
+  "out_le_0" => exp!(inv 1),
+}
+
+#[test]
+mk_test!{
+  simple_array, path_to("simple_array"),
+  "first_le_1" => exp!(inv 1),
+  "third_is_0" => exp!(false 3),
+}
+
+#[test]
+mk_test!{
+  simple_bv, path_to("simple_bv"),
+  "evn_is_even" => exp!(inv 1),
+  "cnt_no_overflow" => exp!(false 15),
+}
+
+#[test]
+mk_test!{
+  simple_enum, path_to("simple_enum"),
+  "cnt_pos" => exp!(inv 1),
+  "never_run" => exp!(false 2),
+}
+
+#[test]
+mk_test!{
+  simple_mod, path_to("simple_mod"),
+  "cnt_div_3" => exp!(inv 1),
+  "rcnt_is_cnt" => exp!(inv 1),
+  "cnt_not_2" => exp!(false 2),
+}
+
+#[test]
+mk_test!{
+  simple_lustre, "rsc/simple/simple_lustre.lus".to_string(),
+  "clk >= 0" => exp!(inv 1),
+  "cnt < 2" => exp!(false 1),
+}
+
+#[test]
+mk_test!{
+  simple_btor, "rsc/simple/simple_btor.btor2".to_string(),
+  "cnt_is_5" => exp!(false 5),
+  "cnt_neg" => exp!(inv 1),
+}
+
+#[test]
+mk_test!{
+  simple_aiger, "rsc/simple/simple_aiger.aag".to_string(),
+  "both_set" => exp!(false 3),
+  "stuck_set" => exp!(inv 1),
+}
+
+#[test]
+mk_test!{
+  simple_vmtlib, "rsc/simple/simple_vmtlib.vmt".to_string(),
+  "prop_0" => exp!(inv 1),
+  "prop_1" => exp!(false 3),
+}
+
+#[test]
+fn simple_vmtlib_str() {
+  use std::fs::File ;
+  use std::io::Read ;
+  use kino::SymMaker ;
+  let mut src = String::new() ;
+  File::open("rsc/simple/simple_vmtlib.vmt").and_then(
+    |mut file| file.read_to_string(& mut src)
+  ).expect("could not read file") ;
+  // Annotated VMT-LIB is recognized from the input, without any path.
+  let (context, report) = kino::analyze_str(
+    & src, kino::conf::Master::default()
+  ).expect("analysis failed") ;
+  let prop_0 = context.factory().sym("prop_0") ;
+  assert!(
+    report.get(& prop_0).expect("property not in report").is_invariant()
+  ) ;
+  let prop_1 = context.factory().sym("prop_1") ;
+  assert!(
+    report.get(& prop_1).expect("property not in report").is_falsified()
+  )
+}
+
+#[test]
+mk_test!{
+  compositional, path_to("compositional"), with kind_only(true),
+  "ok_holds" => exp!(inv 1),
+}
+
+#[test]
+fn compositional_off() {
+  use kino::SymMaker ;
+  use kino::PropStatus::* ;
+  let (context, report) = kino::analyze_file(
+    & path_to("compositional"), kind_only(false)
+  ).expect("analysis failed") ;
+  let ok_holds = context.factory().sym("ok_holds") ;
+  // Without `out_positive` lifted, `ok_holds` is not 1-inductive.
+  match report.get(& ok_holds).expect("property not in report").status {
+    Invariant(1) | MinInvariant(1, _) => panic!(
+      "`ok_holds` should not be proved by 1-induction"
+    ),
+    _ => (),
+  }
+}
+
+#[test]
+mk_test!{
+  simple_pdr, path_to("simple"), with pdr_only(),
+  "out_positive" => exp!(inv),
+}
+
+#[test]
+mk_test!{
+  simple_two_props_pdr, path_to("simple_two_props"), with pdr_only(),
+  "out_positive" => exp!(inv),
+  "out_le_10" => exp!(false),
+}
+
+#[test]
+mk_test!{
+  simple_false_pdr, path_to("simple_false"), with pdr_only(),
+  "out_positive" => exp!(false),
+  "out_positive1" => exp!(false),
+}
+
+#[test]
+mk_test!{
+  finite_state_pdr, path_to("finite_state"), with pdr_only(),
+  "out_inc" => exp!(inv),
+  "out_le_4" => exp!(inv),
+}
+
+#[test]
+mk_test!{
+  two_state, path_to("two_state"), with tig_twind(true, false),
+  "x_positive" => exp!(inv),
+}
+
+#[test]
+mk_test!{
+  arith, path_to("arith"), with tig_twind(false, true),
+  "y_is_10" => exp!(inv),
+}
+
+#[test]
+mk_test!{
+  disjoint_one_job, path_to("disjoint"), with one_job(),
+  "a_pos" => exp!(inv 1),
+  "a_lt_3" => exp!(false 3),
+  "b_pos" => exp!(inv 1),
+}
+
+#[test]
+fn simple_false_report() {
+  use std::fs::File ;
+  let mut file = File::open( path_to("simple_false") ).expect(
+    "could not open file"
+  ) ;
+  let (context, report) = kino::analyze_read(
+    & mut file, kino::conf::Master::default()
+  ).expect("analysis failed") ;
+  assert!( report.is_unsafe() ) ;
+  for (sym, len) in vec![ ("out_positive", 10), ("out_positive1", 1) ] {
+    use kino::SymMaker ;
+    let sym = context.factory().sym(sym) ;
+    let prop = report.get(& sym).expect("property not in report") ;
+    assert!( prop.tek.is_some() ) ;
+    assert!( prop.time.is_some() ) ;
+    assert_eq!( prop.cex().map(|cex| cex.len()), Some(len) )
+  }
+}
+
+#[test]
+fn simple_false_vcd() {
+  use std::fs::File ;
+  use kino::SymMaker ;
+  let mut file = File::open( path_to("simple_false") ).expect(
+    "could not open file"
+  ) ;
+  let (context, report) = kino::analyze_read(
+    & mut file, kino::conf::Master::default()
+  ).expect("analysis failed") ;
+  let sym = context.factory().sym("out_positive") ;
+  let cex = report.get(& sym).and_then(
+    |prop| prop.cex()
+  ).expect("property should be falsified") ;
+  let mut vcd = vec![] ;
+  cex.write_vcd(& mut vcd).expect("could not write vcd") ;
+  let vcd = String::from_utf8(vcd).expect("vcd is not utf8") ;
+  assert!( vcd.contains("$enddefinitions $end") ) ;
+  assert!( vcd.contains("$dumpvars") ) ;
+  // One timestep per state, plus the final one.
+  for time in 0..(cex.len() + 2) {
+    assert!( vcd.contains( & format!("\n#{}\n", time) ) )
+  }
+}
+
+#[test]
+fn modular_four_replay() {
+  use std::fs::File ;
+  let mut file = File::open( path_to("modular_four") ).expect(
+    "could not open file"
+  ) ;
+  let (context, report) = kino::analyze_read(
+    & mut file, kino::conf::Master::default()
+  ).expect("analysis failed") ;
+  let mut falsified = 0 ;
+  for prop in report.props.iter() {
+    if let Some(cex) = prop.cex() {
+      falsified += 1 ;
+      if let Err(e) = cex.replay(
+        context.factory(), & [ prop.prop.clone() ]
+      ) {
+        panic!("cex for {} does not replay: {}", prop.sym(), e)
+      }
+    }
+  }
+  assert_eq!(falsified, 2)
+}
+
+#[test]
+fn simple_certif() {
+  use std::fs::File ;
+  use std::io::Read ;
+  let dir = std::env::temp_dir().join("kino_simple_certif") ;
+  let mut conf = kino::conf::Master::default() ;
+  conf.certif = Some( format!("{}", dir.display()) ) ;
+  let mut file = File::open( path_to("simple") ).expect(
+    "could not open file"
+  ) ;
+  let (_, report) = kino::analyze_read(& mut file, conf).expect(
+    "analysis failed"
+  ) ;
+  assert!( report.is_safe() ) ;
+  let mut certif = String::new() ;
+  File::open( dir.join("out_positive.smt2") ).expect(
+    "could not open certificate"
+  ).read_to_string(& mut certif).expect(
+    "could not read certificate"
+  ) ;
+  assert!( certif.contains("(reset)") ) ;
+  assert!( certif.contains("check-sat-assuming") )
+}
+
+#[test]
+fn simple_two_props_minimize() {
+  use std::fs::File ;
+  use kino::conf::HasSet ;
+  use kino::{ PropStatus, SymMaker } ;
+  let mut conf = kino::conf::Master::default() ;
+  let mut pruner = kino::conf::Pruner::default() ;
+  pruner.set("minimize", "on").expect("could not set pruner option") ;
+  conf.pruner = Some(pruner) ;
+  let mut file = File::open( path_to("simple_two_props") ).expect(
+    "could not open file"
+  ) ;
+  let (context, report) = kino::analyze_read(& mut file, conf).expect(
+    "analysis failed"
+  ) ;
+  let sym = context.factory().sym("out_positive") ;
+  match report.get(& sym).expect("property not in report").status {
+    // `out_positive` is inductive on its own.
+    PropStatus::MinInvariant(1, ref lemmas) => assert!( lemmas.is_empty() ),
+    ref status => panic!(
+      "expected a minimized 1-invariant, got {}",
+      common::str_of_status(status)
+    ),
+  }
+}
+
+#[test]
+fn simple_path() {
+  use std::fs::File ;
+  use kino::conf::HasSet ;
+  let mut conf = kino::conf::Master::default() ;
+  let mut kind = kino::conf::Kind::default() ;
+  kind.set("simple_path", "on").expect("could not set kind option") ;
+  conf.kind = Some(kind) ;
+  // Only BMC and k-induction, invariant generation would find the inductive
+  // strengthening `(<= (_ curr x) 0)`.
+  conf.twind = None ;
+  conf.tig = None ;
+  conf.pruner = None ;
+  conf.pdr = None ;
+  let mut file = File::open( path_to("simple_path") ).expect(
+    "could not open file"
+  ) ;
+  let (_, report) = kino::analyze_read(& mut file, conf).expect(
+    "analysis failed"
+  ) ;
+  assert!( report.is_safe() )
+}
+
+#[test]
+fn simple_live() {
+  use std::fs::File ;
+  use kino::SymMaker ;
+  use kino::conf::HasSet ;
+  // Proving `served` needs invariants k-induction does not find, it only
+  // must not be falsified. Bounded so that the analysis ends.
+  let mut conf = kind_only(true) ;
+  let mut bmc = kino::conf::Bmc::default() ;
+  bmc.set("max", "5").expect("could not set bmc option") ;
+  conf.bmc = Some(bmc) ;
+  let mut kind = kino::conf::Kind::default() ;
+  kind.set("max", "5").expect("could not set kind option") ;
+  conf.kind = Some(kind) ;
+  conf.timeout = Some(10) ;
+  let mut file = File::open( path_to("simple_live") ).expect(
+    "could not open file"
+  ) ;
+  let (context, report) = kino::analyze_read(& mut file, conf).expect(
+    "analysis failed"
+  ) ;
+  let starved = context.factory().sym("starved") ;
+  let cex = report.get(& starved).and_then(
+    |prop| prop.cex()
+  ).expect("property should be falsified") ;
+  assert_eq!( cex.len(), 2 ) ;
+  assert_eq!( cex.loop_start(), Some(1) ) ;
+  // The last state closes the loop.
+  assert!( cex.state(1).is_some() ) ;
+  assert!( cex.state( cex.len() ) == cex.state(1) ) ;
+  let served = context.factory().sym("served") ;
+  assert!(
+    ! report.get(& served).expect("property not in report").is_falsified()
+  ) ;
+  let consistent = context.factory().sym("consistent") ;
+  assert!(
+    report.get(& consistent).expect("property not in report").is_invariant()
+  )
+}
+
+#[test]
+fn simple_live_bounded() {
+  use std::fs::File ;
+  use kino::SymMaker ;
+  let mut conf = kind_only(false) ;
+  conf.timeout = Some(10) ;
+  let mut file = File::open( path_to("simple_live_rr") ).expect(
+    "could not open file"
+  ) ;
+  let (context, report) = kino::analyze_read(& mut file, conf).expect(
+    "analysis failed"
+  ) ;
+  let scheduled = context.factory().sym("scheduled") ;
+  assert!(
+    report.get(& scheduled).expect("property not in report").is_invariant()
+  )
+}
+
+#[test]
+fn hard_timeout() {
+  use std::fs::File ;
+  use std::time::Instant ;
+  let mut conf = kino::conf::Master::default() ;
+  conf.timeout = Some(1) ;
+  let mut file = File::open( path_to("hard") ).expect(
+    "could not open file"
+  ) ;
+  let start = Instant::now() ;
+  let (_, report) = kino::analyze_read(& mut file, conf).expect(
+    "analysis failed"
+  ) ;
+  // The techniques are blocked in solver queries when the timeout hits, the
+  // analysis does not wait for them and their solvers exit by themselves.
+  assert!( start.elapsed().as_secs() < 10 ) ;
+  assert!( report.is_unknown() )
+}
+
+#[test]
+fn modular_chc() {
+  let mut chc = vec![] ;
+  kino::write_chc( & path_to("modular"), & mut chc ).expect(
+    "could not export CHCs"
+  ) ;
+  let chc = String::from_utf8(chc).expect("CHCs are not utf8") ;
+  assert!( chc.starts_with("(set-logic HORN)") ) ;
+  assert!( chc.contains("(declare-fun | oracle| ( ) Bool)") ) ;
+  // Sub-system first.
+  assert!(
+    chc.find("(declare-fun |reach[sys_a]|").expect("no predicate for sys_a")
+    < chc.find("(declare-fun |reach[sys_b]|").expect("no predicate for sys_b")
+  ) ;
+  assert!( chc.contains("; Property prop(b,1).") ) ;
+  assert!( chc.trim_end().ends_with("(check-sat)") )
+}
+
+#[test]
+fn assuming_chc() {
+  let mut chc = vec![] ;
+  kino::write_chc( & path_to("simple_assuming"), & mut chc ).expect(
+    "could not export CHCs"
+  ) ;
+  let chc = String::from_utf8(chc).expect("CHCs are not utf8") ;
+  // The assumption holds in the initial and reached states.
+  assert!(
+    chc.contains("(and (| init[sys_a]| |@0 inc| |@0 out|) (not |@0 inc|))")
+  ) ;
+  assert!( chc.contains("|@1 inc| |@1 out|) (not |@1 inc|))") ) ;
+  assert!(
+    chc.contains(
+      "(and (|reach[sys_a]| |@0 inc| |@0 out|) (not |@0 inc|) (not "
+    )
+  )
+}
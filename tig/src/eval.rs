@@ -89,6 +89,16 @@ impl<Val: Domain> Eval<Val> {
     ()
   }
 
+  /// Changes the domain of the evaluator. The model and the cache are kept.
+  pub fn retype<V: Domain>(self) -> Eval<V> {
+    Eval {
+      phantom: PhantomData,
+      sys: self.sys,
+      model: self.model, offset: self.offset,
+      cache: self.cache, factory: self.factory
+    }
+  }
+
   /// Evaluates a real term. Cached at top level.
   pub fn eval_term(& mut self, term: & Term) -> Res<Val> {
     self.eval(
@@ -17,7 +17,7 @@ use common::conf ;
 use common::errors::* ;
 
 use term::{
-  Sym, Factory, Term, TermSet, TermMap, Bool
+  Sym, Factory, Term, TermSet, TermMap, Bool, Int, Rat
 } ;
 use term::tmp::{ TmpTerm, TmpTermSet, TmpTermMap } ;
 
@@ -966,6 +966,26 @@ pub fn mk_bool_learner(
   Learner::mk(sys, rep, class, factory, conf)
 }
 
+/// Creates a graph-based learner over int terms. `None` if the system has no
+/// int state variable.
+pub fn mk_int_learner(
+  sys: Sys, factory: Factory, conf: & conf::Tig
+) -> Option< Learner< Graph<Int> > > {
+  mine::int(& factory, & sys, * conf.all_out()).map(
+    |(rep, class)| Learner::mk(sys, rep, class, factory, conf)
+  )
+}
+
+/// Creates a graph-based learner over rat terms. `None` if the system has no
+/// rat state variable.
+pub fn mk_rat_learner(
+  sys: Sys, factory: Factory, conf: & conf::Tig
+) -> Option< Learner< Graph<Rat> > > {
+  mine::rat(& factory, & sys, * conf.all_out()).map(
+    |(rep, class)| Learner::mk(sys, rep, class, factory, conf)
+  )
+}
+
 
 
 
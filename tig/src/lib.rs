@@ -45,6 +45,7 @@ pub mod chain ;
 pub mod graph ;
 use graph::CanLog ;
 pub mod lsd ;
+pub mod two_state ;
 
 
 /// Invgen technique.
@@ -62,6 +63,7 @@ impl CanRun<conf::Tig> for Tig {
       None => (),
       Some(ref cmd) => solver_conf = solver_conf.cmd(cmd.clone()),
     } ;
+    let solver_conf = event.time_limit(solver_conf) ;
 
     mk_two_solver_run!(
       solver_conf, conf.smt_log(), "tig", event.factory(),
@@ -93,12 +95,43 @@ impl CanRun<conf::Tig> for Tig {
 }
 
 
+/// Stabilizes the graph of a learner at the current unrolling. The graphs
+/// logged are tagged with `name`, the unrolling and the iteration.
+fn stabilize<
+  Val: Domain, Base, Step,
+  GraphLog: Fn(& CanLog, & str, & str) -> Res<()>
+>(
+  learner: & mut graph::Learner< graph::Graph<Val> >,
+  base: & mut Base, step: & mut Step, event: & mut Event,
+  graph_log: & GraphLog, name: & str, cnt: usize
+) -> Res<()> where
+Base: lsd::BaseTrait<Val, Step>,
+Step: lsd::StepTrait<Val, Base> {
+  let learner_log = |
+    learner: & graph::Learner< graph::Graph<Val> >, tag1: & str, tag2: & str
+  | graph_log(learner as & CanLog, tag1, tag2) ;
+
+  let mut is_done = false ;
+  let mut inner_cnt = 0 ;
+
+  while ! is_done {
+    is_done = try!(
+      learner.stabilize_next_class_and_edges(
+        base, step, event,
+        & learner_log, & format!("{}{}_{}", name, cnt, inner_cnt)
+      )
+    ) ;
+
+    inner_cnt += 1
+  }
+
+  Ok(())
+}
+
 /// Runs invgen.
 fn invgen<
   'a, S: SolverTrait<'a>,
-  GraphLog: Fn(
-    & graph::Learner< graph::Graph<Bool> >, & str, & str
-  ) -> Res<()>
+  GraphLog: Fn(& CanLog, & str, & str) -> Res<()>
 >(
   conf: Arc<conf::Tig>, solver_1: S, solver_2: S, sys: Sys, event: & mut Event,
   graph_log: GraphLog
@@ -123,6 +156,21 @@ fn invgen<
   //   Graph::<Bool>::mk(sys.clone(), rep, class),
   //   & (* conf)
   // ) ;
+  let mut two_state = if * conf.two_state() {
+    Some( two_state::TwoState::mk(& sys, factory.clone()) )
+  } else {
+    None
+  } ;
+
+  let (mut int_graph, mut rat_graph) = if * conf.arith() {
+    (
+      graph::mk_int_learner(sys.clone(), factory.clone(), & * conf),
+      graph::mk_rat_learner(sys.clone(), factory.clone(), & * conf)
+    )
+  } else {
+    (None, None)
+  } ;
+
   let mut graph = graph::mk_bool_learner(
     sys.clone(), factory, & * conf
   ) ;
@@ -130,16 +178,32 @@ fn invgen<
   event.log(
     & format!("running with {} candidate terms", graph.len() + 1)
   ) ;
+  if let Some(ref graph) = int_graph {
+    event.log(
+      & format!("and {} int candidate terms", graph.len())
+    )
+  }
+  if let Some(ref graph) = rat_graph {
+    event.log(
+      & format!("and {} rat candidate terms", graph.len())
+    )
+  }
+  if let Some(ref two_state) = two_state {
+    event.log(
+      & format!("and {} two-state candidate terms", two_state.len())
+    )
+  }
 
   // event.log("creating base checker") ;
   let mut base = log_try!(
-    event, Base::mk(& sys, solver_1, 0) => "while creating base checker"
+    event, Base::mk(& sys, event.assumptions(), solver_1, 0)
+    => "while creating base checker"
   ) ;
 
   // event.log("creating step checker") ;
   let mut step = {
     let mut base = log_try!(
-      event, Base::mk(& sys, solver_2, 0)
+      event, Base::mk(& sys, event.assumptions(), solver_2, 0)
       => "while creating base checker to create step checker"
     ) ;
     log_try!(
@@ -157,29 +221,15 @@ fn invgen<
 
   'work: while max_k.map_or(true, |max| cnt <= max) {
 
-    let mut is_done = false ;
-    let mut inner_cnt = 0 ;
     let start = Instant::now() ;
 
     event.log( & format!("stabilizing at {}...", cnt) ) ;
 
-    'stabilize: while ! is_done {
-      is_done = log_try!(
-        event, graph.stabilize_next_class_and_edges(
-          & mut base, & mut step, event,
-          & graph_log, & format!("{}_{}", cnt, inner_cnt)
-        ) => "while stabilizing at {}", cnt
-      ) ;
-
-      // try_error!(
-      //   base.restart(), event, "while restarting base at {}", cnt - 1
-      // ) ;
-      // try_error!(
-      //   step.restart(), event, "while restarting step at {}", cnt - 1
-      // ) ;
-
-      inner_cnt += 1
-    }
+    log_try!(
+      event, stabilize(
+        & mut graph, & mut base, & mut step, event, & graph_log, "", cnt
+      ) => "while stabilizing at {}", cnt
+    ) ;
 
     let time = Instant::now() - start ;
 
@@ -197,6 +247,53 @@ fn invgen<
       => "while splitting all at {}", cnt
     ) ;
 
+    // Arithmetic graphs use the same checkers, in their own domain.
+    if let Some(ref mut graph) = int_graph {
+      let mut int_base = base.to_domain::<Int>() ;
+      let mut int_step = step.to_domain::<Int>() ;
+      log_try!(
+        event, stabilize(
+          graph, & mut int_base, & mut int_step, event, & graph_log,
+          "int_", cnt
+        ) => "while stabilizing int graph at {}", cnt
+      ) ;
+      graph.clear() ;
+      log_try!(
+        event, graph.k_split_all(& mut int_base, & mut int_step, event)
+        => "while splitting all int graph at {}", cnt
+      ) ;
+      base = int_base.to_domain::<Bool>() ;
+      step = int_step.to_domain::<Bool>()
+    }
+    if let Some(ref mut graph) = rat_graph {
+      let mut rat_base = base.to_domain::<Rat>() ;
+      let mut rat_step = step.to_domain::<Rat>() ;
+      log_try!(
+        event, stabilize(
+          graph, & mut rat_base, & mut rat_step, event, & graph_log,
+          "rat_", cnt
+        ) => "while stabilizing rat graph at {}", cnt
+      ) ;
+      graph.clear() ;
+      log_try!(
+        event, graph.k_split_all(& mut rat_base, & mut rat_step, event)
+        => "while splitting all rat graph at {}", cnt
+      ) ;
+      base = rat_base.to_domain::<Bool>() ;
+      step = rat_step.to_domain::<Bool>()
+    }
+
+    if let Some(ref mut two_state) = two_state {
+      log_try!(
+        event, two_state.base_check(& mut base)
+        => "while checking two-state candidates in base at {}", cnt
+      ) ;
+      log_try!(
+        event, two_state.k_split(& base, & mut step, event)
+        => "while splitting two-state candidates at {}", cnt
+      )
+    }
+
     log_try!(
       event, base.restart() => "while restarting base at {}", cnt - 1
     ) ;
@@ -305,10 +402,18 @@ impl Domain for Int  {
     }
   }
   fn mk_cmp(lhs: & Term, rhs: & Term) -> Option<TmpTerm> {
-    Some( TmpTerm::mk_term_le(lhs.clone(), rhs.clone()) )
+    if ! lhs.is_cst() || ! rhs.is_cst() {
+      Some( TmpTerm::mk_term_le(lhs.clone(), rhs.clone()) )
+    } else {
+      None
+    }
   }
   fn mk_eq(lhs: & Term, rhs: & Term) -> Option<TmpTerm> {
-    Some( TmpTerm::mk_term_eq(lhs.clone(), rhs.clone()) )
+    if ! lhs.is_cst() || ! rhs.is_cst() {
+      Some( TmpTerm::mk_term_eq(lhs.clone(), rhs.clone()) )
+    } else {
+      None
+    }
   }
   fn choose_rep(_: & Factory, mut set: TermSet) -> Res<(Term, TermSet)> {
     let rep = match set.iter().next() {
@@ -330,10 +435,18 @@ impl Domain for Rat  {
     }
   }
   fn mk_cmp(lhs: & Term, rhs: & Term) -> Option<TmpTerm> {
-    Some( TmpTerm::mk_term_le(lhs.clone(), rhs.clone()) )
+    if ! lhs.is_cst() || ! rhs.is_cst() {
+      Some( TmpTerm::mk_term_le(lhs.clone(), rhs.clone()) )
+    } else {
+      None
+    }
   }
   fn mk_eq(lhs: & Term, rhs: & Term) -> Option<TmpTerm> {
-    Some( TmpTerm::mk_term_eq(lhs.clone(), rhs.clone()) )
+    if ! lhs.is_cst() || ! rhs.is_cst() {
+      Some( TmpTerm::mk_term_eq(lhs.clone(), rhs.clone()) )
+    } else {
+      None
+    }
   }
   fn choose_rep(_: & Factory, mut set: TermSet) -> Res<(Term, TermSet)> {
     let rep = match set.iter().next() {
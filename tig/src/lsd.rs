@@ -39,6 +39,11 @@ pub trait StepTrait<
   fn k_split<Info: Clone>(
     & mut self, & mut TmpTermMap<Info>
   ) -> Res<TmpTermMap<Info>> ;
+  /// Same as `k_split` for two-state terms. The terms are assumed on all the
+  /// transitions of the trace but the last one, on which they are checked.
+  fn k_split_two_state<Info: Clone>(
+    & mut self, & mut TmpTermMap<Info>
+  ) -> Res<TmpTermMap<Info>> ;
 }
 
 /// High-level LSD features.
@@ -133,11 +138,16 @@ pub mod top_only {
     }
 
     /// Creates a base checker, unrolls the transition relation `unroll` times.
-    pub fn mk(sys: & Sys, solver: Solver, unroll: usize) -> Res<Self> {
+    /// The assumptions are asserted at each step of the unrolling.
+    pub fn mk(
+      sys: & Sys, assumptions: & [::term::Term], solver: Solver,
+      unroll: usize
+    ) -> Res<Self> {
       let factory = solver.parser().clone() ;
-      let unroller = try_chain!(
+      let mut unroller = try_chain!(
         Unroller::mk(sys, & [], solver) => "[Base] while creating unroller"
       ) ;
+      unroller.assume(assumptions) ;
       Base::of(
         unroller, unroll, Eval::mk(
           sys.clone(), vec![], Step::<Val, Solver>::check_offset(), factory
@@ -150,6 +160,14 @@ pub mod top_only {
     pub fn of_step(step: Step<Val, Solver>) -> Res<Self> {
       Self::of(step.unroller, step.k.next().to_usize(), step.eval)
     }
+
+    /// Changes the domain of a base checker. The solver is left untouched.
+    #[inline]
+    pub fn to_domain<V: Domain>(self) -> Base<V, Solver> {
+      Base {
+        unroller: self.unroller, k: self.k, eval: self.eval.retype()
+      }
+    }
   }
 
   impl<
@@ -321,13 +339,21 @@ pub mod top_only {
     pub fn of_base(base: Base<Val, Solver>) -> Res<Self> {
       Self::of(base.unroller, base.k.next().to_usize(), base.eval)
     }
-  }
 
-  impl<
-    'a, Val: Domain, Solver: SolverTrait<'a>
-  > StepTrait< Val, Base<Val, Solver> > for Step<Val, Solver> {
-    fn k_split<Info: Clone>(
-      & mut self, in_map: & mut TmpTermMap<Info>
+    /// Changes the domain of a step checker. The solver is left untouched.
+    #[inline]
+    pub fn to_domain<V: Domain>(self) -> Step<V, Solver> {
+      Step {
+        unroller: self.unroller, k: self.k, check: self.check,
+        eval: self.eval.retype()
+      }
+    }
+
+    /// Splits the input map, the terms are assumed at all offsets between
+    /// `first` and `last` (inclusive) and checked at the check offset.
+    fn split<Info: Clone>(
+      & mut self, in_map: & mut TmpTermMap<Info>,
+      first: Offset2, last: Offset2
     ) -> Res<TmpTermMap<Info>> {
       if in_map.is_empty() {
         return Ok( TmpTermMap::new() )
@@ -350,15 +376,15 @@ pub mod top_only {
 
       // Asserting all positive implications.
       let positive = Term::and(positive) ;
-      let mut unroll = self.check.nxt() ;
+      let mut unroll = first ;
 
-      while unroll <= self.k {
-        unroll = unroll.nxt() ;
+      while unroll <= last {
         try_chain!(
           self.unroller.assert(& positive, & unroll)
           => "[Step::k_split] while asserting positive implications at {}",
             unroll
-        )
+        ) ;
+        unroll = unroll.nxt()
       }
 
       // Splitting loop.
@@ -454,6 +480,23 @@ pub mod top_only {
     }
   }
 
+  impl<
+    'a, Val: Domain, Solver: SolverTrait<'a>
+  > StepTrait< Val, Base<Val, Solver> > for Step<Val, Solver> {
+    fn k_split<Info: Clone>(
+      & mut self, in_map: & mut TmpTermMap<Info>
+    ) -> Res<TmpTermMap<Info>> {
+      let (first, last) = ( self.check.nxt().nxt(), self.k.nxt() ) ;
+      self.split(in_map, first, last)
+    }
+    fn k_split_two_state<Info: Clone>(
+      & mut self, in_map: & mut TmpTermMap<Info>
+    ) -> Res<TmpTermMap<Info>> {
+      let (first, last) = ( self.check.nxt(), self.k.clone() ) ;
+      self.split(in_map, first, last)
+    }
+  }
+
   impl<
     'a, Val: Domain, Solver: SolverTrait<'a>
   > Lsd< Val, Base<Val, Solver>, Step<Val, Solver> > for Step<Val, Solver> {
@@ -19,6 +19,8 @@ use system::Sys ;
 
 use common::errors::* ;
 
+use Domain ;
+
 /// A set of symbols.
 pub type SymSet = HashSet<Sym> ;
 /// A set of constants.
@@ -100,6 +102,10 @@ pub struct Miner {
   int: Info,
   /// Rat info.
   rat: Info,
+  /// Bit-vector info, by type.
+  bv: TypMap<Info>,
+  /// Enumerated info, by type.
+  enm: TypMap<Info>,
 }
 impl Miner {
   /// Creates a miner from a system.
@@ -111,6 +117,8 @@ impl Miner {
     let mut boo = Info::empty(17) ;
     let mut int = Info::empty(17) ;
     let mut rat = Info::empty(17) ;
+    let mut bv = TypMap::new() ;
+    let mut enm = TypMap::new() ;
 
     // Splitting state variables of the system.
     for & (ref sym, ref typ) in sys.state().args().iter() {
@@ -155,6 +163,27 @@ impl Miner {
           ) ;
           rat.add_term( svar )
         },
+        BitVec(_) => {
+          let info = bv.entry( typ.get().clone() ).or_insert_with(
+            || Info::empty(17)
+          ) ;
+          info.add_svar(sym) ;
+          // Zero.
+          info.add_cst( factory.cst( typ.get().default() ) )
+        },
+        Enum(ref e) => {
+          use term::real_term::Cst as RCst ;
+          let info = enm.entry( typ.get().clone() ).or_insert_with(
+            || Info::empty(17)
+          ) ;
+          info.add_svar(sym) ;
+          // All the constructors.
+          for idx in 0..e.cons().len() {
+            info.add_cst( factory.mk_rcst( RCst::Enum(e.clone(), idx) ) )
+          }
+        },
+        // Array variables are not mined.
+        Array(_, _) => (),
       }
     }
 
@@ -183,17 +212,78 @@ impl Miner {
         boo: boo,
         int: int,
         rat: rat,
+        bv: bv,
+        enm: enm,
       }, | mut miner, cst | {
         use term::Type::* ;
         match cst.typ() {
           Bool => (),
           Int  => miner.int.add_cst( cst.clone() ),
           Rat  => miner.rat.add_cst( cst.clone() ),
+          // Only keeping the widths of the state variables.
+          BitVec(_) => if let Some(info) = miner.bv.get_mut( & cst.typ() ) {
+            info.add_cst( cst.clone() )
+          },
+          // Constructors were added with the state variables.
+          Enum(_) | Array(_, _) => (),
         }
         miner
       }
     ) ;
 
+    // Add svar bvule svar/cst and svar/cst bvule svar for bit-vectors to boo.
+    for info in miner.bv.values() {
+      use term::OpMaker ;
+      use term::Operator::BvULe ;
+      let mut svar_iter = info.vars.iter() ;
+      while let Some(svar) = svar_iter.next() {
+        let svar_term: Term = factory.svar(svar.clone(), State::Curr) ;
+        let mut others: Vec<Term> = svar_iter.clone().map(
+          |svar| factory.svar(svar.clone(), State::Curr)
+        ).collect() ;
+        for cst in info.csts.iter() {
+          others.push( factory.cst(cst.clone()) )
+        }
+        for other in others.into_iter() {
+          let term = factory.op(
+            BvULe, vec![ svar_term.clone(), other.clone() ]
+          ) ;
+          let term = STerm::One(
+            term.clone(), factory.bump(& term).unwrap()
+          ) ;
+          miner.boo.add_term( term ) ;
+          let term = factory.op(
+            BvULe, vec![ other, svar_term.clone() ]
+          ) ;
+          let term = STerm::One(
+            term.clone(), factory.bump(& term).unwrap()
+          ) ;
+          miner.boo.add_term( term ) ;
+        }
+      }
+    }
+
+    // Add svar = svar/cst for enumerated types to boo.
+    for info in miner.enm.values() {
+      let mut svar_iter = info.vars.iter() ;
+      while let Some(svar) = svar_iter.next() {
+        let svar_term: Term = factory.svar(svar.clone(), State::Curr) ;
+        let mut others: Vec<Term> = svar_iter.clone().map(
+          |svar| factory.svar(svar.clone(), State::Curr)
+        ).collect() ;
+        for cst in info.csts.iter() {
+          others.push( factory.cst(cst.clone()) )
+        }
+        for other in others.into_iter() {
+          let term = factory.eq( vec![ svar_term.clone(), other ] ) ;
+          let term = STerm::One(
+            term.clone(), factory.bump(& term).unwrap()
+          ) ;
+          miner.boo.add_term( term ) ;
+        }
+      }
+    }
+
     if all_out {
 
       // Add svar le/lt/ge/gt cst for int and rat to boo.
@@ -259,7 +349,13 @@ impl Miner {
   pub fn shrink(& mut self) {
     self.boo.shrink() ;
     self.int.shrink() ;
-    self.rat.shrink()
+    self.rat.shrink() ;
+    for info in self.bv.values_mut() {
+      info.shrink()
+    }
+    for info in self.enm.values_mut() {
+      info.shrink()
+    }
   }
 
   /// Extracts the set of candidates terms from a miner.
@@ -469,4 +565,108 @@ pub fn bool(factory: & Factory, sys: & Sys, all_out: bool) -> (Term, TermSet) {
   set.remove(& rep) ;
   set.insert( factory.cst(true) ) ;
   (rep, set)
-}
\ No newline at end of file
+}
+
+/// Turns the info of an arithmetic type into a representative and its class.
+/// The class contains the candidate terms and the constants. `None` if there
+/// is no candidate term.
+fn arith_class<Val: Domain>(
+  factory: & Factory, info: Info
+) -> Option<(Term, TermSet)> {
+  let Info { csts, trms, .. } = info ;
+  if trms.is_empty() { return None }
+
+  let mut set: TermSet = trms.into_iter().filter_map(
+    |sterm| match sterm {
+      STerm::One(_, nxt) => Some(nxt),
+      STerm::Two(_) => None,
+    }
+  ).collect() ;
+  for cst in csts.into_iter() {
+    set.insert( factory.mk_cst(cst) ) ;
+  }
+
+  Val::choose_rep(factory, set).ok()
+}
+
+/// Mines a system for int candidate terms. `None` if the system has no int
+/// state variable.
+pub fn int(
+  factory: & Factory, sys: & Sys, all_out: bool
+) -> Option<(Term, TermSet)> {
+  let mut miner = Miner::mk(sys, factory, false) ;
+  if all_out {
+    match miner.int_synth_os_oct2() {
+      Ok(()) => (),
+      Err(e) => panic!(
+        "[mine::int] in call to `Miner::int_synth_os_oct2`: {}", e
+      )
+    }
+  }
+  arith_class::<::term::Int>(factory, miner.int)
+}
+
+/// Mines a system for rat candidate terms. `None` if the system has no rat
+/// state variable.
+pub fn rat(
+  factory: & Factory, sys: & Sys, all_out: bool
+) -> Option<(Term, TermSet)> {
+  let mut miner = Miner::mk(sys, factory, false) ;
+  if all_out {
+    match miner.rat_synth_os_oct2() {
+      Ok(()) => (),
+      Err(e) => panic!(
+        "[mine::rat] in call to `Miner::rat_synth_os_oct2`: {}", e
+      )
+    }
+  }
+  arith_class::<::term::Rat>(factory, miner.rat)
+}
+/// Mines a system for two-state boolean candidate terms.
+///
+/// For each state variable `x`, the candidates are `(=> (_ next x) (_ curr
+/// x))` and `(=> (_ curr x) (_ next x))` if `x` is a bool, and `(<= (_ curr x)
+/// (_ next x))` and `(<= (_ next x) (_ curr x))` if it is arithmetic, and
+/// the same with `bvule` if it is a bit-vector. If `x` is enumerated, the
+/// candidates are `(=> (= (_ curr x) c) (= (_ next x) c))` for each
+/// constructor `c`. Array variables are ignored.
+pub fn two_state(factory: & Factory, sys: & Sys) -> TermSet {
+  use term::{ VarMaker, OpMaker, Operator, State } ;
+  let mut set = TermSet::with_capacity( 2 * sys.state().len() ) ;
+  for & (ref sym, ref typ) in sys.state().args().iter() {
+    use term::Type::* ;
+    let curr: Term = factory.svar(sym.get().clone(), State::Curr) ;
+    let next: Term = factory.svar(sym.get().clone(), State::Next) ;
+    match * typ.get() {
+      Bool => {
+        set.insert( factory.imp( next.clone(), curr.clone() ) ) ;
+        set.insert( factory.imp(curr, next) ) ;
+      },
+      Int | Rat => {
+        set.insert( factory.le( curr.clone(), next.clone() ) ) ;
+        set.insert( factory.le(next, curr) ) ;
+      },
+      BitVec(_) => {
+        set.insert(
+          factory.op( Operator::BvULe, vec![ curr.clone(), next.clone() ] )
+        ) ;
+        set.insert( factory.op( Operator::BvULe, vec![next, curr] ) ) ;
+      },
+      Enum(ref e) => for idx in 0..e.cons().len() {
+        use term::CstMaker ;
+        use term::real_term::Cst as RCst ;
+        let cst: Term = factory.cst(
+          factory.mk_rcst( RCst::Enum(e.clone(), idx) )
+        ) ;
+        set.insert(
+          factory.imp(
+            factory.eq( vec![ curr.clone(), cst.clone() ] ),
+            factory.eq( vec![ next.clone(), cst ] )
+          )
+        ) ;
+      },
+      Array(_, _) => (),
+    }
+  }
+  set
+}
@@ -0,0 +1,116 @@
+// Copyright 2016 Adrien Champion. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*! Two-state candidate invariants.
+
+Relational candidates such as `(<= (_ curr x) (_ next x))` cannot be
+evaluated on the first state of a trace, and hence do not fit in the graph.
+They are handled as a plain set of candidates instead: base drops the ones
+falsified on the last transition of its trace, and step extracts the ones
+that are k-inductive. The latter are communicated as two-state invariants. */
+
+use term::{ Factory, Sym, Bool, STerm, STermSet } ;
+use term::tmp::{ TmpTerm, TmpTermSet, TmpTermMap } ;
+
+use system::Sys ;
+
+use common::msg::Event ;
+use common::errors::* ;
+
+use lsd::{ BaseTrait, StepTrait, Lsd } ;
+use mine ;
+
+/// Two-state candidate invariants of a system.
+pub struct TwoState {
+  /// System's identifier.
+  sys: Sym,
+  /// Factory, for `TmpTerm` transformation.
+  factory: Factory,
+  /// Candidates not falsified so far.
+  candidates: TmpTermSet,
+}
+
+impl TwoState {
+  /// Mines the two-state candidates of a system.
+  pub fn mk(sys: & Sys, factory: Factory) -> Self {
+    let candidates = mine::two_state(& factory, sys).into_iter().map(
+      |term| TmpTerm::Trm(term)
+    ).collect() ;
+    TwoState {
+      sys: sys.sym().get().clone(),
+      factory: factory,
+      candidates: candidates,
+    }
+  }
+
+  /// Number of candidates left.
+  #[inline]
+  pub fn len(& self) -> usize { self.candidates.len() }
+
+  /// Drops the candidates falsified on the last transition of the trace of
+  /// a base checker. Does nothing if the trace has no transition.
+  pub fn base_check<Base, Step>(& mut self, base: & mut Base) -> Res<()>
+  where Base: BaseTrait<Bool, Step>, Step: StepTrait<Bool, Base> {
+    if base.unroll_len() == 0 { return Ok(()) }
+
+    while ! self.candidates.is_empty() {
+      let terms: Vec<TmpTerm> = self.candidates.iter().cloned().collect() ;
+      match try_chain!(
+        base.k_falsify( terms.clone() )
+        => "[TwoState::base_check] while falsifying candidates"
+      ) {
+        Some(eval) => for term in terms.into_iter() {
+          let term_is_true = try_chain!(
+            eval.eval(& term)
+            => "[TwoState::base_check] \
+              could not evaluate term {:?} in current model", term
+          ) ;
+          if ! term_is_true {
+            self.candidates.remove(& term) ; ()
+          }
+        },
+        None => break,
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Extracts the candidates that are k-inductive and communicates them.
+  pub fn k_split<Base, Step>(
+    & mut self, base: & Base, step: & mut Step, event: & mut Event
+  ) -> Res<()>
+  where Base: BaseTrait<Bool, Step>, Step: StepTrait<Bool, Base> {
+    let err_pref = "[TwoState::k_split]" ;
+
+    let mut map: TmpTermMap<()> = self.candidates.iter().map(
+      |term| ( term.clone(), () )
+    ).collect() ;
+    let invars = try_chain!(
+      step.k_split_two_state(& mut map)
+      => "{} step query", err_pref
+    ) ;
+
+    if ! invars.is_empty() {
+      let mut set = STermSet::with_capacity( invars.len() ) ;
+      for (invar, ()) in invars.into_iter() {
+        let was_there = self.candidates.remove(& invar) ;
+        debug_assert!( was_there ) ;
+        let invar = try_chain!(
+          invar.to_term_safe(& self.factory)
+          => "{} while building two-state invariant", err_pref
+        ) ;
+        set.insert( STerm::Two(invar) ) ; ()
+      }
+      event.invariants_at( & self.sys, set, base.unroll_len() )
+    }
+
+    Ok(())
+  }
+}
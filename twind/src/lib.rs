@@ -53,6 +53,7 @@ impl common::CanRun<conf::Twind> for Twind {
       None => (),
       Some(ref cmd) => solver_conf = solver_conf.cmd(cmd.clone()),
     } ;
+    let solver_conf = event.time_limit(solver_conf) ;
 
     mk_solver_run!(
       solver_conf, conf.smt_log(), "twind", event.factory(),
@@ -79,6 +80,7 @@ fn twind<
     event, Unroller::mk(& sys, & props, solver)
     => "while creating unroller"
   ) ;
+  unroller.assume( event.assumptions() ) ;
 
   // event.log("creating manager, declaring actlits") ;
   let mut props = log_try!(
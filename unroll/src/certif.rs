@@ -0,0 +1,176 @@
+// Copyright 2016 Adrien Champion. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Certificates for k-inductive invariants.
+//!
+//! A certificate is the list of queries showing that a set of invariants is
+//! `k`-inductive, built with an [`Unroller`](../struct.Unroller.html). When
+//! the solver is tee-ed to a file, the file is a self-contained SMT-LIB 2
+//! script: the invariants are `k`-inductive iff all the `check-sat-assuming`
+//! queries of the script are `unsat`.
+//!
+//! The script starts with the base case, the `k` first states of any trace
+//! satisfy the invariants, checked one state at a time. It then resets the
+//! solver and performs the step case: `k` successive states satisfying the
+//! invariants are followed by a state satisfying them.
+
+use term::{ Term, Offset2, STerm, STermSet } ;
+use term::tmp::{ TmpTerm, TmpTermMker } ;
+
+use sys::{ Sys, Prop } ;
+
+use common::SolverTrait ;
+use common::errors::* ;
+
+use Unroller ;
+
+/// Conjunction of some invariants, for the first state of a trace if `init`
+/// and for the next state otherwise. `None` if there is nothing to conjoin.
+fn conj(invs: & STermSet, init: bool) -> Option<TmpTerm> {
+  let mut terms = Vec::with_capacity( invs.len() ) ;
+  for inv in invs.iter() {
+    match * inv {
+      // Two-state invariants hold in the first state.
+      STerm::Two(_) if init => (),
+      STerm::One(ref curr, _) if init => terms.push(
+        TmpTerm::Trm( curr.clone() )
+      ),
+      _ => terms.push( TmpTerm::Trm( inv.next().clone() ) ),
+    }
+  }
+  if terms.is_empty() { None } else { Some( TmpTerm::and(terms) ) }
+}
+
+/// Resets the solver and declares everything.
+fn reset<'a, S: SolverTrait<'a>>(
+  unroller: & mut Unroller<S>, props: & [Prop]
+) -> Res<()> {
+  try_chain!(
+    unroller.solver().reset() => "[Certif] while `reset`ing the solver"
+  ) ;
+  try_chain!(
+    unroller.defclare_funs(props)
+    => "[Certif] while declaring UFs, init and trans"
+  ) ;
+  Ok(())
+}
+
+/// Asserts some invariants at some offset.
+fn assert_invs<'a, S: SolverTrait<'a>>(
+  unroller: & mut Unroller<S>, invs: & STermSet, init: bool, o: & Offset2
+) -> Res<()> {
+  if let Some(conj) = conj(invs, init) {
+    try_chain!(
+      unroller.assert(& conj, o)
+      => "[Certif] while asserting invariants at {}", o
+    )
+  }
+  Ok(())
+}
+
+/// Checks that some invariants hold at some offset. True if the query is
+/// `unsat`.
+fn check_invs<'a, S: SolverTrait<'a>>(
+  unroller: & mut Unroller<S>, invs: & STermSet, init: bool, o: & Offset2
+) -> Res<bool> {
+  let conj = match conj(invs, init) {
+    Some(conj) => conj,
+    None => return Ok(true),
+  } ;
+  let actlit = try_chain!(
+    unroller.fresh_actlit() => "[Certif] while declaring actlit at {}", o
+  ) ;
+  try_chain!(
+    unroller.assert( & actlit.activate_term( conj.tmp_neg() ), o )
+    => "[Certif] while asserting invariant falsification at {}", o
+  ) ;
+  let is_sat = try_chain!(
+    unroller.check_sat_assuming( & [ actlit.name() ] )
+    => "[Certif] during a `check_sat_assuming` query at {}", o
+  ) ;
+  try_chain!(
+    unroller.deactivate(actlit)
+    => "[Certif] while deactivating actlit at {}", o
+  ) ;
+  Ok(! is_sat)
+}
+
+/// Checks that some invariants are `k`-inductive for the system of an
+/// unroller, relative to some lemmas. A `k` of `0` is understood as `1`.
+///
+/// The lemmas are asserted in all the states of the base and step cases,
+/// they should be known invariants of the system. The functions used by
+/// `props` are declared, the properties themselves are not asserted: they
+/// should be in `invs` if they are part of the strengthening. Returns `false`
+/// as soon as one query is `sat`.
+pub fn check_k_inductive<'a, S: SolverTrait<'a>>(
+  unroller: & mut Unroller<S>, props: & [Prop],
+  lemmas: & STermSet, invs: & STermSet, k: usize
+) -> Res<bool> {
+  let k = if k == 0 { 1 } else { k } ;
+
+  // Base case, states `0` to `k - 1`.
+  try!( reset(unroller, props) ) ;
+  let mut o = Offset2::init() ;
+  try_chain!(
+    unroller.assert_init(& o) => "[Certif] while asserting init"
+  ) ;
+  try!( assert_invs(unroller, lemmas, true, & o) ) ;
+  if ! try!( check_invs(unroller, invs, true, & o) ) {
+    return Ok(false)
+  }
+  for _ in 1..k {
+    try_chain!(
+      unroller.unroll(& o) => "[Certif] while unrolling system at {}", o
+    ) ;
+    try!( assert_invs(unroller, lemmas, false, & o) ) ;
+    if ! try!( check_invs(unroller, invs, false, & o) ) {
+      return Ok(false)
+    }
+    o = o.nxt()
+  }
+
+  // Step case, invariants hold from `0` to `k - 1`.
+  try!( reset(unroller, props) ) ;
+  let mut o = Offset2::init() ;
+  try_chain!(
+    unroller.declare_svars( o.curr() )
+    => "[Certif] while declaring state variables"
+  ) ;
+  try!( assert_invs(unroller, lemmas, true, & o) ) ;
+  try!( assert_invs(unroller, invs, true, & o) ) ;
+  for _ in 1..k {
+    try_chain!(
+      unroller.unroll(& o) => "[Certif] while unrolling system at {}", o
+    ) ;
+    try!( assert_invs(unroller, lemmas, false, & o) ) ;
+    try!( assert_invs(unroller, invs, false, & o) ) ;
+    o = o.nxt()
+  }
+  try_chain!(
+    unroller.unroll(& o) => "[Certif] while unrolling system at {}", o
+  ) ;
+  try!( assert_invs(unroller, lemmas, false, & o) ) ;
+  check_invs(unroller, invs, false, & o)
+}
+
+/// Creates an unroller and checks that some invariants are `k`-inductive for
+/// a system under some assumptions.
+///
+/// See [`check_k_inductive`](fn.check_k_inductive.html).
+pub fn certify<'a, S: SolverTrait<'a>>(
+  solver: S, sys: & Sys, props: & [Prop], assumptions: & [Term],
+  invs: & STermSet, k: usize
+) -> Res<bool> {
+  let mut unroller = try_chain!(
+    Unroller::mk(sys, props, solver) => "[Certif] while creating unroller"
+  ) ;
+  unroller.assume(assumptions) ;
+  check_k_inductive(& mut unroller, props, & STermSet::new(), invs, k)
+}
@@ -18,14 +18,14 @@ extern crate common ;
 #[macro_use]
 extern crate error_chain ;
 
-use std::collections::{ HashSet, HashMap } ;
+use std::collections::{ HashSet, HashMap, BTreeSet } ;
 use std::hash::Hash ;
 use std::cmp::Eq ;
 use std::fmt::Display ;
 use std::iter::{ Iterator, IntoIterator } ;
 
 use term::{
-  Type, Sym, Term, Model,
+  Type, Sym, Term, Cst, Model,
   Offset, Offset2, STerm, STermSet, real_term
 } ;
 use term::smt::{
@@ -36,9 +36,14 @@ use term::tmp::* ;
 
 use sys::{ Prop, Sys, Callable } ;
 
+use term::smt::internals::SolverBasic ;
+use term::smt::errors::ErrorKind ;
+
 use common::SolverTrait ;
 use common::errors::* ;
 
+pub mod certif ;
+
 /// Manages some properties.
 pub type PropManager = TermManager<Sym> ;
 /// Manages some invariants.
@@ -89,6 +94,54 @@ impl<T: Clone> Opt<T> {
   }
 }
 
+/// Adds the enumerated sorts appearing in a type to a set.
+fn enums_of(typ: & Type, set: & mut BTreeSet<Type>) {
+  match * typ {
+    Type::Enum(_) => {
+      set.insert( typ.clone() ) ; ()
+    },
+    Type::Array(ref idx, ref elm) => {
+      enums_of(idx, set) ;
+      enums_of(elm, set)
+    },
+    _ => (),
+  }
+}
+
+/// Adds the enumerated sorts appearing in the signature of a function to a
+/// set.
+fn fun_enums(fun: & Callable, set: & mut BTreeSet<Type>) {
+  use sys::real_sys::Callable::* ;
+  match * * fun {
+    Dec(ref fun_dec) => {
+      for typ in fun_dec.sig() {
+        enums_of(typ.get(), set)
+      }
+      enums_of(fun_dec.typ().get(), set)
+    },
+    Def(ref fun_def) => {
+      for & (_, ref typ) in fun_def.args() {
+        enums_of(typ.get(), set)
+      }
+      enums_of(fun_def.typ().get(), set)
+    },
+  }
+}
+
+/// Adds the enumerated sorts used by a system to a set: the ones of its state
+/// variables, of the functions it calls and of its sub-systems.
+fn sys_enums(sys: & Sys, set: & mut BTreeSet<Type>) {
+  for & (_, ref typ) in sys.init().1.iter() {
+    enums_of(typ, set)
+  }
+  for fun in sys.calls().get() {
+    fun_enums(fun, set)
+  }
+  for & (ref sub, _) in sys.subsys() {
+    sys_enums(sub, set)
+  }
+}
+
 /// Defines the init and trans predicates of a system.
 fn define<'a, S: SolverTrait<'a>>(
   sys: & sys::Sys, solver: & mut S, o: & Offset2
@@ -132,6 +185,9 @@ fn define<'a, S: SolverTrait<'a>>(
 /// - [`unroll`](struct.Unroller.html#method.unroll)
 /// - [`unroll_init`](struct.Unroller.html#method.unroll_init)
 /// - [`unroll_bak`](struct.Unroller.html#method.unroll_bak)
+///
+/// Assumptions (see [`assume`](struct.Unroller.html#method.assume)) are
+/// asserted by `assert_init` and at each unrolling.
 pub struct Unroller<S> {
   /// The system to unroll.
   sys: Sys,
@@ -139,6 +195,8 @@ pub struct Unroller<S> {
   solver: S,
   /// The invariants known on the system.
   invs: STermSet,
+  /// The assumptions the system is verified under.
+  assumptions: Vec<Term>,
   // /// Offset of the beginning of the trace.
   // beg_k: Offset2,
   // /// Offset of the end of the trace.
@@ -159,6 +217,7 @@ impl<
       sys: sys.clone(),
       solver: solver,
       invs: STermSet::with_capacity(107),
+      assumptions: vec![],
       // beg_k: Offset2::init(),
       // end_k: Offset2::init().pre(),
       act_factory: ActlitFactory::mk(),
@@ -189,6 +248,34 @@ impl<
   /// Accessor for the invariants.
   #[inline]
   pub fn invs(& self) -> & STermSet { & self.invs }
+  /// Accessor for the assumptions.
+  #[inline]
+  pub fn assumptions(& self) -> & [Term] { & self.assumptions }
+
+  /// Memorizes some assumptions. **Does not assert anything.**
+  ///
+  /// Assumptions are asserted by `assert_init` in the initial state, and by
+  /// all the unrolling functions in both states of the transition they
+  /// unroll.
+  #[inline]
+  pub fn assume(& mut self, assumptions: & [Term]) {
+    self.assumptions.extend( assumptions.iter().cloned() )
+  }
+
+  /// Asserts the assumptions in some state.
+  fn assert_assumptions(& mut self, off: & Offset) -> Res<()> {
+    // Assumptions are over the current state.
+    let o = Offset2::mk( off.clone(), off.nxt() ) ;
+    for assumption in self.assumptions.iter() {
+      try!(
+        chain_err!(
+          unroll, format!("while asserting assumption at {}", off) =>
+          self.solver.assert(assumption, & o)
+        )
+      )
+    }
+    Ok(())
+  }
 
   /// Creates and declares a fresh activation literal.
   #[inline]
@@ -329,11 +416,55 @@ impl<
     Ok(())
   }
 
-  /// Declares/defines UFs, functions, and system init/trans predicates.
+  /// Declares the enumerated sorts used by the system and some properties as
+  /// datatypes.
+  fn declare_enums(& mut self, props: & [ Prop ]) -> Res<()> {
+    let mut enums = BTreeSet::new() ;
+    sys_enums(& self.sys, & mut enums) ;
+    for prop in props.iter() {
+      for fun in prop.calls().get() {
+        fun_enums(fun, & mut enums)
+      }
+    }
+    for typ in enums.iter() {
+      if let Type::Enum(ref e) = * typ {
+        // `rsmt2` has no datatype declaration, writing it directly.
+        let mut decl = format!("(declare-datatypes ( ({} 0) ) ( (", e.name()) ;
+        for cons in e.cons() {
+          decl.push_str( & format!(" ({})", cons) )
+        }
+        decl.push_str(" ) ))\n") ;
+        try!(
+          chain_err!(
+            unroll, "during enumerated sort declaration" =>
+            self.solver.write(
+              |w| w.write_all( decl.as_bytes() ).map_err(
+                |e| ErrorKind::IoError( format!("{}", e) ).into()
+              )
+            )
+          )
+        ) ;
+        if self.solver.solver().conf().get_parse_success() {
+          try!(
+            chain_err!(
+              unroll, "during enumerated sort declaration" =>
+              self.solver.parse_success()
+            )
+          )
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Declares enumerated sorts, declares/defines UFs, functions, and system
+  /// init/trans predicates.
   pub fn defclare_funs(& mut self, props: & [ Prop ]) -> Res<()> {
     // Will not really be used.
     let offset = Offset2::init() ;
 
+    try!( self.declare_enums(props) ) ;
+
     let mut known = HashSet::with_capacity(7) ;
     let mut rest = HashSet::with_capacity(7) ;
     // Declaring UFs and defining functions.
@@ -436,13 +567,14 @@ impl<
     Ok(())
   }
 
-  /// Asserts the init predicate. **Declares** state variables in the current
-  /// offset.
+  /// Asserts the init predicate and the assumptions. **Declares** state
+  /// variables in the current offset.
   #[inline]
   pub fn assert_init(& mut self, o: & Offset2) -> Res<()> {
     try!(
       self.declare_svars( o.curr() )
     ) ;
+    try!( self.assert_assumptions( o.curr() ) ) ;
     chain_err!(
       unroll, "during init predicate assertion" => self.solver.assert(
         self.sys.init_term(), o
@@ -453,6 +585,10 @@ impl<
   /// Unrolls the transition relation once. **Declares** state variables in
   /// the next offset if the offset is not reversed, in the current offset
   /// otherwise (for backward unrolling).
+  ///
+  /// Asserts the assumptions in the current and next states, so that they
+  /// hold in all the states of the unrolling, including the last one and the
+  /// ones declared with `declare_svars`.
   fn just_unroll(& mut self, o: & Offset2) -> Res<()> {
     let off = if o.is_rev() { o.curr() } else { o.next() } ;
     try!(
@@ -460,6 +596,8 @@ impl<
         unroll, format!("during unrolling at {}", o) => self.declare_svars(off)
       )
     ) ;
+    try!( self.assert_assumptions( o.curr() ) ) ;
+    try!( self.assert_assumptions( o.next() ) ) ;
     chain_err!(
       unroll, format!("during unrolling at {}", o) => self.solver.assert(
         self.sys.trans_term(), o
@@ -682,6 +820,35 @@ impl<
     }
     Ok(model)
   }
+
+  /// The values of the state variables of the system in some state.
+  pub fn get_state(& mut self, o: & Offset) -> Res< HashMap<Sym, Cst> > {
+    use term::{ VarMaker, State } ;
+    let mut vars = Vec::with_capacity( self.sys.state().len() ) ;
+    for & (ref sym, _) in self.sys.state().args().iter() {
+      vars.push(
+        self.solver.parser().svar( sym.get().clone(), State::Curr )
+      )
+    }
+    let values = try!(
+      self.solver.get_values( & vars, & Offset2::mk(* o, o.nxt()) ).chain_err(
+        || format!("[Unroller] while getting state at {}", o)
+      )
+    ) ;
+    let mut state = HashMap::with_capacity( values.len() ) ;
+    for ( (term, _), val ) in values.into_iter() {
+      if let real_term::Term::V(ref var) = * term.get() {
+        state.insert( var.sym().clone(), val ) ; ()
+      } else {
+        bail!(
+          format!(
+            "unexpected term {}, all terms should be variables", term
+          )
+        )
+      }
+    }
+    Ok(state)
+  }
 }
 
 